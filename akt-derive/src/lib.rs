@@ -0,0 +1,57 @@
+//! Derive macros for [akt](https://docs.rs/akt), enabled with its `derive`
+//! feature and re-exported from there.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, LitStr, Type};
+
+/// Implements `akt::Message`.
+///
+/// The result type is given with `#[rtype(result = "...")]` and defaults to
+/// `()`. Generic messages get the impl for the same generics and bounds.
+///
+/// ```ignore
+/// #[derive(Message)]
+/// #[rtype(result = "Option<u64>")]
+/// struct Lookup(String);
+///
+/// #[derive(Message)]
+/// struct Ping;
+/// ```
+#[proc_macro_derive(Message, attributes(rtype))]
+pub fn derive_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_message(input) {
+        Ok(expanded) => expanded.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_message(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut result: Option<Type> = None;
+
+    for attribute in input.attrs.iter().filter(|a| a.path().is_ident("rtype")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("result") {
+                let value: LitStr = meta.value()?.parse()?;
+                result = Some(value.parse()?);
+
+                Ok(())
+            } else {
+                Err(meta.error("expected `result = \"...\"`"))
+            }
+        })?;
+    }
+
+    let result = result.unwrap_or_else(|| syn::parse_quote!(()));
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::akt::Message for #name #type_generics #where_clause {
+            type Result = #result;
+        }
+    })
+}
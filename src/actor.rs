@@ -1,91 +1,1303 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{self, Display},
+    future::{poll_fn, Future},
+    mem,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{self, Poll},
+    thread,
+    time::Duration,
+};
+
 use async_trait::async_trait;
 
 use tokio::{
+    runtime::Handle,
     select,
-    sync::mpsc::{self},
+    sync::{mpsc, oneshot, Notify},
+    task::{AbortHandle, JoinHandle},
+    time::{Instant, Interval, MissedTickBehavior},
 };
 
 use crate::{
     address::{Address, UnboundedAddress},
-    handler::Envelope,
-    ActorState, Context,
+    audit::{AuditOutcome, AuditRecord},
+    builder::ActorBuilder,
+    control::ControlAddress,
+    handler::{CatchUnwind, Envelope},
+    mailbox::{Mailbox, PublicMailbox},
+    registry::registry,
+    status::{Status, StoppedGuard},
+    ActorState, ConcurrencyLimiter, Context, Journal, PanicReason, Recipient, SpawnLimiter,
+    StopReason, System, TapStream,
 };
 
 /// Core trait that should be implemented for each Actor.
 #[async_trait]
 pub trait Actor: Send + Sized + 'static {
+    /// Capacity of the public mailbox used by [Actor::run], see
+    /// [Actor::mailbox_capacity] to size it at runtime. Like any mailbox
+    /// capacity it must be greater than zero.
+    const DEFAULT_MAILBOX_CAPACITY: usize = 16;
+
+    /// How many messages sent with
+    /// [Address::send_concurrent](crate::Address::send_concurrent) may be
+    /// handled at once
+    const MAX_CONCURRENCY: usize = 1;
+
+    /// Capacity of the private mailbox, unbounded by default.
+    ///
+    /// Bounding it keeps a handler that keeps notifying itself from eating
+    /// up all the memory. Sends to a full private mailbox fail right away
+    /// with [FailedToDeliver](crate::FailedToDeliver) instead of waiting,
+    /// since a handler waiting for room in its own mailbox would deadlock.
+    /// Stop requests and items of streams attached with
+    /// [Context::add_stream] are not limited.
+    const PRIVATE_MAILBOX_CAPACITY: Option<usize> = None;
+
+    /// Depth of the public mailbox above which [Actor::on_mailbox_pressure]
+    /// is called, the check is disabled by default
+    const HIGH_WATER_MARK: Option<usize> = None;
+
+    /// Period [Actor::on_interval] is called with, disabled by default
+    const INTERVAL: Option<Duration> = None;
+
+    /// Whether handlers are cancelled once the sender drops the future of
+    /// the response.
+    ///
+    /// Cancelling saves work nobody waits for, which suits reads. Actors
+    /// running commands with side effects may set it to `false` to always
+    /// run handlers to completion, discarding results nobody waits for.
+    /// [Message::CANCEL_ON_DISCONNECT](crate::Message::CANCEL_ON_DISCONNECT)
+    /// does the same for a single message type.
+    const CANCEL_ON_DISCONNECT: bool = true;
+
+    /// Whether public messages sent before [Actor::try_start] has completed
+    /// are rejected instead of being handled once the actor is started.
+    ///
+    /// Messages buffered meanwhile are dropped right before the actor is
+    /// started, and the requests among them fail with
+    /// [ActorSendError::NotReady](crate::ActorSendError::NotReady), so
+    /// callers could retry against a ready instance. Only the reply channel
+    /// of [Address::send_with](crate::Address::send_with) has no room for
+    /// the error and is just dropped. Persisted messages are acknowledged
+    /// as they are rejected. Messages sent through the priority mailbox,
+    /// [Address::stop](crate::Address::stop) and
+    /// [Address::flush](crate::Address::flush) are still honored.
+    const REJECT_UNTIL_STARTED: bool = false;
+
+    /// Which of the private and public mailboxes is preferred when both
+    /// have messages waiting, see [ActorBuilder::mailbox_policy] to pick it
+    /// per run
+    const MAILBOX_POLICY: MailboxPolicy = MailboxPolicy::PrivateFirst;
+
+    /// Most futures piped with [Context::pipe_bounded] running at once
+    const MAX_PIPED_TASKS: usize = 64;
+
+    /// How long the actor may run without yielding before
+    /// [Context::yield_if_needed] yields to other tasks, 10ms by default.
+    ///
+    /// Lower it to keep the latency of other tasks on the same runtime
+    /// thread down, raise it to spend less time switching tasks.
+    const YIELD_BUDGET: Duration = Duration::from_millis(10);
+
+    /// Longest time [Actor::on_stopping] and [Actor::on_stopped] may run
+    /// for, unlimited by default.
+    ///
+    /// A hook running longer is abandoned at its current await point and
+    /// [Actor::on_shutdown_timeout] runs instead. An abandoned
+    /// [Actor::on_stopping] counts as agreeing to stop.
+    const SHUTDOWN_TIMEOUT: Option<Duration> = None;
+
+    /// Capacity of the public mailbox consulted by [Actor::run] and the
+    /// other ways to run the actor without an explicit capacity.
+    ///
+    /// Returns [Actor::DEFAULT_MAILBOX_CAPACITY] by default, override it
+    /// with [default_mailbox_capacity] to scale it with the core count.
+    fn mailbox_capacity(&self) -> usize {
+        Self::DEFAULT_MAILBOX_CAPACITY
+    }
+
     /// Runs actor consuming it and returning its address
     fn run(self) -> Address<Self> {
-        // Public mailbox is bounded
-        let (addr_tx, mut addr_rx) = mpsc::channel::<Box<dyn Envelope<Self> + Send>>(16);
+        let capacity = self.mailbox_capacity();
 
-        // Private mailbox is unbounded
-        let (private_addr_tx, mut private_addr_rx) =
-            mpsc::unbounded_channel::<Box<dyn Envelope<Self> + Send>>();
+        self.run_with_capacity(capacity)
+    }
 
-        // Public address
-        // Intended to be used by anyone
-        let address = Address { tx: addr_tx };
+    /// Runs actor with the given public mailbox capacity
+    ///
+    /// Capacity affects only the public bounded mailbox, see
+    /// [Actor::PRIVATE_MAILBOX_CAPACITY] for the private one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    fn run_with_capacity(self, capacity: usize) -> Address<Self> {
+        self.builder().mailbox_capacity(capacity).run()
+    }
 
-        // Private address
-        // Intended to be used by actor that owned it and actors spawned and controlled by it
-        let private_address = UnboundedAddress {
-            tx: private_addr_tx,
-        };
+    /// Runs actor with a custom public mailbox, see [Mailbox]
+    fn run_with_mailbox<M: Mailbox<Self>>(self, mailbox: M) -> Address<Self> {
+        self.builder().mailbox(mailbox).run()
+    }
 
-        let weak_address = address.downgrade();
+    /// Runs actor keeping a copy of its initial state, which
+    /// [Context::spawn_self] starts replicas from
+    fn run_replicable(self) -> Address<Self>
+    where
+        Self: Clone,
+    {
+        self.builder().replicable().run()
+    }
 
-        tokio::spawn(async move {
-            let mut actor = self;
+    /// Returns a builder to configure how the actor is run, see
+    /// [ActorBuilder]
+    fn builder(self) -> ActorBuilder<Self> {
+        ActorBuilder::new(self)
+    }
 
-            let mut context = Context::new(weak_address, private_address, ActorState::Starting);
+    /// Runs actor on the given runtime instead of the current one.
+    ///
+    /// Unlike [Actor::run] it could be called outside of a runtime context.
+    fn run_on(self, handle: Handle) -> Address<Self> {
+        self.builder().on(handle).run()
+    }
 
-            actor.on_start(&mut context).await;
+    /// Runs actor returning its address and a handle to forcibly abort it.
+    ///
+    /// Aborting is the last resort for actors stuck in a non-cooperative
+    /// handler. The task is dropped at its current await point, so
+    /// [Actor::on_stopped] is never called.
+    fn run_abortable(self) -> (Address<Self>, AbortHandle) {
+        let (address, handle) = self.run_with_handle();
 
-            context.state = ActorState::Started;
+        (address, handle.abort_handle())
+    }
 
-            loop {
-                if context.state == ActorState::Stopping
-                    && actor.on_stopping(&mut context).await == true
-                {
-                    break;
-                }
+    /// Runs actor returning its address and the handle of its task.
+    ///
+    /// The handle resolves once the message loop is finished and
+    /// [Actor::on_stopped] has completed.
+    fn run_with_handle(self) -> (Address<Self>, JoinHandle<()>) {
+        self.builder().run_with_handle()
+    }
 
-                select! {
-                    biased;
-                    
-                    Some(message) = private_addr_rx.recv() => {
-                        message.handle(&mut actor, &mut context).await;
-                    }
-                    response = addr_rx.recv() => match response {
-                        Some(message) => { message.handle(&mut actor, &mut context).await },
-                        None => break
-                    }
-                }
-            }
+    /// Runs actor returning its address and the address of its control
+    /// mailbox, see [ControlAddress]
+    fn run_with_control(self) -> (Address<Self>, ControlAddress<Self>) {
+        let spawned = self.builder().spawn();
+
+        (spawned.address, spawned.control)
+    }
 
-            actor.on_stopped(&mut context).await;
+    /// Runs actor with access to the shared state of the system, see
+    /// [System]
+    fn run_in_system<S: Send + Sync + 'static>(self, system: System<S>) -> Address<Self> {
+        self.builder().system(system).run()
+    }
 
-            context.state = ActorState::Stopped;
-        });
+    /// Runs actor returning its address and a future resolving once
+    /// [Actor::on_start] has completed.
+    ///
+    /// Messages sent before that are buffered as usual, the future is meant
+    /// for readiness checks. It resolves to an error if
+    /// [Actor::try_start] failed or the startup panicked.
+    fn run_ready(self) -> (Address<Self>, Ready) {
+        let spawned = self.builder().spawn();
 
-        address
+        (spawned.address, spawned.ready)
+    }
+
+    /// Runs actor returning its address and a stream describing every
+    /// message the actor handles, in the order they are handled.
+    ///
+    /// Meant for debugging message flow and building audit logs. The stream
+    /// is unbounded, so it should be consumed for as long as the actor runs.
+    /// It ends once the actor is stopped.
+    fn run_tapped(self) -> (Address<Self>, TapStream) {
+        let spawned = self.builder().tapped().spawn();
+
+        (spawned.address, spawned.tap.expect("tap is requested"))
+    }
+
+    /// Name the actor is registered under in the global
+    /// [Registry](crate::Registry) once run
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Time after which a handler that is still running is reported as
+    /// stuck.
+    ///
+    /// Unlike [Actor::message_timeout] the handler is not cancelled, instead
+    /// [Address::stuck_on] names the message type until the handler
+    /// completes.
+    fn watchdog_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Recipient of an [AuditRecord] sent after each handled message
+    fn audit_recipient(&self) -> Option<Recipient<AuditRecord>> {
+        None
+    }
+
+    /// Limiter shared with other actors to bound how many handlers run
+    /// concurrently, asked for before each message
+    fn limiter(&self) -> Option<ConcurrencyLimiter> {
+        None
+    }
+
+    /// Longest time a single message may be handled for.
+    ///
+    /// A handler running longer is cancelled at its current await point and
+    /// its result is never sent, so the caller gets
+    /// [ActorSendError::FailedToGetResponse](crate::ActorSendError::FailedToGetResponse).
+    /// Applies to notifications as well.
+    fn message_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Hook that runs before [Actor::try_start] to replay messages that were
+    /// persisted in the [Actor::journal] but never acknowledged, e.g. by
+    /// notifying the actor itself through the context.
+    async fn recover(&mut self, _context: &mut Context<Self>) {}
+
+    /// Journal persisting messages delivered with
+    /// [Address::send_persisted](crate::Address::send_persisted) or
+    /// [Address::notify_persisted](crate::Address::notify_persisted).
+    ///
+    /// Read once when the actor is run. Without a journal these messages
+    /// are delivered like any other.
+    fn journal(&self) -> Option<Arc<dyn Journal>> {
+        None
     }
 
     /// Hook that runs just before the first message is processed
     async fn on_start(&mut self, _context: &mut Context<Self>) {}
 
+    /// Fallible version of [Actor::on_start], calls it by default.
+    ///
+    /// If an error is returned the actor never handles a message:
+    /// [Actor::on_stopped] runs right away and the error is passed to the
+    /// future returned by [Actor::run_ready].
+    async fn try_start(
+        &mut self,
+        context: &mut Context<Self>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.on_start(context).await;
+
+        Ok(())
+    }
+
     /// Hooks that runs just before the actor job is finished
     async fn on_stopped(&mut self, _context: &mut Context<Self>) {}
 
-    /// Hook that runs if [Context::stop] method is called.
+    /// Hook that runs after a handler panicked.
+    ///
+    /// The panic is caught and the actor keeps processing next messages.
+    /// The panicked message is dropped, so its sender gets
+    /// [ActorSendError::FailedToGetResponse](crate::ActorSendError::FailedToGetResponse).
+    /// The panic itself is already reported by the standard panic hook.
+    async fn on_panic(&mut self, _reason: PanicReason, _context: &mut Context<Self>) {}
+
+    /// Hook that runs after each handled message with the time spent in its
+    /// handler.
+    ///
+    /// Panicked and timed out handlers are reported too, while messages
+    /// handled by a [ConcurrentHandler](crate::ConcurrentHandler) are not.
+    /// Use [Actor::audit_recipient] to also get the message type and outcome.
+    async fn on_handled(&mut self, _duration: Duration, _context: &mut Context<Self>) {}
+
+    /// Hook that runs after a handler was cancelled because the caller
+    /// stopped waiting for the response.
+    ///
+    /// The handler is dropped at its current await point, so it may have
+    /// run partially or not at all.
+    async fn on_message_cancelled(&mut self, _context: &mut Context<Self>) {}
+
+    /// Hook that runs on every response right before it is sent to the
+    /// caller, whichever way the message was sent, remote responses before
+    /// they are encoded.
+    ///
+    /// Decorates responses uniformly without repeating it in each handler,
+    /// the ones of interest are picked by downcasting. Replies taken over
+    /// with [Context::take_reply] are sent by the handler itself and
+    /// [ConcurrentHandler](crate::ConcurrentHandler)s don't get the actor
+    /// exclusively, so their responses are not passed here.
+    ///
+    /// ```
+    /// # use akt::{Actor, Context};
+    /// # use std::any::Any;
+    /// struct Tagged {
+    ///     request: u64,
+    ///     body: String,
+    /// }
+    ///
+    /// struct Api {
+    ///     requests: u64,
+    /// }
+    ///
+    /// impl Actor for Api {
+    ///     fn map_response(&mut self, response: &mut dyn Any, _context: &mut Context<Self>) {
+    ///         if let Some(tagged) = response.downcast_mut::<Tagged>() {
+    ///             self.requests += 1;
+    ///             tagged.request = self.requests;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn map_response(&mut self, _response: &mut dyn Any, _context: &mut Context<Self>) {}
+
+    /// Hook that runs when a notification, a message nobody waits the result
+    /// of, is handled with an error.
+    ///
+    /// The error is extracted from the result with
+    /// [Message::notification_error](crate::Message::notification_error),
+    /// so by default there is nothing to report.
+    async fn on_notification_error(
+        &mut self,
+        _error: Box<dyn Error + Send>,
+        _context: &mut Context<Self>,
+    ) {
+    }
+
+    /// Hook that runs when more than [Actor::HIGH_WATER_MARK] messages are
+    /// left in the public mailbox after receiving one.
+    ///
+    /// It runs before the received message is handled, once per received
+    /// message for as long as the mailbox stays above the mark. `depth` is
+    /// the number of messages still buffered.
+    async fn on_mailbox_pressure(&mut self, _depth: usize, _context: &mut Context<Self>) {}
+
+    /// Hook that runs once the public mailbox is found empty, a good place
+    /// for maintenance that should run only while there is no pending work.
+    ///
+    /// It is edge-triggered: after it runs, it runs again only once
+    /// [Actor::on_busy] has. Private and control messages don't affect it.
+    async fn on_idle(&mut self, _context: &mut Context<Self>) {}
+
+    /// Hook that runs when a public message is received after
+    /// [Actor::on_idle], right before the message is handled
+    async fn on_busy(&mut self, _context: &mut Context<Self>) {}
+
+    /// Hook that runs every [Actor::INTERVAL] between messages, the first
+    /// time one period after the actor is started.
+    ///
+    /// Unlike [Context::notify_interval] it needs neither a message nor a
+    /// round-trip through the mailbox. Ticks are delayed while a message is
+    /// handled, missed ones are not made up for.
+    async fn on_interval(&mut self, _context: &mut Context<Self>) {}
+
+    /// Hook that runs when [Actor::on_stopping] or [Actor::on_stopped],
+    /// named by `hook`, was abandoned after running for longer than
+    /// [Actor::SHUTDOWN_TIMEOUT].
+    ///
+    /// It is not limited by the timeout itself, so keep it short.
+    async fn on_shutdown_timeout(&mut self, _hook: &'static str, _context: &mut Context<Self>) {}
+
+    /// Hook that runs when the actor is about to stop.
     ///
     /// Return false from this hook to prevent actor from being stopped.
-    async fn on_stopping(&mut self, _context: &mut Context<Self>) -> bool {
+    /// The returned value is ignored for [StopReason::AllAddressesDropped]
+    /// since nobody could send the actor new messages anyway.
+    async fn on_stopping(&mut self, _reason: StopReason, _context: &mut Context<Self>) -> bool {
         true
     }
 }
 
+/// Actor addresses and the message loop future that is yet to be spawned
+pub(crate) struct Prepared<A: Actor, J> {
+    pub address: Address<A>,
+    pub control_address: ControlAddress<A>,
+    /// Resolves once [Actor::try_start] has completed
+    pub started: oneshot::Receiver<Result<(), StartError>>,
+    pub job: J,
+}
+
+/// Creates actor mailboxes along with the public, private and control
+/// addresses
+pub(crate) fn open_mailboxes<A: Actor>(
+    capacity: usize,
+    journal: Option<Arc<dyn Journal>>,
+) -> (
+    Address<A>,
+    UnboundedAddress<A>,
+    UnboundedAddress<A>,
+    Mailboxes<A>,
+) {
+    assert!(capacity > 0, "mailbox capacity must be greater than zero");
+
+    // Public mailbox is bounded
+    let (addr_tx, addr_rx) = mpsc::channel::<Box<dyn Envelope<A> + Send>>(capacity);
+
+    // Priority mailbox is bounded the same way and polled before the public one
+    let (priority_tx, priority_rx) = mpsc::channel::<Box<dyn Envelope<A> + Send>>(capacity);
+
+    // Private mailbox is unbounded unless the actor limits it, the limit is
+    // enforced by the address since a bounded channel would make the
+    // actor wait for room in its own mailbox
+    let (private_addr_tx, private_addr_rx) =
+        mpsc::unbounded_channel::<Box<dyn Envelope<A> + Send>>();
+
+    // Control mailbox is unbounded and has the highest priority
+    let (control_tx, control_rx) = mpsc::unbounded_channel::<Box<dyn Envelope<A> + Send>>();
+
+    let control_address = UnboundedAddress {
+        tx: control_tx,
+        len: Arc::new(AtomicUsize::new(0)),
+        limit: usize::MAX,
+        room: Arc::new(Notify::new()),
+    };
+
+    let (alive, addresses) = oneshot::channel();
+
+    // Public address
+    // Intended to be used by anyone
+    let address = Address {
+        tx: addr_tx,
+        priority_tx,
+        control: Some(ControlAddress::new(control_address.clone())),
+        status: Arc::new(Status {
+            journal,
+            ..Status::default()
+        }),
+        alive: Arc::new(alive),
+    };
+
+    // Private address
+    // Intended to be used by actor that owned it and actors spawned and controlled by it
+    let private_address = UnboundedAddress {
+        tx: private_addr_tx,
+        len: Arc::new(AtomicUsize::new(0)),
+        limit: A::PRIVATE_MAILBOX_CAPACITY.unwrap_or(usize::MAX),
+        room: Arc::new(Notify::new()),
+    };
+
+    let mailboxes = Mailboxes {
+        control: control_rx,
+        control_len: control_address.len.clone(),
+        private: private_addr_rx,
+        private_len: private_address.len.clone(),
+        private_room: private_address.room.clone(),
+        priority: priority_rx,
+        public: PublicMailbox::new(addr_rx),
+        addresses: Some(addresses),
+        policy: A::MAILBOX_POLICY,
+        source: MessageSource::Public,
+    };
+
+    (address, private_address, control_address, mailboxes)
+}
+
+/// Creates actor mailboxes and the message loop future driving the actor
+pub(crate) fn prepare<A: Actor>(
+    actor: A,
+    capacity: usize,
+) -> Prepared<A, impl Future<Output = ()> + Send + 'static> {
+    prepare_with(actor, capacity, |_| {})
+}
+
+/// Same as [prepare], `setup` configures the context before the actor is
+/// started
+pub(crate) fn prepare_with<A: Actor>(
+    actor: A,
+    capacity: usize,
+    setup: impl FnOnce(&mut Context<A>) + Send + 'static,
+) -> Prepared<A, impl Future<Output = ()> + Send + 'static> {
+    let (address, private_address, control_address, mut mailboxes) =
+        open_mailboxes(capacity, actor.journal());
+
+    if let Some(name) = actor.name() {
+        registry().register_named(name, &address);
+    }
+
+    let weak_address = address.downgrade();
+
+    let job_control_address = control_address.clone();
+
+    let (started_tx, started) = oneshot::channel();
+
+    let status = address.status.clone();
+    let stopped = StoppedGuard(status.clone());
+
+    let job = async move {
+        let _stopped = stopped;
+        let mut actor = actor;
+        let mut context = Context::new(
+            weak_address,
+            private_address,
+            ControlAddress::new(job_control_address),
+            ActorState::Starting,
+        );
+
+        setup(&mut context);
+
+        if let Some(mailbox) = context.mailbox.take() {
+            mailboxes.public.set_custom(mailbox);
+        }
+
+        if let Some(policy) = context.mailbox_policy.take() {
+            mailboxes.policy = policy;
+        }
+
+        actor.recover(&mut context).await;
+
+        match actor.try_start(&mut context).await {
+            Ok(()) => {
+                let kept = if A::REJECT_UNTIL_STARTED {
+                    mailboxes.reject_public().await
+                } else {
+                    Vec::new()
+                };
+
+                context.state = ActorState::Started;
+                status.set_state(ActorState::Started);
+
+                let _ = started_tx.send(Ok(()));
+
+                for message in kept {
+                    context.source = MessageSource::Public;
+                    dispatch(message, &mut actor, &mut context).await;
+                }
+
+                serve(&mut actor, &mut context, &mut mailboxes).await;
+            }
+            Err(error) => {
+                context.state = ActorState::Stopping;
+                status.set_state(ActorState::Stopping);
+                context.cancellation.cancel();
+
+                let _ = started_tx.send(Err(StartError::Failed(error)));
+            }
+        }
+
+        if within_shutdown_timeout::<A, _>(actor.on_stopped(&mut context))
+            .await
+            .is_none()
+        {
+            actor.on_shutdown_timeout("on_stopped", &mut context).await;
+        }
+
+        context.finalize().await;
+
+        context.stop_linked();
+
+        context.state = ActorState::Stopped;
+
+        // The context goes last, dropping it tells the shutdown coordinator
+        // the actor is gone
+        drop(mailboxes);
+        drop(actor);
+    };
+
+    #[cfg(feature = "deadlock-detection")]
+    let job = crate::deadlock::scoped::<A, _>(address.id(), job);
+
+    Prepared {
+        address,
+        control_address: ControlAddress::new(control_address),
+        started,
+        job,
+    }
+}
+
+/// Runs the message loop until the actor is stopped
+async fn serve<A: Actor>(actor: &mut A, context: &mut Context<A>, mailboxes: &mut Mailboxes<A>) {
+    // Message that ended the last concurrent batch, `Some(None)` if the
+    // public mailbox was found closed
+    let mut pending = None;
+
+    // When the last message was handled, see `Context::stop_after`
+    let mut last_activity = Instant::now();
+
+    // Whether the public mailbox was found empty, see `Actor::on_idle`
+    let mut idle = false;
+
+    let mut interval = A::INTERVAL.map(|period| {
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        interval
+    });
+
+    loop {
+        // Nothing to agree on in `on_stopping`, the actor never started
+        if context.start_failed {
+            context.publish_state(ActorState::Stopping);
+            context.cancellation.cancel();
+
+            break;
+        }
+
+        // Envelopes send their reply before returning, so any state
+        // change requested by the last handler is honored only after
+        // the caller got its response.
+        if context.state == ActorState::Stopping {
+            let stopping = actor.on_stopping(StopReason::Requested, context);
+
+            let agreed = match within_shutdown_timeout::<A, _>(stopping).await {
+                Some(agreed) => agreed,
+                None => {
+                    actor.on_shutdown_timeout("on_stopping", context).await;
+
+                    true
+                }
+            };
+
+            if agreed {
+                context.publish_state(ActorState::Stopping);
+
+                if context.wait_for_replies {
+                    for taken in context.taken_replies.drain(..) {
+                        let _ = taken.await;
+                    }
+                }
+
+                context.cancellation.cancel();
+
+                if context.drain_on_stop {
+                    mailboxes.close_public();
+
+                    if let Some(Some(message)) = pending.take() {
+                        context.source = mailboxes.source;
+                        dispatch(message, actor, context).await;
+                    }
+
+                    // Handle everything already queued in the order of
+                    // mailbox priorities
+                    while let Some(message) = mailboxes.try_recv() {
+                        context.source = mailboxes.source;
+                        dispatch(message, actor, context).await;
+                    }
+                }
+
+                break;
+            }
+
+            // Stop was cancelled by the hook
+            context.state = ActorState::Started;
+            context.drain_on_stop = false;
+            context.wait_for_replies = false;
+        }
+
+        if pending.is_none() && !idle && mailboxes.is_public_empty() {
+            idle = true;
+
+            actor.on_idle(context).await;
+
+            // The hook may have stopped the actor
+            continue;
+        }
+
+        let idle_deadline = context.idle_timeout.map(|idle| last_activity + idle);
+
+        let received = match pending.take() {
+            Some(received) => received,
+            None => select! {
+                received = mailboxes.recv(context.paused) => received,
+                _ = sleep_until(idle_deadline) => {
+                    context.stop();
+
+                    // Wait the full period again if the hook cancels the stop
+                    last_activity = Instant::now();
+
+                    continue;
+                }
+                _ = next_tick(&mut interval) => {
+                    actor.on_interval(context).await;
+
+                    // The hook may have stopped the actor
+                    continue;
+                }
+            },
+        };
+
+        let message = match received {
+            Some(message) => message,
+            None => {
+                context.publish_state(ActorState::Stopping);
+                context.cancellation.cancel();
+
+                let stopping = actor.on_stopping(StopReason::AllAddressesDropped, context);
+
+                if within_shutdown_timeout::<A, _>(stopping).await.is_none() {
+                    actor.on_shutdown_timeout("on_stopping", context).await;
+                }
+
+                break;
+            }
+        };
+
+        context.source = mailboxes.source;
+
+        if idle && context.source == MessageSource::Public {
+            idle = false;
+
+            actor.on_busy(context).await;
+        }
+
+        if let Some(mark) = A::HIGH_WATER_MARK {
+            let depth = mailboxes.public_len();
+
+            if depth > mark {
+                actor.on_mailbox_pressure(depth, context).await;
+            }
+        }
+
+        if message.is_private_snapshot() {
+            let queued = mailboxes.snapshot_private(&context.private_address());
+
+            message.reply_private_snapshot(queued);
+        } else if message.is_concurrent() {
+            let (next, panics, errors) =
+                handle_concurrently(message, actor, mailboxes, context).await;
+
+            for payload in panics {
+                actor.on_panic(PanicReason::new(payload), context).await;
+            }
+
+            for error in errors {
+                actor.on_notification_error(error, context).await;
+            }
+
+            pending = next;
+        } else if message.is_batched() {
+            let (batch, rest) = message.collect_batch(&mut || mailboxes.try_recv_public());
+
+            dispatch(batch, actor, context).await;
+
+            pending = rest.map(Some);
+        } else {
+            dispatch(message, actor, context).await;
+        }
+
+        last_activity = Instant::now();
+    }
+}
+
+/// Sleeps until the deadline, never resolves if there is none
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the next tick of the interval, never resolves if there is none
+async fn next_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Runs a stop hook within [Actor::SHUTDOWN_TIMEOUT], `None` if it was
+/// abandoned
+async fn within_shutdown_timeout<A: Actor, T>(future: impl Future<Output = T>) -> Option<T> {
+    match A::SHUTDOWN_TIMEOUT {
+        Some(timeout) => tokio::time::timeout(timeout, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Future returned by [Actor::run_ready]
+pub struct Ready {
+    pub(crate) rx: oneshot::Receiver<Result<(), StartError>>,
+}
+
+impl Future for Ready {
+    type Output = Result<(), StartError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|result| result.unwrap_or(Err(StartError::Aborted)))
+    }
+}
+
+/// Reason the actor never started handling messages, see [Actor::run_ready]
+#[derive(Debug)]
+pub enum StartError {
+    /// [Actor::try_start] returned an error
+    Failed(Box<dyn Error + Send + Sync>),
+
+    /// Startup panicked or the actor task was aborted
+    Aborted,
+}
+
+impl Display for StartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartError::Failed(err) => write!(f, "Actor failed to start: {}", err),
+            StartError::Aborted => write!(f, "Actor was aborted before it started"),
+        }
+    }
+}
+
+impl Error for StartError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StartError::Failed(err) => Some(&**err),
+            StartError::Aborted => None,
+        }
+    }
+}
+
+/// Order in which an actor takes messages from its mailboxes, see
+/// [Actor::MAILBOX_POLICY].
+///
+/// Only the private mailbox, used by the actor to message itself, and the
+/// public one, used by its addresses, are affected. Control messages,
+/// stop requests from [Address::stop] included, always come first and
+/// priority messages always go before other public ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailboxPolicy {
+    /// Messages the actor sent itself are handled before any waiting public
+    /// message. An actor that keeps notifying itself starves its senders.
+    PrivateFirst,
+
+    /// A mailbox is picked at random among those with messages waiting, so
+    /// neither is starved while both are busy. Latency of either side
+    /// depends on the load of the other one.
+    Fair,
+
+    /// Public messages are handled before any waiting private one. Work the
+    /// actor scheduled for itself, like intervals and piped results, waits
+    /// for as long as senders keep the public mailbox busy.
+    PublicFirst,
+}
+
+/// Public mailbox capacity scaled with the number of cores, four messages
+/// per core but no less than 16 and no more than 1024.
+///
+/// Meant to be returned from [Actor::mailbox_capacity].
+pub fn default_mailbox_capacity() -> usize {
+    let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
+    (cores * 4).clamp(16, 1024)
+}
+
+/// Mailbox a message was received from, see [Context::message_source]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageSource {
+    /// Sent through the [ControlAddress]
+    Control,
+
+    /// Sent by the actor itself or by the actors it controls through the
+    /// private address
+    Private,
+
+    /// Sent through an [Address], the public mailbox
+    Public,
+}
+
+type BoxedEnvelope<A> = Box<dyn Envelope<A> + Send>;
+
+/// Receiving ends of the actor mailboxes
+pub(crate) struct Mailboxes<A: Actor> {
+    control: mpsc::UnboundedReceiver<BoxedEnvelope<A>>,
+    control_len: Arc<AtomicUsize>,
+    private: mpsc::UnboundedReceiver<BoxedEnvelope<A>>,
+    private_len: Arc<AtomicUsize>,
+    private_room: Arc<Notify>,
+    priority: mpsc::Receiver<BoxedEnvelope<A>>,
+    public: PublicMailbox<A>,
+    /// Resolves once every public address is dropped, none after that
+    addresses: Option<oneshot::Receiver<()>>,
+    policy: MailboxPolicy,
+    /// Mailbox the last message was received from
+    pub(crate) source: MessageSource,
+}
+
+impl<A: Actor> Mailboxes<A> {
+    /// Waits for the next message in the order of mailbox priorities, the
+    /// private and public mailboxes are picked from according to the
+    /// [MailboxPolicy].
+    ///
+    /// Returns `None` once all public addresses are dropped and the private
+    /// mailbox is empty. Private and control mailboxes never close while the
+    /// actor context is alive. While `paused`, the public messages still
+    /// buffered by then are left behind, nobody could resume sending them.
+    async fn recv(&mut self, paused: bool) -> Option<BoxedEnvelope<A>> {
+        use MessageSource::{Control, Private, Public};
+
+        let (source, received) = match self.policy {
+            MailboxPolicy::PrivateFirst => select! {
+                biased;
+
+                Some(message) = self.control.recv() => (Control, taken(&self.control_len, message)),
+                Some(message) = self.private.recv() => (Private, taken_private(&self.private_len, &self.private_room, message)),
+                Some(message) = self.priority.recv(), if !paused => (Public, Some(message)),
+                message = self.public.recv(), if !paused => (Public, message),
+                _ = dropped(&mut self.addresses), if paused => (Public, None),
+            },
+            MailboxPolicy::PublicFirst => select! {
+                biased;
+
+                Some(message) = self.control.recv() => (Control, taken(&self.control_len, message)),
+                Some(message) = self.priority.recv(), if !paused => (Public, Some(message)),
+                message = self.public.recv(), if !paused => (Public, message),
+                Some(message) = self.private.recv() => (Private, taken_private(&self.private_len, &self.private_room, message)),
+                _ = dropped(&mut self.addresses), if paused => (Public, None),
+            },
+            MailboxPolicy::Fair => {
+                // Control messages keep their priority
+                if let Ok(message) = self.control.try_recv() {
+                    self.source = Control;
+
+                    return taken(&self.control_len, message);
+                }
+
+                select! {
+                    Some(message) = self.control.recv() => (Control, taken(&self.control_len, message)),
+                    Some(message) = self.private.recv() => (Private, taken_private(&self.private_len, &self.private_room, message)),
+                    Some(message) = self.priority.recv(), if !paused => (Public, Some(message)),
+                    message = self.public.recv(), if !paused => (Public, message),
+                    _ = dropped(&mut self.addresses), if paused => (Public, None),
+                }
+            }
+        };
+
+        self.source = source;
+
+        // Messages the actor sent itself are handled even after the public
+        // mailbox is closed
+        received.or_else(|| self.try_recv_private())
+    }
+
+    /// Takes the next message that is already queued, if any
+    pub(crate) fn try_recv(&mut self) -> Option<BoxedEnvelope<A>> {
+        if let Ok(message) = self.control.try_recv() {
+            self.source = MessageSource::Control;
+
+            return taken(&self.control_len, message);
+        }
+
+        if self.policy == MailboxPolicy::PublicFirst {
+            return self
+                .try_recv_any_public()
+                .or_else(|| self.try_recv_private());
+        }
+
+        self.try_recv_private()
+            .or_else(|| self.try_recv_any_public())
+    }
+
+    fn try_recv_private(&mut self) -> Option<BoxedEnvelope<A>> {
+        let message = self.private.try_recv().ok()?;
+
+        self.source = MessageSource::Private;
+
+        taken_private(&self.private_len, &self.private_room, message)
+    }
+
+    fn try_recv_any_public(&mut self) -> Option<BoxedEnvelope<A>> {
+        let message = self
+            .priority
+            .try_recv()
+            .ok()
+            .or_else(|| self.public.try_recv())?;
+
+        self.source = MessageSource::Public;
+
+        Some(message)
+    }
+
+    /// Rejects the messages buffered in the public mailbox, returns the
+    /// control envelopes among them, see [Actor::REJECT_UNTIL_STARTED].
+    ///
+    /// The priority mailbox is left alone like the control one, where stop
+    /// requests go.
+    async fn reject_public(&mut self) -> Vec<BoxedEnvelope<A>> {
+        let mut kept = Vec::new();
+
+        while let Some(message) = self.public.try_recv() {
+            if message.is_control() {
+                kept.push(message);
+            } else {
+                message.reject().await;
+            }
+        }
+
+        kept
+    }
+
+    /// Receives a message from the public mailbox without waiting
+    fn try_recv_public(&mut self) -> Option<BoxedEnvelope<A>> {
+        self.public.try_recv()
+    }
+
+    /// Takes the queued private messages out and sends them back in the
+    /// same order, returns their type names
+    fn snapshot_private(&mut self, address: &UnboundedAddress<A>) -> Vec<&'static str> {
+        let mut queued = Vec::new();
+
+        while let Ok(message) = self.private.try_recv() {
+            queued.push(message);
+        }
+
+        queued
+            .into_iter()
+            .filter_map(|message| taken(&self.private_len, message))
+            .map(|message| {
+                let message_type = message.message_type();
+
+                let _ = address.deliver(message);
+
+                message_type
+            })
+            .collect()
+    }
+
+    /// Number of messages buffered in the public mailbox
+    fn public_len(&self) -> usize {
+        self.public.len()
+    }
+
+    /// Returns `true` if no public message is waiting, prioritized ones
+    /// included
+    fn is_public_empty(&self) -> bool {
+        self.public.len() == 0 && self.priority.is_empty()
+    }
+
+    /// Stops accepting new public messages, queued ones are kept
+    fn close_public(&mut self) {
+        self.public.close();
+        self.priority.close();
+    }
+}
+
+/// Resolves once every public address is dropped, right away after it
+/// resolved once
+async fn dropped(addresses: &mut Option<oneshot::Receiver<()>>) {
+    if let Some(receiver) = addresses {
+        // Nothing is ever sent, the sender is dropped with the last address
+        let _ = receiver.await;
+    }
+
+    *addresses = None;
+}
+
+/// Counts a message out of a mailbox with tracked length
+fn taken<A: Actor>(len: &AtomicUsize, message: BoxedEnvelope<A>) -> Option<BoxedEnvelope<A>> {
+    len.fetch_sub(1, Ordering::AcqRel);
+
+    Some(message)
+}
+
+/// Counts a message out of the private mailbox waking up one sender
+/// waiting for room, see [UnboundedAddress::notify_async]
+fn taken_private<A: Actor>(
+    len: &AtomicUsize,
+    room: &Notify,
+    message: BoxedEnvelope<A>,
+) -> Option<BoxedEnvelope<A>> {
+    let message = taken(len, message);
+
+    // Only once the message is counted out, so the sender sees the room
+    room.notify_one();
+
+    message
+}
+
+/// Message that ended the batch, panics of the handlers and errors of the
+/// notifications
+type ConcurrentOutcome<A> = (
+    Option<Option<BoxedEnvelope<A>>>,
+    Vec<Box<dyn Any + Send>>,
+    Vec<Box<dyn Error + Send>>,
+);
+
+/// Handles concurrent messages until a regular one is received.
+///
+/// Up to [Actor::MAX_CONCURRENCY] handlers share the actor at once. Once a
+/// regular message arrives, or the public mailbox is closed, receiving
+/// stops and the handlers in flight are awaited. What was received is
+/// returned to be processed next along with panics of the handlers and
+/// errors of the notifications.
+async fn handle_concurrently<A: Actor>(
+    first: BoxedEnvelope<A>,
+    actor: &mut A,
+    mailboxes: &mut Mailboxes<A>,
+    context: &mut Context<A>,
+) -> ConcurrentOutcome<A> {
+    // Shared borrows of the actor live only inside the type-erased handler
+    // futures, which are `Send`, so the loop stays `Send` for actors that
+    // are not `Sync`
+    context.tap(first.message_type());
+
+    let critical = context.critical.clone();
+    let mut in_flight = vec![CatchUnwind::new(
+        first.handle_concurrent(&*actor, critical.clone()),
+    )];
+    let mut next = None;
+    let mut panics = Vec::new();
+    let mut errors = Vec::new();
+
+    while !in_flight.is_empty() {
+        let accepts = next.is_none() && in_flight.len() < A::MAX_CONCURRENCY;
+        let paused = context.paused;
+
+        select! {
+            biased;
+
+            result = poll_any(&mut in_flight) => {
+                context.handled += 1;
+
+                match result {
+                    Ok(error) => errors.extend(error),
+                    Err(payload) => panics.push(payload),
+                }
+            }
+            received = mailboxes.recv(paused), if accepts => match received {
+                Some(message) if message.is_concurrent() => {
+                    context.tap(message.message_type());
+
+                    in_flight.push(CatchUnwind::new(message.handle_concurrent(&*actor, critical.clone())));
+                }
+                received => next = Some(received),
+            }
+        }
+    }
+
+    (next, panics, errors)
+}
+
+/// Resolves once any of the futures is ready, removing it
+async fn poll_any<F: Future + Unpin>(futures: &mut Vec<F>) -> F::Output {
+    poll_fn(|cx| {
+        for i in 0..futures.len() {
+            if let Poll::Ready(output) = Pin::new(&mut futures[i]).poll(cx) {
+                futures.swap_remove(i);
+
+                return Poll::Ready(output);
+            }
+        }
+
+        Poll::Pending
+    })
+    .await
+}
+
+/// Handles a single message catching a possible handler panic
+pub(crate) async fn dispatch<A: Actor>(
+    message: Box<dyn Envelope<A> + Send>,
+    actor: &mut A,
+    context: &mut Context<A>,
+) {
+    let limiter = actor.limiter();
+    let _permit = match &limiter {
+        Some(limiter) => Some(limiter.acquire().await),
+        None => None,
+    };
+
+    let timeout = actor.message_timeout();
+    let watchdog = actor.watchdog_timeout();
+    let message_type = message.message_type();
+    let status = context.address().status;
+
+    context.tap(message_type);
+    context.correlation = message.correlation_id();
+
+    let started = Instant::now();
+
+    let handling = async {
+        if message.is_inline() {
+            // Nothing to time out, the handler is done once it returns
+            return Some(panic::catch_unwind(AssertUnwindSafe(|| {
+                message.handle_inline(actor, context)
+            })));
+        }
+
+        let handling = CatchUnwind::new(message.handle(actor, context));
+
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, handling).await.ok(),
+            None => Some(handling.await),
+        }
+    };
+
+    let result = match watchdog {
+        Some(watchdog) => {
+            tokio::pin!(handling);
+
+            select! {
+                result = &mut handling => result,
+                _ = tokio::time::sleep(watchdog) => {
+                    status.set_stuck(Some(message_type));
+                    let result = handling.await;
+                    status.set_stuck(None);
+
+                    result
+                }
+            }
+        }
+        None => handling.await,
+    };
+
+    let duration = started.elapsed();
+
+    context.handled += 1;
+
+    let outcome = match result {
+        Some(Ok(())) if context.failed => AuditOutcome::Failure,
+        Some(Ok(())) => AuditOutcome::Success,
+        Some(Err(payload)) => {
+            // Release the reply sender of the panicked message, if any
+            context.reply = None;
+            context.expects_reply = false;
+
+            actor.on_panic(PanicReason::new(payload), context).await;
+
+            AuditOutcome::Panicked
+        }
+        None => {
+            // Same for the cancelled one
+            context.reply = None;
+            context.expects_reply = false;
+
+            AuditOutcome::TimedOut
+        }
+    };
+
+    context.failed = false;
+    context.correlation = None;
+
+    if let Some(next) = context.replacement.take() {
+        let mut replaced = mem::replace(actor, next);
+
+        replaced.on_stopped(context).await;
+
+        if actor.try_start(context).await.is_err() {
+            context.start_failed = true;
+            context.state = ActorState::Stopping;
+        }
+    }
+
+    actor.on_handled(duration, context).await;
+
+    if let Some(recipient) = actor.audit_recipient() {
+        let record = AuditRecord {
+            message_type,
+            outcome,
+            duration,
+        };
+
+        let _ = recipient.notify(record).await;
+    }
+}
+
 /// `ActorSpawner` is useful when you need to store or pass somewhere and object
 /// capable of spawning actors.
 ///
@@ -99,13 +1311,31 @@ pub trait Actor: Send + Sized + 'static {
 /// ```
 pub struct ActorSpawner<A: Actor> {
     spawn: Box<dyn Fn() -> A + Send>,
+    setup: Option<SpawnSetup<A>>,
 }
 
+type SpawnSetup<A> = Box<dyn Fn(&Address<A>) + Send>;
+
 impl<A: Actor> ActorSpawner<A> {
     /// Creates ActorSpawner from a closure
     pub fn from<F: Fn() -> A + Send + 'static>(spawner: F) -> ActorSpawner<A> {
         ActorSpawner {
             spawn: Box::new(spawner),
+            setup: None,
+        }
+    }
+
+    /// Creates ActorSpawner from a closure along with a hook called with
+    /// the address of each actor started by [ActorSpawner::spawn_run] or
+    /// [ActorSpawner::spawn_run_limited], e.g. to register it somewhere
+    pub fn from_with_setup<F, S>(spawner: F, setup: S) -> ActorSpawner<A>
+    where
+        F: Fn() -> A + Send + 'static,
+        S: Fn(&Address<A>) + Send + 'static,
+    {
+        ActorSpawner {
+            spawn: Box::new(spawner),
+            setup: Some(Box::new(setup)),
         }
     }
 
@@ -116,6 +1346,41 @@ impl<A: Actor> ActorSpawner<A> {
 
     /// Spawns an actor and immediately returns its address
     pub fn spawn_run(&self) -> Address<A> {
-        (self.spawn)().run()
+        let address = (self.spawn)().run();
+
+        self.set_up(&address);
+
+        address
+    }
+
+    /// Waits until the limiter allows one more actor, then spawns an actor
+    /// and returns its address.
+    ///
+    /// The permit is held by the actor task and released once the actor is
+    /// stopped.
+    pub async fn spawn_run_limited(&self, limiter: &SpawnLimiter) -> Address<A> {
+        let permit = limiter.acquire().await;
+
+        let actor = (self.spawn)();
+        let capacity = actor.mailbox_capacity();
+        let prepared = prepare(actor, capacity);
+
+        let job = prepared.job;
+
+        tokio::spawn(async move {
+            job.await;
+
+            drop(permit);
+        });
+
+        self.set_up(&prepared.address);
+
+        prepared.address
+    }
+
+    fn set_up(&self, address: &Address<A>) {
+        if let Some(setup) = &self.setup {
+            setup(address);
+        }
     }
 }
@@ -1,113 +1,1220 @@
-use std::{time::Duration, fmt::Display, error::Error};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    hash::{Hash, Hasher},
+    io,
+    iter::FromIterator,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    task::{self, ready, Poll},
+    time::Duration,
+};
 
 use tokio::{
     sync::{
-        mpsc::{self, WeakSender},
-        oneshot,
+        mpsc::{self, error::TrySendError, WeakSender, WeakUnboundedSender},
+        oneshot::{self, error::TryRecvError},
+        Notify,
     },
-    task::JoinHandle,
-    time::Instant,
+    time::{Instant, Interval},
 };
 
 use crate::{
-    handler::{Envelope, MessageWithSender, UnpackableResult},
-    Actor, Handler,
+    batch::Batched,
+    concurrent::{ConcurrentHandler, ConcurrentMessageWithSender, ConcurrentNotification},
+    handler::{Barrier, Envelope, MessageWithSender, Response, StopEnvelope, UnpackableResult},
+    inline::{InlineHandler, InlineMessageWithSender, InlineNotification},
+    inspect::WithState,
+    migrate::{Migrate, SwapEnvelope},
+    persist::{PersistedMessageWithSender, PersistedNotification, Receipt},
+    random,
+    retry::{self, RetryPolicy},
+    sink::NotifySink,
+    spawn::SpawnHandle,
+    status::Status,
+    validate::{ValidatedMessageWithSender, ValidatedNotification},
+    Actor, ActorState, BatchHandler, Codec, ControlAddress, CorrelationId, Handler, ReceiverStream,
+    Recipient, Stream, Subscription, Validate, ValidationError,
 };
 
 pub struct Address<A: Actor> {
     pub(crate) tx: mpsc::Sender<Box<dyn Envelope<A> + Send>>,
+    pub(crate) priority_tx: mpsc::Sender<Box<dyn Envelope<A> + Send>>,
+    /// Control mailbox stop requests go to, none for hand-rolled loops
+    pub(crate) control: Option<ControlAddress<A>>,
+    pub(crate) status: Arc<Status>,
+    /// Dropped along with the last address, which tells a paused actor
+    /// that nobody could resume sending to it
+    pub(crate) alive: Arc<oneshot::Sender<()>>,
 }
 
 pub struct UnboundedAddress<A: Actor> {
     pub(crate) tx: mpsc::UnboundedSender<Box<dyn Envelope<A> + Send>>,
+    pub(crate) len: Arc<AtomicUsize>,
+    /// Most messages the mailbox may hold, `usize::MAX` if unbounded
+    pub(crate) limit: usize,
+    /// Signalled each time a message is taken out of the mailbox
+    pub(crate) room: Arc<Notify>,
 }
 
 impl<A: Actor> Clone for Address<A> {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
+            priority_tx: self.priority_tx.clone(),
+            control: self.control.clone(),
+            status: self.status.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+}
+
+/// Clones of the same address are equal, addresses of different actors
+/// are not. Only this identity is hashed, so clippy's `mutable_key_type`
+/// lint is a false positive for sets keyed by addresses.
+impl<A: Actor> PartialEq for Address<A> {
+    fn eq(&self, other: &Self) -> bool {
+        // Status is allocated once per running actor and shared by clones
+        Arc::ptr_eq(&self.status, &other.status)
+    }
+}
+
+impl<A: Actor> Eq for Address<A> {}
+
+impl<A: Actor> Hash for Address<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.status).hash(state)
+    }
+}
+
+impl<A: Actor> fmt::Debug for Address<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Address")
+            .field("actor", &std::any::type_name::<A>())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<A: Actor> Clone for UnboundedAddress<A> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            len: self.len.clone(),
+            limit: self.limit,
+            room: self.room.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "unstable")]
+impl<A: Actor> Address<A> {
+    /// Builds an address on top of an existing channel.
+    ///
+    /// **Unstable.** The envelope type is an implementation detail and may
+    /// change in any release. Nothing drives the channel but the caller, so
+    /// messages are only handled if the receiving end passes them to
+    /// [Envelope::handle](crate::Envelope::handle) itself, e.g. with the
+    /// `MockContext` of the `testing` feature. There is no
+    /// separate priority or control mailbox, priority messages and stop
+    /// requests are sent to the same channel and the sender counts include
+    /// them.
+    pub fn from_sender(tx: mpsc::Sender<Box<dyn Envelope<A> + Send>>) -> Address<A> {
+        Address {
+            priority_tx: tx.clone(),
+            tx,
+            control: None,
+            status: Arc::default(),
+            alive: Arc::new(oneshot::channel().0),
+        }
+    }
+
+    /// Underlying channel of the address.
+    ///
+    /// **Unstable.** Envelopes sent directly bypass everything the address
+    /// methods do, e.g. nobody waits for their responses.
+    pub fn sender(&self) -> &mpsc::Sender<Box<dyn Envelope<A> + Send>> {
+        &self.tx
+    }
+}
+
+impl<A: Actor> Address<A> {
+    // Sends a message to the actor
+    pub async fn send<M: Message + 'static>(&self, message: M) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let packed = MessageWithSender::new(message, tx);
+
+        self.tx
+            .send(Box::new(packed))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        #[cfg(feature = "deadlock-detection")]
+        let _waiting = crate::deadlock::wait_for(self.id());
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Sends a message and waits for the response until `deadline`.
+    ///
+    /// Unlike a timeout, a deadline is not reset at each hop, so passing it
+    /// along keeps a chain of requests within one overall budget. Fails with
+    /// [ActorSendError::Timeout] once the deadline passes, without sending
+    /// anything if it has already passed. The handler is cancelled as the
+    /// caller stops waiting, see [Actor::CANCEL_ON_DISCONNECT].
+    pub async fn send_deadline<M: Message + 'static>(
+        &self,
+        message: M,
+        deadline: Instant,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        if deadline <= Instant::now() {
+            return Err(ActorSendError::Timeout);
+        }
+
+        tokio::time::timeout_at(deadline, self.send(message))
+            .await
+            .map_err(|_| ActorSendError::Timeout)?
+    }
+
+    /// Sends a message and waits for the response at most `timeout`.
+    ///
+    /// The time the message spends queued counts, including while the
+    /// actor is paused with [Context::pause](crate::Context::pause), so it
+    /// fails with [ActorSendError::Timeout] unless the actor resumes and
+    /// handles the message in time. See [Address::send_deadline] to share
+    /// one budget across several hops.
+    pub async fn send_timeout<M: Message + 'static>(
+        &self,
+        message: M,
+        timeout: Duration,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        self.send_deadline(message, Instant::now() + timeout).await
+    }
+
+    /// Identity of the actor shared by all its addresses
+    #[cfg(feature = "deadlock-detection")]
+    pub(crate) fn id(&self) -> crate::deadlock::ActorId {
+        Arc::as_ptr(&self.status) as crate::deadlock::ActorId
+    }
+
+    /// Sends a message with a reply channel owned by the caller, returns
+    /// as soon as the message is delivered.
+    ///
+    /// The response is sent into `tx`. Dropping the receiving end before it
+    /// arrives cancels the handler, same as dropping the future returned by
+    /// [Address::send].
+    ///
+    /// `tx` carries no error, so it is just dropped if the message is
+    /// rejected, see [Actor::REJECT_UNTIL_STARTED].
+    pub async fn send_with<M: Message + 'static>(
+        &self,
+        message: M,
+        tx: oneshot::Sender<M::Result>,
+    ) -> Result<(), ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        self.tx
+            .send(Box::new(MessageWithSender {
+                message,
+                tx: Box::new(tx),
+            }))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)
+    }
+
+    /// Sends a message and waits for the response blocking the current
+    /// thread, for synchronous code like `spawn_blocking` closures or
+    /// dedicated threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called within an async execution context, where blocking
+    /// would stall the runtime, same as [mpsc::Sender::blocking_send].
+    pub fn blocking_send<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        let envelope = Box::new(MessageWithSender::new(message, tx));
+
+        self.tx
+            .blocking_send(envelope)
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.blocking_recv()
+            .unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Runs the closure against the actor state from within the message
+    /// loop, like any other message, and returns its result.
+    ///
+    /// An escape hatch for tests and tooling that saves defining a message
+    /// type for every one-off read or mutation.
+    pub async fn with_state<F, R>(&self, f: F) -> Result<R, ActorSendError>
+    where
+        F: FnOnce(&mut A) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.send(WithState(Box::new(f))).await
+    }
+
+    /// Sends a message retrying delivery according to the policy.
+    ///
+    /// Only [ActorSendError::FailedToDeliver] is retried, e.g. while the
+    /// actor is being restarted. [ActorSendError::FailedToGetResponse] is
+    /// returned right away since the handler may have already run.
+    pub async fn send_retry<M>(
+        &self,
+        message: M,
+        policy: RetryPolicy,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+        M: Message + Clone + 'static,
+    {
+        retry::call_with_retry(self, message, policy, |err| {
+            *err == ActorSendError::FailedToDeliver
+        })
+        .await
+    }
+
+    /// Waits until every message sent through the public mailbox before
+    /// the call is handled.
+    ///
+    /// A barrier is queued behind them and its reply awaited, the mailbox
+    /// being FIFO. Messages the actor sent itself are not waited for.
+    pub async fn flush(&self) -> Result<(), ActorSendError> {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(Barrier(tx)))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+    }
+
+    /// Delivers a message returning a future of the response without
+    /// waiting for it.
+    ///
+    /// Waits only for room in the mailbox, so several requests can be made
+    /// first and their responses awaited later.
+    pub async fn ask<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<ResponseFuture<M::Result>, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(MessageWithSender::new(message, tx)))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        Ok(ResponseFuture { rx })
+    }
+
+    /// Delivers all the messages, then yields their responses in the order
+    /// the messages were sent, whatever order they are handled in.
+    ///
+    /// A message that could not be delivered yields
+    /// [ActorSendError::FailedToDeliver] in its place.
+    pub async fn send_ordered<M, I>(&self, messages: I) -> OrderedResponses<M::Result>
+    where
+        A: Handler<M>,
+        M: Message + 'static,
+        I: IntoIterator<Item = M>,
+    {
+        let mut pending = VecDeque::new();
+
+        for message in messages {
+            pending.push_back(self.ask(message).await);
+        }
+
+        OrderedResponses { pending }
+    }
+
+    /// Sends a message handled by a [ConcurrentHandler] and waits for the
+    /// response.
+    ///
+    /// The message may be handled concurrently with other such messages,
+    /// see [Actor::MAX_CONCURRENCY](crate::Actor::MAX_CONCURRENCY).
+    pub async fn send_concurrent<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: ConcurrentHandler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(ConcurrentMessageWithSender { message, tx }))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Sends a notification handled by a [ConcurrentHandler]
+    pub async fn notify_concurrent<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: ConcurrentHandler<M>,
+    {
+        self.tx
+            .send(Box::new(ConcurrentNotification(message)))
+            .await
+            .map_err(|_| FailedToDeliver)
+    }
+
+    /// Sends a message handled by an [InlineHandler] and waits for the
+    /// response
+    pub async fn send_inline<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: InlineHandler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(InlineMessageWithSender { message, tx }))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Sends a notification handled by an [InlineHandler]
+    pub async fn notify_inline<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: InlineHandler<M>,
+    {
+        self.tx
+            .send(Box::new(InlineNotification(message)))
+            .await
+            .map_err(|_| FailedToDeliver)
+    }
+
+    /// Sends a notification handled by a [BatchHandler] together with the
+    /// ones queued right after it
+    pub async fn notify_batched<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: BatchHandler<M>,
+    {
+        self.tx
+            .send(Box::new(Batched(Some(message))))
+            .await
+            .map_err(|_| FailedToDeliver)
+    }
+
+    /// Sends a message through the priority mailbox and waits for the
+    /// response.
+    ///
+    /// Priority messages are handled ahead of the ones buffered in the
+    /// regular public mailbox, but after the control and private ones.
+    /// The priority mailbox has the same capacity as the regular one.
+    pub async fn send_priority<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.priority_tx
+            .send(Box::new(MessageWithSender::new(message, tx)))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Asks the actor to stop however many addresses are still around, the
+    /// external counterpart of [Context::stop](crate::Context::stop).
+    ///
+    /// The request goes through the control mailbox, so it is handled
+    /// right after the current message even if the actor is paused or busy
+    /// with its own messages. [Actor::on_stopping](crate::Actor::on_stopping)
+    /// may still cancel it. Waits only for the request to be delivered.
+    pub async fn stop(&self) -> Result<(), ActorSendError> {
+        match &self.control {
+            Some(control) => control.stop(),
+            None => self
+                .priority_tx
+                .send(Box::new(StopEnvelope))
+                .await
+                .map_err(|_| FailedToDeliver),
+        }
+        .map_err(|_| ActorSendError::FailedToDeliver)
+    }
+
+    /// Replaces the running actor with a new instance keeping the mailbox.
+    ///
+    /// The swap is queued as a regular message, so everything sent before
+    /// it is handled by the previous instance and everything after it by
+    /// the new one. [Migrate::migrate] receives the previous instance to
+    /// take over its state. Resolves once the new instance is started. If
+    /// it fails to start, the actor stops as with
+    /// [Context::replace](crate::Context::replace) and
+    /// [ActorSendError::FailedToGetResponse] is returned.
+    pub async fn hot_swap(&self, new: A) -> Result<(), ActorSendError>
+    where
+        A: Migrate,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(SwapEnvelope { actor: new, tx }))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+    }
+
+    /// Sends a message and unpacks the result
+    ///
+    /// Simplifies operations with some common kinds of results. Unpacks:
+    /// - `tokio::oneshot::Receiver<T>` into `T`
+    /// - `Result<tokio::oneshot::Receiver<Result<T, E>, E>` into `Result<T, E>`
+    /// - [Deferred<T>](crate::Deferred) into `T`
+    /// 
+    /// It could be useful when you need to return `Receiver<T>` from handler
+    /// immediately unblocking actors message loop to send `T` later.
+    pub async fn send_unpack<M: Message + 'static, R>(
+        &self,
+        message: M,
+    ) -> Result<R, ActorSendError>
+    where
+        A: Handler<M>,
+        M::Result: UnpackableResult<UnpackedResult = R>,
+    {
+        match self.send(message).await {
+            Ok(v) => v.unpack_result().await,
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sends a message and unpacks the result flattening the domain error
+    ///
+    /// Works like `send_unpack` for handlers whose result unpacks into
+    /// `Result<T, E>`, but returns `Result<T, SendUnpackError<E>>` instead
+    /// of the nested form.
+    pub async fn send_unpack_flat<M: Message + 'static, T, E>(
+        &self,
+        message: M,
+    ) -> Result<T, SendUnpackError<E>>
+    where
+        A: Handler<M>,
+        M::Result: UnpackableResult<UnpackedResult = Result<T, E>>,
+    {
+        match self.send_unpack(message).await {
+            Ok(Ok(v)) => Ok(v),
+            Ok(Err(err)) => Err(SendUnpackError::Domain(err)),
+            Err(err) => Err(SendUnpackError::Send(err)),
+        }
+    }
+
+    /// Sends a message that is validated right before it is handled.
+    ///
+    /// The handler is not invoked for invalid messages, the validation
+    /// error is returned instead.
+    pub async fn send_validated<M: Message + Validate + 'static>(
+        &self,
+        message: M,
+    ) -> Result<Result<M::Result, ValidationError>, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Box::new(ValidatedMessageWithSender { message, tx }))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Sends a notification that is dropped if it turns out to be invalid
+    pub async fn notify_validated<M: Message + Validate + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        self.tx
+            .send(Box::new(ValidatedNotification(message)))
+            .await
+            .map_err(|_| FailedToDeliver)
+    }
+
+    /// Sends a message that is persisted in the [Actor::journal] before it
+    /// is put in the mailbox and acknowledged right after it is handled.
+    ///
+    /// The handler runs to completion even if the caller stops waiting. A
+    /// message that fails to be delivered is acknowledged right away, so it
+    /// is not replayed behind the back of a caller retrying it.
+    pub async fn send_persisted<M: Message + Codec + 'static>(
+        &self,
+        message: M,
+    ) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        let (tx, rx) = oneshot::channel();
+        let receipt = Receipt::persist(self.status.journal.as_ref(), &message).await;
+        let unsent = receipt.clone();
+
+        let sent = self
+            .tx
+            .send(Box::new(PersistedMessageWithSender {
+                message,
+                tx,
+                receipt,
+            }))
+            .await;
+
+        if sent.is_err() {
+            Receipt::acknowledge(unsent).await;
+
+            return Err(ActorSendError::FailedToDeliver);
+        }
+
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
+    }
+
+    /// Sends a notification that is persisted and acknowledged like the
+    /// messages of [Address::send_persisted]
+    pub async fn notify_persisted<M: Message + Codec + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        let receipt = Receipt::persist(self.status.journal.as_ref(), &message).await;
+        let unsent = receipt.clone();
+
+        let sent = self
+            .tx
+            .send(Box::new(PersistedNotification { message, receipt }))
+            .await;
+
+        if sent.is_err() {
+            Receipt::acknowledge(unsent).await;
+
+            return Err(FailedToDeliver);
+        }
+
+        Ok(())
+    }
+
+    /// Sends a notification, waiting for mailbox capacity if it is full
+    pub async fn notify<M: Message + 'static>(&self, message: M) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        self.tx
+            .send(Box::new(message))
+            .await
+            .map_err(|_| FailedToDeliver)
+    }
+
+    /// Sends a message without waiting for nor allocating room for a result.
+    ///
+    /// Same as [Address::notify] but reports failures as [ActorSendError]
+    /// to be handled uniformly with [Address::send] errors.
+    pub async fn do_send<M: Message + 'static>(&self, message: M) -> Result<(), ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        self.notify(message)
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)
+    }
+
+    /// Sends a notification built around the recipient the handler should
+    /// reply to, instead of replying to the caller.
+    ///
+    /// Meant for pipelines where the result goes further down the line: the
+    /// handler forwards it with [Recipient::notify] or [Recipient::send].
+    pub async fn send_and_reply_to<M, R, F, T>(
+        &self,
+        make_message: F,
+        reply_to: T,
+    ) -> Result<(), FailedToDeliver>
+    where
+        M: Message + 'static,
+        R: Message + 'static,
+        F: FnOnce(Recipient<R>) -> M,
+        T: Into<Recipient<R>>,
+        A: Handler<M>,
+    {
+        self.notify(make_message(reply_to.into())).await
+    }
+
+    /// Sends a notification only if the mailbox has free capacity right now
+    pub fn try_notify<M: Message + 'static>(&self, message: M) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        self.tx.try_send(Box::new(message)).map_err(|err| {
+            if let TrySendError::Full(_) = err {
+                self.status.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+
+            FailedToDeliver
+        })
+    }
+
+    pub fn notify_later<M: Message + 'static>(&self, message: M, after: Duration) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let address = self.clone();
+
+        SpawnHandle::new(tokio::spawn(async move {
+            tokio::time::sleep(after).await;
+
+            let _ = address.notify(message).await;
+        }))
+    }
+
+    pub fn notify_at<M: Message + 'static>(&self, message: M, at: Instant) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let address = self.clone();
+
+        SpawnHandle::new(tokio::spawn(async move {
+            tokio::time::sleep_until(at).await;
+
+            let _ = address.notify(message).await;
+        }))
+    }
+
+    /// Be aware that the spawned ticker holds a strong address and keeps the
+    /// actor alive until it is aborted.
+    ///
+    /// The first message is sent right away, see [FirstTick::Immediate].
+    pub fn notify_interval<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        self.notify_interval_with(create_message, period, FirstTick::Immediate)
+    }
+
+    /// Same as [Address::notify_interval] with control over when the first
+    /// message is sent
+    pub fn notify_interval_with<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+        first: FirstTick,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let address = self.clone();
+        let mut interval = first.interval(period);
+
+        SpawnHandle::new(tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+
+                let message = create_message();
+
+                if address.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Same as [Address::notify_interval], except that the ticker holds only
+    /// a weak address, so it doesn't keep the actor alive.
+    ///
+    /// The ticker stops once every other address of the actor is dropped.
+    pub fn notify_interval_weak<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let address = self.downgrade();
+        let mut interval = tokio::time::interval(period);
+
+        SpawnHandle::new(tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+
+                let address = match address.upgrade() {
+                    Some(address) => address,
+                    None => break,
+                };
+
+                if address.send(create_message()).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Waits until the mailbox has free capacity and reserves a slot in it.
+    ///
+    /// Sending through the returned permit never waits for capacity again.
+    pub async fn ready(&self) -> Result<SendPermit<'_, A>, ActorSendError> {
+        self.tx
+            .reserve()
+            .await
+            .map(|permit| SendPermit { permit })
+            .map_err(|_| ActorSendError::FailedToDeliver)
+    }
+
+    /// Number of messages waiting in the mailbox, including reserved slots
+    pub fn len(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    /// Returns `true` if there are no messages waiting in the mailbox
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of messages that could be sent without waiting
+    pub fn capacity(&self) -> usize {
+        self.tx.capacity()
+    }
+
+    /// Mailbox bound the actor was run with
+    pub fn max_capacity(&self) -> usize {
+        self.tx.max_capacity()
+    }
+
+    /// Returns `true` if the actor do not receive messages any more.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Converts the `Address` to a [`WeakAddress`] that does not count
+    /// towards RAII semantics, i.e. if all `Address` instances of the
+    /// actor were dropped and only `WeakAddress` instances remain,
+    /// the actor is stopped.
+    pub fn downgrade(&self) -> WeakAddress<A> {
+        WeakAddress {
+            tx: self.tx.downgrade(),
+            priority_tx: self.priority_tx.downgrade(),
+            control: self.control.clone(),
+            status: self.status.clone(),
+            alive: Arc::downgrade(&self.alive),
+        }
+    }
+
+    /// Sends a message whose handler streams the response back through an
+    /// `mpsc` channel.
+    ///
+    /// The handler creates the channel, returns the receiver right away
+    /// freeing the message loop and feeds the items from a spawned task.
+    /// The stream ends once every sender is dropped.
+    pub async fn send_stream<M, T>(&self, message: M) -> Result<ReceiverStream<T>, ActorSendError>
+    where
+        M: Message<Result = mpsc::Receiver<T>> + 'static,
+        A: Handler<M>,
+    {
+        self.send(message).await.map(ReceiverStream::new)
+    }
+
+    /// Subscribes to events the actor pushes, see [Subscription].
+    ///
+    /// Unlike [Address::send_stream] the stream is open-ended, it ends only
+    /// once the actor drops the sender it got with the subscription.
+    pub async fn subscribe<M, E>(&self, message: M) -> Result<ReceiverStream<E>, ActorSendError>
+    where
+        M: Send + 'static,
+        E: Send + 'static,
+        A: Handler<Subscription<M, E>>,
+    {
+        let (events, rx) = mpsc::channel(Subscription::<M, E>::CAPACITY);
+
+        self.send(Subscription { message, events }).await?;
+
+        Ok(ReceiverStream::new(rx))
+    }
+
+    /// Converts the address into a [Sink](crate::Sink) of notifications.
+    ///
+    /// Each item waits for free space in the mailbox, nobody waits for the
+    /// responses. Closing the sink drops only its own address, so the actor
+    /// stops only if no other address is left.
+    pub fn into_sink<M>(self) -> NotifySink<A, M>
+    where
+        A: Handler<M>,
+        M: Message + 'static,
+    {
+        NotifySink::new(self)
+    }
+
+    /// Repeatedly sends a message built by `make_message` and yields each
+    /// response as a stream item.
+    ///
+    /// Only one request is outstanding at a time and the next one is sent
+    /// only when the stream is polled again. The stream ends once the actor
+    /// fails to deliver or respond. It holds a strong address, so the actor
+    /// is kept alive while the stream exists.
+    pub fn poll_stream<M, F>(&self, make_message: F) -> PollStream<A, M, F>
+    where
+        M: Message + 'static,
+        F: Fn() -> M,
+        A: Handler<M>,
+    {
+        PollStream {
+            address: self.clone(),
+            make_message,
+            pending: None,
+            done: false,
         }
     }
+
+    /// Returns `true` if actor is steel receiving messages.
+    /// 
+    /// The opposite of `is_closed`
+    pub fn is_connected(&self) -> bool {
+        !self.tx.is_closed()
+    }
+
+    /// Current lifecycle stage of the actor.
+    ///
+    /// The actor is [ActorState::Stopping] once a stop is confirmed by
+    /// [Actor::on_stopping](crate::Actor::on_stopping) or all its addresses
+    /// are gone, and [ActorState::Stopped] once its task is finished or
+    /// aborted.
+    pub fn state(&self) -> ActorState {
+        *self.status.state.borrow()
+    }
+
+    /// Waits until the actor reaches `state` or any later one
+    pub async fn wait_for_state(&self, state: ActorState) {
+        let mut rx = self.status.state.subscribe();
+
+        // The sender lives in the status held by this address
+        let _ = rx.wait_for(|current| *current >= state).await;
+    }
+
+    /// Resolves once the actor is stopped, after [Actor::on_stopped] and
+    /// the finalizers have run, or right away if it already is.
+    ///
+    /// The returned future doesn't borrow the address nor keeps the actor
+    /// alive, so any number of observers may wait for it.
+    pub fn stopped(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.status.state.subscribe();
+
+        async move {
+            // The sender is gone only once the actor task is
+            let _ = rx.wait_for(|state| *state == ActorState::Stopped).await;
+        }
+    }
+
+    /// Returns `true` while a handler runs longer than
+    /// [Actor::watchdog_timeout](crate::Actor::watchdog_timeout)
+    pub fn is_stuck(&self) -> bool {
+        self.stuck_on().is_some()
+    }
+
+    /// Type name of the message whose handler runs longer than
+    /// [Actor::watchdog_timeout](crate::Actor::watchdog_timeout), if any
+    pub fn stuck_on(&self) -> Option<&'static str> {
+        self.status.stuck_on()
+    }
+
+    /// Number of messages [Address::try_notify] failed to deliver because
+    /// the mailbox was full, counted across all addresses of the actor
+    pub fn dropped_count(&self) -> u64 {
+        self.status.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of `Address` instances keeping the actor alive.
+    ///
+    /// Useful to find out who prevents an actor from stopping.
+    pub fn strong_count(&self) -> usize {
+        self.tx.strong_count()
+    }
+
+    /// Number of [`WeakAddress`] instances, including the one held by the
+    /// actor's own [Context](crate::Context) while it is running.
+    pub fn weak_count(&self) -> usize {
+        self.tx.weak_count()
+    }
 }
 
-impl<A: Actor> Clone for UnboundedAddress<A> {
-    fn clone(&self) -> Self {
-        Self {
-            tx: self.tx.clone(),
-        }
-    }
+/// Reserved mailbox slot returned by [Address::ready]
+pub struct SendPermit<'a, A: Actor> {
+    permit: mpsc::Permit<'a, Box<dyn Envelope<A> + Send>>,
 }
 
-impl<A: Actor> Address<A> {
-    // Sends a message to the actor
-    pub async fn send<M: Message + 'static>(&self, message: M) -> Result<M::Result, ActorSendError>
+impl<'a, A: Actor> SendPermit<'a, A> {
+    /// Sends a message into the reserved slot and waits for the response
+    pub async fn send<M: Message + 'static>(self, message: M) -> Result<M::Result, ActorSendError>
     where
         A: Handler<M>,
     {
         let (tx, rx) = oneshot::channel();
 
-        let packed = MessageWithSender { message, tx };
-
-        self.tx
-            .send(Box::new(packed))
-            .await
-            .map_err(|_| ActorSendError::FailedToDeliver)?;
+        self.permit.send(Box::new(MessageWithSender::new(message, tx)));
 
-        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
     }
 
-    /// Sends a message and unpacks the result
-    ///
-    /// Simplifies operations with some common kinds of results. Unpacks:
-    /// - `tokio::oneshot::Receiver<T>` into `T`
-    /// - `Result<tokio::oneshot::Receiver<Result<T, E>, E>` into `Result<T, E>`
-    /// 
-    /// It could be useful when you need to return `Receiver<T>` from handler
-    /// immediately unblocking actors message loop to send `T` later.
-    pub async fn send_unpack<M: Message + 'static, R>(
-        &self,
-        message: M,
-    ) -> Result<R, ActorSendError>
+    /// Sends a notification into the reserved slot
+    pub fn notify<M: Message + 'static>(self, message: M)
     where
         A: Handler<M>,
-        M::Result: UnpackableResult<UnpackedResult = R>,
     {
-        match self.send(message).await {
-            Ok(v) => v.unpack_result().await,
-            Err(err) => Err(err),
+        self.permit.send(Box::new(message));
+    }
+}
+
+type PendingRequest<T> = Pin<Box<dyn Future<Output = Result<T, ActorSendError>> + Send>>;
+
+/// Response of a request made with [Address::ask].
+///
+/// Dropping it tells the actor that nobody waits for the response anymore,
+/// the same as dropping the future returned by [Address::send].
+pub struct ResponseFuture<T> {
+    rx: oneshot::Receiver<Response<T>>,
+}
+
+impl<T> Future for ResponseFuture<T> {
+    type Output = Result<T, ActorSendError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|response| response.unwrap_or(Err(ActorSendError::FailedToGetResponse)))
+    }
+}
+
+/// Collection of pending responses for scatter-gather queries.
+///
+/// Responses are collected with [ResponseSet::poll_ready] as they become
+/// available, without waiting, which allows aggregating within a time
+/// budget. [ResponseSet::await_all] waits for the rest.
+pub struct ResponseSet<T> {
+    pending: Vec<ResponseFuture<T>>,
+}
+
+impl<T> ResponseSet<T> {
+    pub fn new() -> ResponseSet<T> {
+        ResponseSet {
+            pending: Vec::new(),
         }
     }
 
-    /// Returns `true` if the actor do not receive messages any more.
-    pub fn is_closed(&self) -> bool {
-        self.tx.is_closed()
+    pub fn push(&mut self, response: ResponseFuture<T>) {
+        self.pending.push(response);
     }
 
-    /// Converts the `Address` to a [`WeakAddress`] that does not count
-    /// towards RAII semantics, i.e. if all `Address` instances of the
-    /// actor were dropped and only `WeakAddress` instances remain,
-    /// the actor is stopped.
-    pub fn downgrade(&self) -> WeakAddress<A> {
-        WeakAddress {
-            tx: self.tx.downgrade(),
+    /// Number of responses not collected yet
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Takes the responses that are already available, in the order they
+    /// were pushed, and keeps the rest pending
+    pub fn poll_ready(&mut self) -> Vec<Result<T, ActorSendError>> {
+        let mut ready = Vec::new();
+
+        self.pending
+            .retain_mut(|response| match response.rx.try_recv() {
+                Ok(response) => {
+                    ready.push(response);
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Closed) => {
+                    ready.push(Err(ActorSendError::FailedToGetResponse));
+                    false
+                }
+            });
+
+        ready
+    }
+
+    /// Waits for all the pending responses, in the order they were pushed
+    pub async fn await_all(self) -> Vec<Result<T, ActorSendError>> {
+        let mut results = Vec::with_capacity(self.pending.len());
+
+        for response in self.pending {
+            results.push(response.await);
         }
+
+        results
     }
+}
 
-    /// Returns `true` if actor is steel receiving messages.
-    /// 
-    /// The opposite of `is_closed`
-    pub fn is_connected(&self) -> bool {
-        !self.tx.is_closed()
+impl<T> Default for ResponseSet<T> {
+    fn default() -> Self {
+        ResponseSet::new()
+    }
+}
+
+impl<T> FromIterator<ResponseFuture<T>> for ResponseSet<T> {
+    fn from_iter<I: IntoIterator<Item = ResponseFuture<T>>>(iter: I) -> Self {
+        ResponseSet {
+            pending: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Stream returned by [Address::send_ordered]
+pub struct OrderedResponses<T> {
+    pending: VecDeque<Result<ResponseFuture<T>, ActorSendError>>,
+}
+
+impl<T> Stream for OrderedResponses<T> {
+    type Item = Result<T, ActorSendError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let result = match this.pending.front_mut() {
+            Some(Ok(response)) => ready!(Pin::new(response).poll(cx)),
+            Some(Err(err)) => Err(*err),
+            None => return Poll::Ready(None),
+        };
+
+        this.pending.pop_front();
+
+        Poll::Ready(Some(result))
+    }
+}
+
+/// Stream returned by [Address::poll_stream]
+pub struct PollStream<A: Actor, M: Message, F> {
+    address: Address<A>,
+    make_message: F,
+    pending: Option<PendingRequest<M::Result>>,
+    done: bool,
+}
+
+// Nothing is structurally pinned, the pending request is boxed.
+impl<A: Actor, M: Message, F> Unpin for PollStream<A, M, F> {}
+
+impl<A, M, F> Stream for PollStream<A, M, F>
+where
+    A: Handler<M>,
+    M: Message + 'static,
+    F: Fn() -> M,
+{
+    type Item = M::Result;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<M::Result>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let address = &this.address;
+        let make_message = &this.make_message;
+
+        let pending = this.pending.get_or_insert_with(|| {
+            let address = address.clone();
+            let message = make_message();
+
+            Box::pin(async move { address.send(message).await })
+        });
+
+        match pending.as_mut().poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(result) => {
+                this.pending = None;
+
+                match result {
+                    Ok(response) => Poll::Ready(Some(response)),
+                    Err(_) => {
+                        this.done = true;
+
+                        Poll::Ready(None)
+                    }
+                }
+            }
+        }
     }
 }
 
 pub struct WeakAddress<A: Actor> {
     tx: WeakSender<Box<dyn Envelope<A> + Send>>,
+    priority_tx: WeakSender<Box<dyn Envelope<A> + Send>>,
+    // The control mailbox doesn't keep the actor alive
+    control: Option<ControlAddress<A>>,
+    pub(crate) status: Arc<Status>,
+    alive: Weak<oneshot::Sender<()>>,
 }
 
 impl<A: Actor> WeakAddress<A> {
     pub fn upgrade(&self) -> Option<Address<A>> {
-        self.tx.upgrade().map(|tx| Address { tx })
+        Some(Address {
+            tx: self.tx.upgrade()?,
+            priority_tx: self.priority_tx.upgrade()?,
+            control: self.control.clone(),
+            status: self.status.clone(),
+            alive: self.alive.upgrade()?,
+        })
+    }
+
+    /// Number of `Address` instances keeping the actor alive
+    pub fn strong_count(&self) -> usize {
+        self.tx.strong_count()
+    }
+
+    /// Number of `WeakAddress` instances, this one included
+    pub fn weak_count(&self) -> usize {
+        self.tx.weak_count()
     }
 }
 
@@ -115,6 +1222,49 @@ impl<A: Actor> Clone for WeakAddress<A> {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
+            priority_tx: self.priority_tx.clone(),
+            control: self.control.clone(),
+            status: self.status.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+}
+
+pub struct WeakUnboundedAddress<A: Actor> {
+    tx: WeakUnboundedSender<Box<dyn Envelope<A> + Send>>,
+    len: Arc<AtomicUsize>,
+    limit: usize,
+    room: Arc<Notify>,
+}
+
+impl<A: Actor> WeakUnboundedAddress<A> {
+    pub fn upgrade(&self) -> Option<UnboundedAddress<A>> {
+        Some(UnboundedAddress {
+            tx: self.tx.upgrade()?,
+            len: self.len.clone(),
+            limit: self.limit,
+            room: self.room.clone(),
+        })
+    }
+
+    /// Number of `UnboundedAddress` instances
+    pub fn strong_count(&self) -> usize {
+        self.tx.strong_count()
+    }
+
+    /// Number of `WeakUnboundedAddress` instances, this one included
+    pub fn weak_count(&self) -> usize {
+        self.tx.weak_count()
+    }
+}
+
+impl<A: Actor> Clone for WeakUnboundedAddress<A> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            len: self.len.clone(),
+            limit: self.limit,
+            room: self.room.clone(),
         }
     }
 }
@@ -126,13 +1276,12 @@ impl<A: Actor> UnboundedAddress<A> {
     {
         let (tx, rx) = oneshot::channel();
 
-        let packed = MessageWithSender { message, tx };
+        let packed = MessageWithSender::new(message, tx);
 
-        self.tx
-            .send(Box::new(packed))
+        self.deliver_limited(Box::new(packed))
             .map_err(|_| ActorSendError::FailedToDeliver)?;
 
-        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+        rx.await.unwrap_or(Err(ActorSendError::FailedToGetResponse))
     }
 
     /// Sends a message and unpacks the result
@@ -152,66 +1301,227 @@ impl<A: Actor> UnboundedAddress<A> {
         }
     }
 
+    /// Sends a message and unpacks the result flattening the domain error
+    ///
+    /// Works like `send_unpack` for handlers whose result unpacks into
+    /// `Result<T, E>`, but returns `Result<T, SendUnpackError<E>>` instead
+    /// of the nested form.
+    pub async fn send_unpack_flat<M: Message + 'static, T, E>(
+        &self,
+        message: M,
+    ) -> Result<T, SendUnpackError<E>>
+    where
+        A: Handler<M>,
+        M::Result: UnpackableResult<UnpackedResult = Result<T, E>>,
+    {
+        match self.send_unpack(message).await {
+            Ok(Ok(v)) => Ok(v),
+            Ok(Err(err)) => Err(SendUnpackError::Domain(err)),
+            Err(err) => Err(SendUnpackError::Send(err)),
+        }
+    }
+
     pub fn notify<M: Message + 'static>(&self, message: M) -> Result<(), FailedToDeliver>
     where
         A: Handler<M>,
     {
-        self.tx.send(Box::new(message)).map_err(|_| FailedToDeliver)
+        self.deliver_limited(Box::new(message))
+    }
+
+    /// Sends a notification waiting for room in the mailbox if it is full,
+    /// fails only if the actor has stopped.
+    ///
+    /// Gives the same backpressure as [Address::notify] to the ones sending
+    /// to a mailbox bounded with
+    /// [Actor::PRIVATE_MAILBOX_CAPACITY](crate::Actor::PRIVATE_MAILBOX_CAPACITY),
+    /// it doesn't wait if the mailbox is unbounded.
+    pub async fn notify_async<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        self.deliver_async(Box::new(message)).await
     }
 
-    pub fn notify_later<M: Message + 'static>(&self, message: M, after: Duration) -> ()
+    pub fn notify_later<M: Message + 'static>(&self, message: M, after: Duration) -> SpawnHandle
     where
         A: Handler<M>,
     {
         let address = self.clone();
 
-        tokio::spawn(async move {
+        SpawnHandle::new(tokio::spawn(async move {
             tokio::time::sleep(after).await;
 
             let _ = address.notify(message);
-        });
+        }))
     }
 
-    pub fn notify_at<M: Message + 'static>(&self, message: M, at: Instant) -> ()
+    pub fn notify_at<M: Message + 'static>(&self, message: M, at: Instant) -> SpawnHandle
     where
         A: Handler<M>,
     {
         let address = self.clone();
 
-        tokio::spawn(async move {
+        SpawnHandle::new(tokio::spawn(async move {
             tokio::time::sleep_until(at).await;
 
             let _ = address.notify(message);
-        });
+        }))
     }
 
+    /// The first message is sent right away, see [FirstTick::Immediate].
     pub fn notify_interval<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
         &self,
         create_message: F,
         period: Duration,
-    ) -> JoinHandle<()>
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        self.notify_interval_with(create_message, period, FirstTick::Immediate)
+    }
+
+    /// Same as [UnboundedAddress::notify_interval] with control over when
+    /// the first message is sent
+    pub fn notify_interval_with<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+        first: FirstTick,
+    ) -> SpawnHandle
     where
         A: Handler<M>,
     {
         let address = self.clone();
-        let mut interval = tokio::time::interval(period);
+        let mut interval = first.interval(period);
 
-        tokio::spawn(async move {
+        SpawnHandle::new(tokio::spawn(async move {
             loop {
                 interval.tick().await;
 
                 let message = create_message();
 
-                if let Err(_) = address.send(message).await {
+                if address.send(message).await.is_err() {
                     break;
                 }
             }
-        })
+        }))
+    }
+
+    /// Same as [UnboundedAddress::notify_interval] but each tick is delayed
+    /// by a random offset in `0..max_jitter`.
+    ///
+    /// Offsets are not accumulated, so the ticks stay around the regular
+    /// schedule while actors started together don't fire all at once.
+    pub fn notify_interval_jittered<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+        max_jitter: Duration,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let address = self.clone();
+        let mut interval = tokio::time::interval(period);
+
+        SpawnHandle::new(tokio::spawn(async move {
+            loop {
+                let tick = interval.tick().await;
+
+                tokio::time::sleep_until(tick + max_jitter.mul_f64(random::fraction())).await;
+
+                let message = create_message();
+
+                if address.send(message).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Converts the `UnboundedAddress` to a [`WeakUnboundedAddress`] that
+    /// doesn't keep the private mailbox open, e.g. for long-lived timers
+    /// that should not outlive the actor.
+    pub fn downgrade(&self) -> WeakUnboundedAddress<A> {
+        WeakUnboundedAddress {
+            tx: self.tx.downgrade(),
+            len: self.len.clone(),
+            limit: self.limit,
+            room: self.room.clone(),
+        }
     }
 
     pub fn is_closed(&self) -> bool {
         self.tx.is_closed()
     }
+
+    /// Number of messages waiting in the mailbox
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if there are no messages waiting in the mailbox
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Most messages the mailbox may hold, `None` if it is unbounded, see
+    /// [Actor::PRIVATE_MAILBOX_CAPACITY](crate::Actor::PRIVATE_MAILBOX_CAPACITY)
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.limit).filter(|limit| *limit != usize::MAX)
+    }
+
+    /// Delivers the envelope unless the mailbox is full
+    pub(crate) fn deliver_limited(
+        &self,
+        envelope: Box<dyn Envelope<A> + Send>,
+    ) -> Result<(), FailedToDeliver> {
+        if self.len() >= self.limit {
+            return Err(FailedToDeliver);
+        }
+
+        self.deliver(envelope)
+    }
+
+    /// Delivers the envelope once there is room in the mailbox, see
+    /// [UnboundedAddress::notify_async]
+    pub(crate) async fn deliver_async(
+        &self,
+        envelope: Box<dyn Envelope<A> + Send>,
+    ) -> Result<(), FailedToDeliver> {
+        loop {
+            // Registered before the check, so room made meanwhile is not missed
+            let room = self.room.notified();
+
+            if self.len() < self.limit {
+                return self.deliver(envelope);
+            }
+
+            tokio::select! {
+                _ = room => {}
+                _ = self.tx.closed() => return Err(FailedToDeliver),
+            }
+        }
+    }
+
+    /// Delivers the envelope ignoring the limit, used for built-in
+    /// envelopes that must not be lost
+    pub(crate) fn deliver(
+        &self,
+        envelope: Box<dyn Envelope<A> + Send>,
+    ) -> Result<(), FailedToDeliver> {
+        // Count the message before it could be received to never underflow
+        self.len.fetch_add(1, Ordering::AcqRel);
+
+        self.tx.send(envelope).map_err(|_| {
+            self.len.fetch_sub(1, Ordering::AcqRel);
+
+            FailedToDeliver
+        })
+    }
 }
 
 pub trait Message
@@ -219,33 +1529,153 @@ where
     Self: Send,
 {
     type Result: Send;
+
+    /// Whether the handler is cancelled once the sender stops waiting for
+    /// the result. Set it to `false` for commands with side effects that
+    /// must run to completion, see also
+    /// [Actor::CANCEL_ON_DISCONNECT](crate::Actor::CANCEL_ON_DISCONNECT).
+    const CANCEL_ON_DISCONNECT: bool = true;
+
+    /// Tells whether the handler result means a failure, used in
+    /// [AuditRecord](crate::AuditRecord)s.
+    ///
+    /// Every result is a success by default, override it for fallible
+    /// results, e.g. with `result.is_err()`.
+    fn is_failure(_result: &Self::Result) -> bool {
+        false
+    }
+
+    /// Extracts the error of a result nobody is waiting for, so that it is
+    /// reported to [Actor::on_notification_error] instead of being dropped.
+    ///
+    /// No result is an error by default, override it for fallible results,
+    /// e.g. with `result.err().map(|err| Box::new(err) as _)`.
+    fn notification_error(_result: Self::Result) -> Option<Box<dyn Error + Send>> {
+        None
+    }
+
+    /// Id of the request flow the message belongs to, see [CorrelationId].
+    ///
+    /// Messages are not correlated by default, override it to return the
+    /// id the message carries.
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        None
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[non_exhaustive]
 pub enum ActorSendError {
     FailedToDeliver,
 
     FailedToGetResponse,
+
+    /// Message was rejected because the actor was not started yet, see
+    /// [Actor::REJECT_UNTIL_STARTED]
+    NotReady,
+
+    /// Response didn't arrive before the deadline, see
+    /// [Address::send_deadline]
+    Timeout,
 }
 
 impl Display for ActorSendError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ActorSendError::FailedToDeliver => write!(f, "Failed to deliver message to the actor"),
-            ActorSendError::FailedToGetResponse => write!(f, "Failed to get response from the actor"),
+            ActorSendError::FailedToGetResponse => {
+                write!(f, "Failed to get response from the actor")
+            }
+            ActorSendError::NotReady => write!(f, "Actor is not started yet"),
+            ActorSendError::Timeout => write!(f, "Actor didn't respond before the deadline"),
         }
     }
 }
 
 impl Error for ActorSendError {}
 
+impl From<FailedToDeliver> for ActorSendError {
+    fn from(_: FailedToDeliver) -> Self {
+        ActorSendError::FailedToDeliver
+    }
+}
+
+/// Both failures to deliver and to get the response mean the actor is gone,
+/// which is reported as a broken pipe
+impl From<ActorSendError> for io::Error {
+    fn from(err: ActorSendError) -> Self {
+        let kind = match err {
+            ActorSendError::FailedToDeliver | ActorSendError::FailedToGetResponse => {
+                io::ErrorKind::BrokenPipe
+            }
+            ActorSendError::NotReady => io::ErrorKind::Other,
+            ActorSendError::Timeout => io::ErrorKind::TimedOut,
+        };
+
+        io::Error::new(kind, err)
+    }
+}
+
+/// Error returned by `send_unpack_flat`
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SendUnpackError<E> {
+    /// Message was not delivered or the response was not received
+    Send(ActorSendError),
+
+    /// Actor responded with an error
+    Domain(E),
+}
+
+impl<E: Display> Display for SendUnpackError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendUnpackError::Send(err) => err.fmt(f),
+            SendUnpackError::Domain(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for SendUnpackError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SendUnpackError::Send(err) => Some(err),
+            SendUnpackError::Domain(err) => Some(err),
+        }
+    }
+}
+
+impl<E> From<ActorSendError> for SendUnpackError<E> {
+    fn from(err: ActorSendError) -> Self {
+        SendUnpackError::Send(err)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub struct FailedToDeliver;
 
 impl Display for FailedToDeliver {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f , "Failed to deliver notification")
+        write!(f, "Failed to deliver notification")
     }
 }
 
 impl Error for FailedToDeliver {}
+
+/// When the first message of an interval is sent
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FirstTick {
+    /// Right away, then every period. This is what `notify_interval` does
+    Immediate,
+
+    /// One period from now
+    Delayed,
+}
+
+impl FirstTick {
+    fn interval(self, period: Duration) -> Interval {
+        match self {
+            FirstTick::Immediate => tokio::time::interval(period),
+            FirstTick::Delayed => tokio::time::interval_at(Instant::now() + period, period),
+        }
+    }
+}
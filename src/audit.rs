@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use crate::Message;
+
+/// Record about a handled message sent to
+/// [Actor::audit_recipient](crate::Actor::audit_recipient)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Type name of the handled message
+    pub message_type: &'static str,
+    pub outcome: AuditOutcome,
+    /// Time spent in the handler
+    pub duration: Duration,
+}
+
+/// How handling of a message ended.
+///
+/// Whether a returned result is a failure is decided by
+/// [Message::is_failure], so handlers of messages that don't override it
+/// always succeed unless they panic or time out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    Panicked,
+    TimedOut,
+}
+
+impl Message for AuditRecord {
+    type Result = ();
+}
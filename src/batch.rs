@@ -0,0 +1,98 @@
+use std::any::Any;
+
+use async_trait::async_trait;
+
+use crate::{handler::Envelope, Actor, Context, Message};
+
+/// Handler receiving several queued messages of the same type at once.
+///
+/// Messages sent with [Address::notify_batched](crate::Address::notify_batched)
+/// that are queued back to back in the public mailbox are handled together,
+/// up to [BatchHandler::MAX_BATCH] per call. Collecting never waits for more
+/// messages, so under low load the batches are small. Any other message
+/// ends the batch and is handled right after it.
+///
+/// A batch is handled like a single message, so [Actor::message_timeout],
+/// [Actor::limiter] and the other per-message settings apply to the whole
+/// batch.
+#[async_trait]
+pub trait BatchHandler<M: Message>
+where
+    Self: Actor,
+{
+    /// Most messages handled in one call
+    const MAX_BATCH: usize = 64;
+
+    async fn handle_batch(&mut self, messages: Vec<M>, context: &mut Context<Self>);
+}
+
+/// Message sent with `Address::notify_batched`, the message is taken out once
+/// it joins a batch
+pub(crate) struct Batched<M>(pub Option<M>);
+
+/// Messages collected to be handled with a single call
+struct Batch<M>(Vec<M>);
+
+type BoxedEnvelope<A> = Box<dyn Envelope<A> + Send>;
+
+#[async_trait]
+impl<M: Message + 'static, A: BatchHandler<M>> Envelope<A> for Batched<M> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        if let Some(message) = self.0 {
+            actor.handle_batch(vec![message], context).await;
+        }
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn is_batched(&self) -> bool {
+        true
+    }
+
+    fn collect_batch(
+        self: Box<Self>,
+        next: &mut dyn FnMut() -> Option<BoxedEnvelope<A>>,
+    ) -> (BoxedEnvelope<A>, Option<BoxedEnvelope<A>>) {
+        let mut messages: Vec<M> = self.0.into_iter().collect();
+        let mut rest = None;
+
+        while messages.len() < A::MAX_BATCH {
+            match next() {
+                Some(mut envelope) => {
+                    if !envelope.join_batch(&mut messages) {
+                        rest = Some(envelope);
+
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        (Box::new(Batch(messages)), rest)
+    }
+
+    fn join_batch(&mut self, batch: &mut dyn Any) -> bool {
+        match batch.downcast_mut::<Vec<M>>() {
+            Some(messages) => {
+                messages.extend(self.0.take());
+
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Message + 'static, A: BatchHandler<M>> Envelope<A> for Batch<M> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        actor.handle_batch(self.0, context).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+}
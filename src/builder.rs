@@ -0,0 +1,190 @@
+use std::{any::Any, sync::Arc};
+
+use tokio::{runtime::Handle, sync::mpsc, task::JoinHandle};
+
+use crate::{
+    actor::prepare_with, Actor, Address, ControlAddress, Mailbox, MailboxPolicy, Ready, System,
+    TapStream,
+};
+
+/// Run-time options of an actor collected in one place, returned by
+/// [Actor::builder].
+///
+/// Options left unset fall back to what [Actor::run] does. Each of the
+/// `run_*` methods of [Actor] is a shortcut for one of the options, the
+/// builder combines any of them.
+///
+/// ```
+/// # use akt::{Actor, MailboxPolicy};
+/// #[derive(Clone)]
+/// struct Worker;
+///
+/// impl Actor for Worker {}
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let spawned = Worker
+///     .builder()
+///     .mailbox_capacity(64)
+///     .mailbox_policy(MailboxPolicy::Fair)
+///     .replicable()
+///     .tapped()
+///     .on(tokio::runtime::Handle::current())
+///     .spawn();
+///
+/// spawned.ready.await.unwrap();
+/// # }
+/// ```
+pub struct ActorBuilder<A: Actor> {
+    actor: A,
+    capacity: usize,
+    handle: Option<Handle>,
+    system: Option<Arc<dyn Any + Send + Sync>>,
+    mailbox: Option<Box<dyn Mailbox<A>>>,
+    policy: Option<MailboxPolicy>,
+    template: Option<A>,
+    tapped: bool,
+}
+
+/// Everything a run actor could be reached through, returned by
+/// [ActorBuilder::spawn]
+pub struct Spawned<A: Actor> {
+    pub address: Address<A>,
+
+    /// Address of the control mailbox, see [Actor::run_with_control]
+    pub control: ControlAddress<A>,
+
+    /// Resolves once the actor is started, see [Actor::run_ready]
+    pub ready: Ready,
+
+    /// Handle of the actor task, see [Actor::run_with_handle]. Its
+    /// [JoinHandle::abort_handle] aborts the actor like
+    /// [Actor::run_abortable] does.
+    pub handle: JoinHandle<()>,
+
+    /// Messages handled by the actor if [ActorBuilder::tapped] was set, see
+    /// [Actor::run_tapped]
+    pub tap: Option<TapStream>,
+}
+
+impl<A: Actor> ActorBuilder<A> {
+    pub(crate) fn new(actor: A) -> ActorBuilder<A> {
+        ActorBuilder {
+            capacity: actor.mailbox_capacity(),
+            actor,
+            handle: None,
+            system: None,
+            mailbox: None,
+            policy: None,
+            template: None,
+            tapped: false,
+        }
+    }
+
+    /// Capacity of the public mailbox, [Actor::mailbox_capacity] by default.
+    /// Running the actor panics if it is zero.
+    pub fn mailbox_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Custom public mailbox, see [Actor::run_with_mailbox]
+    pub fn mailbox<M: Mailbox<A>>(mut self, mailbox: M) -> Self {
+        self.mailbox = Some(Box::new(mailbox));
+        self
+    }
+
+    /// Order of the private and public mailboxes, [Actor::MAILBOX_POLICY]
+    /// by default
+    pub fn mailbox_policy(mut self, policy: MailboxPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Runtime the actor runs on, the current one by default
+    pub fn on(mut self, handle: Handle) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// Shared state of the system the actor runs in, see [System]
+    pub fn system<S: Send + Sync + 'static>(mut self, system: System<S>) -> Self {
+        self.system = Some(system.erase());
+        self
+    }
+
+    /// Keeps a copy of the initial state replicas are started from, see
+    /// [Actor::run_replicable]
+    pub fn replicable(mut self) -> Self
+    where
+        A: Clone,
+    {
+        self.template = Some(self.actor.clone());
+        self
+    }
+
+    /// Describes every handled message to [Spawned::tap], see
+    /// [Actor::run_tapped]
+    pub fn tapped(mut self) -> Self {
+        self.tapped = true;
+        self
+    }
+
+    /// Runs the actor returning its address
+    pub fn run(self) -> Address<A> {
+        self.spawn().address
+    }
+
+    /// Runs the actor returning its address and the handle of its task, see
+    /// [Actor::run_with_handle]
+    pub fn run_with_handle(self) -> (Address<A>, JoinHandle<()>) {
+        let spawned = self.spawn();
+
+        (spawned.address, spawned.handle)
+    }
+
+    /// Runs the actor returning everything it could be reached through
+    pub fn spawn(self) -> Spawned<A> {
+        let ActorBuilder {
+            actor,
+            capacity,
+            handle,
+            system,
+            mailbox,
+            policy,
+            template,
+            tapped,
+        } = self;
+
+        let (tap_tx, tap) = if tapped {
+            let (tx, rx) = mpsc::unbounded_channel();
+
+            (Some(tx), Some(TapStream::new(rx)))
+        } else {
+            (None, None)
+        };
+
+        let prepared = prepare_with(actor, capacity, move |context| {
+            context.system = system;
+            context.mailbox = mailbox;
+            context.mailbox_policy = policy;
+            context.template = template;
+            context.tap = tap_tx;
+        });
+
+        let handle = match handle {
+            Some(handle) => handle.spawn(prepared.job),
+            None => tokio::spawn(prepared.job),
+        };
+
+        Spawned {
+            address: prepared.address,
+            control: prepared.control_address,
+            ready: Ready {
+                rx: prepared.started,
+            },
+            handle,
+            tap,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+
+use crate::{Actor, Context, Handler, Message, Recipient};
+
+/// Actor broadcasting published events to every subscriber.
+///
+/// Each event is cloned for every subscriber and delivered as a
+/// notification, subscribers whose actors are gone are pruned on publish.
+/// Delivery waits for free space in each subscriber mailbox, so a slow
+/// subscriber holds the bus back.
+///
+/// ```
+/// # use akt::{Actor, Context, EventBus, Handler, Message, Publish, Subscribe};
+/// # use async_trait::async_trait;
+/// #[derive(Clone)]
+/// struct UserCreated(u64);
+///
+/// impl Message for UserCreated {
+///     type Result = ();
+/// }
+///
+/// struct Mailer;
+///
+/// impl Actor for Mailer {}
+///
+/// #[async_trait]
+/// impl Handler<UserCreated> for Mailer {
+///     async fn handle(&mut self, _event: UserCreated, _context: &mut Context<Self>) {}
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let bus = EventBus::<UserCreated>::new().run();
+///
+/// bus.send(Subscribe(Mailer.run().into())).await.unwrap();
+/// bus.send(Publish(UserCreated(1))).await.unwrap();
+/// # }
+/// ```
+pub struct EventBus<E: Message> {
+    subscribers: Vec<(SubscriptionId, Recipient<E>)>,
+    next_id: u64,
+}
+
+impl<E: Message> EventBus<E> {
+    pub fn new() -> EventBus<E> {
+        EventBus {
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<E: Message> Default for EventBus<E> {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
+
+impl<E: Message + Clone + 'static> Actor for EventBus<E> {}
+
+/// Identifier of a subscription returned for [Subscribe]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Subscribes the recipient to the events published on the bus
+pub struct Subscribe<E: Message>(pub Recipient<E>);
+
+impl<E: Message + 'static> Message for Subscribe<E> {
+    type Result = SubscriptionId;
+}
+
+/// Removes the subscription, results in `false` if it was already removed
+pub struct Unsubscribe(pub SubscriptionId);
+
+impl Message for Unsubscribe {
+    type Result = bool;
+}
+
+/// Delivers the event to every subscriber, results in the number of
+/// subscribers it was delivered to
+pub struct Publish<E>(pub E);
+
+impl<E: Message + Clone> Message for Publish<E> {
+    type Result = usize;
+}
+
+#[async_trait]
+impl<E: Message + Clone + 'static> Handler<Subscribe<E>> for EventBus<E> {
+    async fn handle(
+        &mut self,
+        message: Subscribe<E>,
+        _context: &mut Context<Self>,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+
+        self.subscribers.push((id, message.0));
+
+        id
+    }
+}
+
+#[async_trait]
+impl<E: Message + Clone + 'static> Handler<Unsubscribe> for EventBus<E> {
+    async fn handle(&mut self, message: Unsubscribe, _context: &mut Context<Self>) -> bool {
+        let before = self.subscribers.len();
+
+        self.subscribers.retain(|(id, _)| *id != message.0);
+
+        self.subscribers.len() != before
+    }
+}
+
+#[async_trait]
+impl<E: Message + Clone + 'static> Handler<Publish<E>> for EventBus<E> {
+    async fn handle(&mut self, message: Publish<E>, _context: &mut Context<Self>) -> usize {
+        self.subscribers
+            .retain(|(_, subscriber)| subscriber.is_connected());
+
+        let mut delivered = 0;
+        let mut disconnected = Vec::new();
+
+        for (id, subscriber) in &self.subscribers {
+            match subscriber.notify(message.0.clone()).await {
+                Ok(()) => delivered += 1,
+                Err(_) => disconnected.push(*id),
+            }
+        }
+
+        self.subscribers
+            .retain(|(id, _)| !disconnected.contains(id));
+
+        delivered
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Signal handlers may observe to stop long-running work early.
+///
+/// Each actor has its own token returned by
+/// [Context::cancellation_token](crate::Context::cancellation_token), it is
+/// cancelled once the actor starts stopping. Clones share the same state.
+///
+/// ```
+/// # use akt::{Actor, Context, Handler, Message};
+/// # use async_trait::async_trait;
+/// # struct Crawler;
+/// # impl Actor for Crawler {}
+/// struct Crawl;
+///
+/// impl Message for Crawl {
+///     type Result = ();
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Crawl> for Crawler {
+///     async fn handle(&mut self, _message: Crawl, context: &mut Context<Self>) {
+///         let token = context.cancellation_token();
+///
+///         tokio::select! {
+///             _ = token.cancelled() => {}
+///             _ = tokio::time::sleep(std::time::Duration::from_secs(60)) => {}
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    tx: Arc<watch::Sender<bool>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken {
+            tx: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    /// Cancels the token waking up everybody waiting for it
+    pub fn cancel(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    /// Resolves once the token is cancelled
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+
+        // The sender is owned by the token, so the channel is never closed
+        let _ = rx.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
+    }
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fmt::Display};
+
+/// Conversion of a value to and from bytes.
+///
+/// It is the only thing the remote machinery and persisted messages need
+/// from a serialization format, so implementing it on top of serde or any
+/// other library is a matter of two calls.
+pub trait Codec: Sized {
+    fn encode(&self) -> Vec<u8>;
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DecodeError {
+    reason: String,
+}
+
+impl DecodeError {
+    pub fn new<R: Into<String>>(reason: R) -> DecodeError {
+        DecodeError {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to decode: {}", self.reason)
+    }
+}
+
+impl Error for DecodeError {}
+
+impl Codec for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        String::from_utf8(bytes.to_vec()).map_err(|err| DecodeError::new(err.to_string()))
+    }
+}
+
+impl Codec for () {
+    fn encode(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn decode(_bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::{atomic::AtomicUsize, Arc};
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    handler::{cancel_on_disconnect, sender_closed, ConcurrentHandling, Envelope, Response},
+    Actor, ActorSendError, Context, Message,
+};
+
+/// Handler that doesn't mutate the actor and may run concurrently.
+///
+/// Messages sent with [Address::send_concurrent](crate::Address::send_concurrent)
+/// or [Address::notify_concurrent](crate::Address::notify_concurrent) are
+/// handled by up to [Actor::MAX_CONCURRENCY] handlers at once, all of them
+/// sharing the actor. Any other message waits until the concurrent handlers
+/// in flight are done and then runs exclusively as usual.
+///
+/// Concurrent handlers don't get the [Context] and are not covered by
+/// [Actor::message_timeout], [Actor::watchdog_timeout], [Actor::limiter],
+/// [Actor::audit_recipient] and [Actor::map_response]. Their panics are still reported to
+/// [Actor::on_panic].
+#[async_trait]
+pub trait ConcurrentHandler<M: Message>
+where
+    Self: Actor + Sync,
+{
+    async fn handle(&self, message: M) -> M::Result;
+}
+
+pub(crate) struct ConcurrentMessageWithSender<M: Message> {
+    pub message: M,
+    pub tx: oneshot::Sender<Response<M::Result>>,
+}
+
+pub(crate) struct ConcurrentNotification<M>(pub M);
+
+#[async_trait]
+impl<M: Message + 'static, A: ConcurrentHandler<M>> Envelope<A> for ConcurrentMessageWithSender<M>
+where
+    M::Result: 'static,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        self.handle_concurrent(actor, context.critical.clone())
+            .await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn is_concurrent(&self) -> bool {
+        true
+    }
+
+    fn handle_concurrent<'a>(
+        self: Box<Self>,
+        actor: &'a A,
+        critical: Arc<AtomicUsize>,
+    ) -> ConcurrentHandling<'a> {
+        let ConcurrentMessageWithSender { message, mut tx } = *self;
+
+        Box::pin(async move {
+            tokio::select! {
+              // Drop computation if receiver is no longer interested in it
+              _ = sender_closed(&mut tx, &critical), if cancel_on_disconnect::<A, M>() => {}
+              result = ConcurrentHandler::handle(actor, message) => {
+                let _ = tx.send(Ok(result));
+              }
+            }
+
+            None
+        })
+    }
+
+    async fn reject(self: Box<Self>) {
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
+
+#[async_trait]
+impl<M: Message + 'static, A: ConcurrentHandler<M>> Envelope<A> for ConcurrentNotification<M> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let error = self
+            .handle_concurrent(actor, context.critical.clone())
+            .await;
+
+        if let Some(error) = error {
+            actor.on_notification_error(error, context).await;
+        }
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn is_concurrent(&self) -> bool {
+        true
+    }
+
+    fn handle_concurrent<'a>(
+        self: Box<Self>,
+        actor: &'a A,
+        _critical: Arc<AtomicUsize>,
+    ) -> ConcurrentHandling<'a> {
+        Box::pin(async move {
+            let result = ConcurrentHandler::handle(actor, self.0).await;
+
+            M::notification_error(result)
+        })
+    }
+}
@@ -1,27 +1,169 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    convert::TryFrom,
+    future::Future,
+    marker::PhantomData,
+    ops::Deref,
+    pin::Pin,
+    sync::{atomic::AtomicUsize, Arc},
+    time::{Duration, SystemTime},
+};
+
+use tokio::{
+    runtime::Handle,
+    select,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+        oneshot::{self, error::TryRecvError},
+        Semaphore,
+    },
+    task::{AbortHandle, JoinHandle},
+    time::Instant,
+};
+
 use crate::{
-    address::{UnboundedAddress, WeakAddress},
-    Actor,
+    actor::prepare_with,
+    cancel::CancellationToken,
+    address::{Address, FirstTick, UnboundedAddress, WeakAddress},
+    control::ControlAddress,
+    correlation::{self, CorrelationId},
+    handler::{lock, CriticalGuard, FinalMessage, ReplyHandle, ReplySlot},
+    mailbox::Mailbox,
+    recipient::MappedSender,
+    retry::{self, RetryPolicy},
+    shutdown::{Registration, ShutdownCoordinator},
+    spawn::SpawnHandle,
+    stream::{StreamExt, StreamFinished, StreamItem, StreamLagged},
+    tap::TappedMessage,
+    Actor, ActorSendError, FailedToDeliver, Handler, MailboxPolicy, Message, MessageSource,
+    Recipient, Stream, StreamHandler, System,
 };
 
+type Finalizer = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
 
 /// Context passed to each handler
 pub struct Context<A: Actor> {
     address: WeakAddress<A>,
     private_address: UnboundedAddress<A>,
+    control_address: ControlAddress<A>,
     pub(crate) state: ActorState,
+    pub(crate) drain_on_stop: bool,
+    pub(crate) reply: Option<Box<dyn Any + Send>>,
+    pub(crate) expects_reply: bool,
+    pub(crate) failed: bool,
+    pub(crate) paused: bool,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) system: Option<Arc<dyn Any + Send + Sync>>,
+    pub(crate) replacement: Option<A>,
+    // Replacement failed to start, the actor stops without handling more
+    pub(crate) start_failed: bool,
+    // Initial state replicas are started from, see `Context::spawn_self`
+    pub(crate) template: Option<A>,
+    // Depth of critical sections the handler is in, see
+    // `Context::critical_section`
+    pub(crate) critical: Arc<AtomicUsize>,
+    pub(crate) correlation: Option<CorrelationId>,
+    pub(crate) wait_for_replies: bool,
+    // Resolve once the reply handles taken over by handlers are gone
+    pub(crate) taken_replies: Vec<oneshot::Receiver<()>>,
+    pub(crate) cancellation: CancellationToken,
+    pub(crate) handled: u64,
+    pub(crate) tap: Option<mpsc::UnboundedSender<TappedMessage>>,
+    pub(crate) mailbox: Option<Box<dyn Mailbox<A>>>,
+    pub(crate) mailbox_policy: Option<MailboxPolicy>,
+    pub(crate) source: MessageSource,
+    started_at: Instant,
+    last_yield: Instant,
+    debounced: HashMap<TypeId, AbortHandle>,
+    schedules: HashMap<String, SpawnHandle>,
+    piped: Arc<Semaphore>,
+    linked: Vec<Box<dyn FnOnce() + Send>>,
+    finalizers: Vec<Finalizer>,
+    // Dropped along with the context once the actor is gone
+    terminated: Vec<oneshot::Sender<()>>,
 }
 
 impl<A: Actor> Context<A> {
-    pub fn new(
+    pub(crate) fn new(
         address: WeakAddress<A>,
         private_address: UnboundedAddress<A>,
+        control_address: ControlAddress<A>,
         state: ActorState,
     ) -> Context<A> {
         Context {
             address,
             private_address,
+            control_address,
             state,
+            drain_on_stop: false,
+            reply: None,
+            expects_reply: false,
+            failed: false,
+            paused: false,
+            idle_timeout: None,
+            system: None,
+            replacement: None,
+            start_failed: false,
+            template: None,
+            critical: Arc::default(),
+            correlation: None,
+            wait_for_replies: false,
+            taken_replies: Vec::new(),
+            cancellation: CancellationToken::new(),
+            handled: 0,
+            tap: None,
+            mailbox: None,
+            mailbox_policy: None,
+            source: MessageSource::Public,
+            started_at: Instant::now(),
+            last_yield: Instant::now(),
+            debounced: HashMap::new(),
+            schedules: HashMap::new(),
+            piped: Arc::new(Semaphore::new(A::MAX_PIPED_TASKS)),
+            linked: Vec::new(),
+            finalizers: Vec::new(),
+            terminated: Vec::new(),
+        }
+    }
+
+    /// Mailbox the message being handled was received from.
+    ///
+    /// Lets handlers tell messages from outside, which may need stricter
+    /// checks, from the ones the actor and its children sent. Messages
+    /// handled by a [ConcurrentHandler](crate::ConcurrentHandler) don't get
+    /// a context, so they are not reported.
+    pub fn message_source(&self) -> MessageSource {
+        self.source
+    }
+
+    /// Time elapsed since the actor began starting
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Number of messages the actor has handled so far, a batch of
+    /// [BatchHandler](crate::BatchHandler) messages counts once
+    pub fn messages_handled(&self) -> u64 {
+        self.handled
+    }
+
+    /// Yields to other tasks on the runtime if the actor has been running
+    /// for longer than [Actor::YIELD_BUDGET] since it last yielded here or
+    /// since it started.
+    ///
+    /// Meant to be called regularly from long CPU-bound handlers, so that
+    /// they don't starve other tasks without paying for a yield on every
+    /// step.
+    pub async fn yield_if_needed(&mut self) {
+        if self.last_yield.elapsed() < A::YIELD_BUDGET {
+            return;
         }
+
+        tokio::task::yield_now().await;
+
+        self.last_yield = Instant::now();
     }
 
     /// Public address
@@ -33,6 +175,30 @@ impl<A: Actor> Context<A> {
         self.address.clone()
     }
 
+    /// Public address keeping the actor alive while it is held, `None` once
+    /// the actor is stopping or all public addresses are gone.
+    ///
+    /// Useful for tasks spawned by a handler that must finish before the
+    /// actor stops.
+    pub fn strong_address(&self) -> Option<Address<A>> {
+        if matches!(self.state, ActorState::Stopping | ActorState::Stopped) {
+            return None;
+        }
+
+        self.address.upgrade()
+    }
+
+    /// Token keeping the actor alive until dropped, `None` once the actor is
+    /// stopping or all public addresses are gone.
+    ///
+    /// Move it into a task spawned by a handler, so that the actor is still
+    /// around when the task reports back. A guard held forever keeps the
+    /// actor from stopping on its own just like any other [Address].
+    pub fn guard(&self) -> Option<AddressGuard<A>> {
+        self.strong_address()
+            .map(|address| AddressGuard { address })
+    }
+
     /// Private address, prioritized and unbound.
     ///
     /// Should be used only by the current actor and other actors controlled by it.
@@ -43,16 +209,886 @@ impl<A: Actor> Context<A> {
         self.private_address.clone()
     }
 
+    /// Control address, handled with strict priority over both the public
+    /// and the private mailboxes, see [ControlAddress]
+    pub fn control_address(&self) -> ControlAddress<A> {
+        self.control_address.clone()
+    }
+
+    /// Own address accepting only `M` messages.
+    ///
+    /// It is built on top of the private address, so it doesn't prevent
+    /// the actor from stopping and its messages are prioritized.
+    pub fn recipient<M: Message + 'static>(&self) -> Recipient<M>
+    where
+        A: Handler<M>,
+    {
+        Recipient::from(self.private_address.clone())
+    }
+
+    /// Own address accepting only `M` messages, built on top of the public
+    /// address. `None` once the actor is stopping or all public addresses
+    /// are gone.
+    ///
+    /// Unlike [Context::recipient] its messages are bounded by the public
+    /// mailbox and ordered with the other public messages, and holding it
+    /// keeps the actor alive just like any other [Address].
+    pub fn public_recipient<M: Message + 'static>(&self) -> Option<Recipient<M>>
+    where
+        A: Handler<M>,
+    {
+        self.strong_address().map(Recipient::from)
+    }
+
+    /// Id of the request flow the message being handled belongs to, see
+    /// [CorrelationId]
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        self.correlation
+    }
+
+    /// Returns `true` if the message being handled was sent as a request
+    /// and `false` if it is a notification nobody waits a result for.
+    pub fn expects_reply(&self) -> bool {
+        self.expects_reply
+    }
+
+    /// Takes over the reply of the message being handled.
+    ///
+    /// Once taken, the value returned by the handler is discarded and the
+    /// caller receives only what is sent through the returned handle. This
+    /// lets the handler return immediately, unblocking the message loop, and
+    /// respond later, e.g. from a spawned task. Returns `None` for
+    /// notifications or if the reply was already taken.
+    pub fn take_reply<M: Message>(&mut self) -> Option<ReplyHandle<M::Result>>
+    where
+        M::Result: 'static,
+    {
+        let slot = self.reply.as_ref()?.downcast_ref::<ReplySlot<M::Result>>()?;
+        let tx = lock(slot).take()?;
+
+        let (done, taken) = oneshot::channel();
+
+        // Forget replies that were already sent
+        self.taken_replies
+            .retain_mut(|taken| !matches!(taken.try_recv(), Err(TryRecvError::Closed)));
+        self.taken_replies.push(taken);
+
+        Some(ReplyHandle::new(tx, done))
+    }
+
+    /// Runs the future to completion even if the caller stops waiting for
+    /// the response meanwhile.
+    ///
+    /// A handler cancelled on disconnect, see [Actor::CANCEL_ON_DISCONNECT],
+    /// is dropped at an await point, which could leave the actor half
+    /// updated. Within the section the disconnect is ignored, once it is
+    /// left the handler is cancelled as usual. Timeouts set with
+    /// [Actor::message_timeout] still apply.
+    pub async fn critical_section<F: Future>(&mut self, future: F) -> F::Output {
+        let guard = CriticalGuard::enter(&self.critical);
+        let output = future.await;
+
+        drop(guard);
+
+        // Gives the pending disconnect a chance to cancel the handler
+        tokio::task::yield_now().await;
+
+        output
+    }
+
+    /// Sends a message to another actor retrying failed calls according to
+    /// the policy.
+    ///
+    /// Both failing to deliver and failing to get a response are retried,
+    /// the last error is returned once attempts are exhausted. The returned
+    /// future doesn't borrow the context, so it may be awaited within the
+    /// handler as well as spawned.
+    pub fn call_with_retry<B, M>(
+        &self,
+        to: &Address<B>,
+        message: M,
+        policy: RetryPolicy,
+    ) -> impl Future<Output = Result<M::Result, ActorSendError>> + Send + 'static
+    where
+        B: Handler<M>,
+        M: Message + Clone + 'static,
+    {
+        let to = to.clone();
+
+        async move { retry::call_with_retry(&to, message, policy, |_| true).await }
+    }
+
+    /// Runs blocking code on the tokio blocking pool.
+    ///
+    /// The message loop keeps handling other messages meanwhile. Returning
+    /// the receiver from a handler lets the caller get the value with
+    /// [Address::send_unpack]. The receiver fails if `f` panics.
+    pub fn run_blocking<T, F>(&self, f: F) -> oneshot::Receiver<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(f());
+        });
+
+        rx
+    }
+
+    /// Runs the future in a separate task tied to the actor lifecycle.
+    ///
+    /// The task is dropped at its current await point as soon as the
+    /// [cancellation token](Context::cancellation_token) is cancelled, and
+    /// aborted once the actor stops if it didn't react to the token by then.
+    pub fn spawn_scoped<F>(&mut self, future: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.cancellation.clone();
+
+        let handle = tokio::spawn(async move {
+            select! {
+                _ = token.cancelled() => {}
+                _ = future => {}
+            }
+        });
+
+        let abort = handle.abort_handle();
+        self.linked.push(Box::new(move || abort.abort()));
+
+        handle
+    }
+
+    /// Stops receiving messages from the public mailboxes.
+    ///
+    /// Messages sent through [Address] keep buffering meanwhile and once
+    /// the mailbox bound is reached senders wait, which applies
+    /// backpressure. Requests wait for the response until the actor
+    /// resumes and handles them, [Address::send_timeout] bounds the wait.
+    /// Control and private messages are still handled, so one of them
+    /// should eventually call [Context::resume].
+    ///
+    /// Once all public addresses are dropped the actor stops as usual, even
+    /// while paused. Public messages still buffered by then are dropped.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes receiving messages paused with [Context::pause]
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if public messages are paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Stops actor gracefully
+    ///
+    /// State transitions requested from a handler take effect only after
+    /// the handler returns and its reply is sent, so the caller of the
+    /// current message always receives the response.
     pub fn stop(&mut self) {
         self.state = ActorState::Stopping;
     }
+
+    /// Stops actor right after handling `message`.
+    ///
+    /// The message goes to the private mailbox, so with
+    /// [MailboxPolicy::PrivateFirst] only control messages and private
+    /// messages that are already queued are handled before it. With
+    /// [MailboxPolicy::PublicFirst] and [MailboxPolicy::Fair] the public
+    /// messages waiting by then may be handled before it too.
+    ///
+    /// The stop then goes through [Actor::on_stopping] as usual and no
+    /// public message is handled after the final one, unless the hook
+    /// cancels the stop and the actor keeps receiving from every mailbox.
+    /// The stop is requested even if the handler panics or times out.
+    pub fn stop_with_final_message<M: Message + 'static>(&mut self, message: M)
+    where
+        A: Handler<M>,
+    {
+        let _ = self
+            .private_address
+            .deliver(Box::new(FinalMessage(message)));
+    }
+
+    /// Stops actor once the replies taken over with [Context::take_reply]
+    /// are sent.
+    ///
+    /// Replies returned from handlers are always sent before a stop takes
+    /// effect, so this differs from [Context::stop] only for replies taken
+    /// over: once the stop is confirmed by [Actor::on_stopping] no more
+    /// messages are received until every taken [ReplyHandle] is sent or
+    /// dropped.
+    pub fn stop_after_reply(&mut self) {
+        self.wait_for_replies = true;
+        self.state = ActorState::Stopping;
+    }
+
+    /// Token cancelled once the actor starts stopping.
+    ///
+    /// It is cancelled when a requested stop is confirmed by
+    /// [Actor::on_stopping] or when the actor finds out all addresses were
+    /// dropped, which happens only after the current handler returns. While
+    /// the mailboxes are drained by [Context::stop_graceful] the token is
+    /// already cancelled. Handlers may pass its clones to the tasks they
+    /// spawn.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Shared state of the [System] the actor runs in, `None` if it runs
+    /// outside of a system or the system state is not `S`
+    pub fn system<S: Send + Sync + 'static>(&self) -> Option<System<S>> {
+        System::restore(self.system.clone()?)
+    }
+
+    /// Stops actor after handling all the messages already queued.
+    ///
+    /// The public mailbox is closed so no new messages are accepted, but
+    /// everything buffered in both mailboxes is handled before
+    /// [Actor::on_stopped] runs.
+    pub fn stop_graceful(&mut self) {
+        self.drain_on_stop = true;
+        self.state = ActorState::Stopping;
+    }
+
+    /// Replaces the running actor with `next` once the current handler
+    /// returns.
+    ///
+    /// The address, mailboxes and linked children are kept. The replaced
+    /// actor gets [Actor::on_stopped] and `next` gets [Actor::try_start], so
+    /// both ends of a state transition can set up and tear down their own
+    /// resources. If `next` fails to start it handles no message, the
+    /// actor stops right away as if it failed to start in the first place.
+    /// Calling it again within the same handler replaces the pending value.
+    pub fn replace(&mut self, next: A) {
+        self.replacement = Some(next);
+    }
+
+    /// Starts one more instance of the actor sharing nothing with this one,
+    /// e.g. to route overflow there once it is overloaded.
+    ///
+    /// The replica is cloned from the state the actor was run with by
+    /// [Actor::run_replicable], so it can spawn replicas too. Returns `None`
+    /// if the actor was run any other way.
+    pub fn spawn_self(&self) -> Option<Address<A>>
+    where
+        A: Clone,
+    {
+        self.template.clone().map(Actor::run_replicable)
+    }
+
+    /// Stops the actor once no message has been handled for `idle`.
+    ///
+    /// Each handled message restarts the countdown. The stop goes through
+    /// [Actor::on_stopping] with [StopReason::Requested], if the hook
+    /// cancels it the actor waits for another idle period. Calling it again
+    /// replaces the period.
+    pub fn stop_after(&mut self, idle: Duration) {
+        self.idle_timeout = Some(idle);
+    }
+
+    /// Runs a child actor linked to the current one.
+    ///
+    /// Linked children are stopped once the current actor is stopped.
+    /// Children that are already gone by then are skipped.
+    pub fn spawn_linked<B: Actor>(&mut self, child: B) -> Address<B> {
+        self.spawn_child(child).0
+    }
+
+    /// Runs a child actor linked to the current one, events the child
+    /// emits are mapped into messages of the current actor.
+    ///
+    /// `create` builds the child out of a [Recipient] of its events. Each
+    /// event is mapped with `map` and sent to the current actor through
+    /// its private address, so the child doesn't keep the current actor
+    /// alive. Responses to mapped messages are passed back to the child.
+    pub fn spawn_mapped<B, E, M, C, F>(&mut self, create: C, map: F) -> Address<B>
+    where
+        B: Actor,
+        E: Message + 'static,
+        M: Message<Result = E::Result> + 'static,
+        C: FnOnce(Recipient<E>) -> B,
+        F: Fn(E) -> M + Send + Sync + 'static,
+        A: Handler<M>,
+    {
+        let events = Recipient::new(MappedSender::new(
+            self.private_address.clone(),
+            Arc::new(map),
+        ));
+
+        self.spawn_linked(create(events))
+    }
+
+    /// Runs a child actor stopped either when the returned guard is dropped
+    /// or when the current actor is stopped, whichever happens first.
+    pub fn spawn_guarded<B: Actor>(&mut self, child: B) -> ChildGuard<B> {
+        let (address, control_address) = self.spawn_child(child);
+
+        ChildGuard {
+            address,
+            control_address,
+        }
+    }
+
+    fn spawn_child<B: Actor>(&mut self, child: B) -> (Address<B>, ControlAddress<B>) {
+        let system = self.system.clone();
+
+        let capacity = child.mailbox_capacity();
+        let prepared = prepare_with(child, capacity, move |context| {
+            context.system = system;
+        });
+        let (address, control_address) = (prepared.address, prepared.control_address);
+
+        tokio::spawn(prepared.job);
+
+        let linked_address = control_address.clone();
+
+        self.linked.push(Box::new(move || {
+            let _ = linked_address.stop();
+        }));
+
+        (address, control_address)
+    }
+
+    /// Registers the actor to be stopped by the coordinator, see
+    /// [ShutdownCoordinator]
+    pub fn register_for_shutdown(&mut self, coordinator: &ShutdownCoordinator) {
+        let registration = self.shutdown_registration();
+
+        coordinator.register(registration);
+    }
+
+    /// Registers the actor to be stopped by the coordinator under `name`,
+    /// declaring the actors it depends on.
+    ///
+    /// With [ShutdownOrder::Dependencies](crate::ShutdownOrder::Dependencies)
+    /// the actors named in `depends_on` are stopped only after this one is
+    /// gone, e.g. a buffer flushing to a writer depends on the writer.
+    pub fn register_for_shutdown_named(
+        &mut self,
+        coordinator: &ShutdownCoordinator,
+        name: &str,
+        depends_on: &[&str],
+    ) {
+        let registration = self.shutdown_registration();
+
+        coordinator.register(registration.with_dependencies(name, depends_on));
+    }
+
+    fn shutdown_registration(&mut self) -> Registration {
+        let (tx, rx) = oneshot::channel();
+        let control_address = self.control_address.clone();
+
+        self.terminated.push(tx);
+
+        let stop = Box::new(move || {
+            let _ = control_address.stop();
+        });
+
+        Registration::new(stop, rx)
+    }
+
+    /// Registers cleanup to run once the actor is stopped, after
+    /// [Actor::on_stopped].
+    ///
+    /// Finalizers run in reverse order of registration, whether the actor
+    /// stopped on its own or was asked to. If the actor task is aborted, the
+    /// remaining finalizers are spawned onto the runtime on a best-effort
+    /// basis, which doesn't happen if the runtime itself is shutting down.
+    pub fn on_finalize<F, Fut>(&mut self, finalizer: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.finalizers
+            .push(Box::new(move || Box::pin(finalizer())));
+    }
+
+    /// Runs the registered finalizers, the last registered first
+    pub(crate) async fn finalize(&mut self) {
+        while let Some(finalizer) = self.finalizers.pop() {
+            finalizer().await;
+        }
+    }
+
+    /// Publishes the state to the addresses of the actor
+    pub(crate) fn publish_state(&self, state: ActorState) {
+        self.address.status.set_state(state);
+    }
+
+    /// Describes the message about to be handled to the tap, if any
+    pub(crate) fn tap(&self, message_type: &'static str) {
+        if let Some(tap) = &self.tap {
+            let _ = tap.send(TappedMessage {
+                message_type,
+                at: SystemTime::now(),
+            });
+        }
+    }
+
+    pub(crate) fn stop_linked(&mut self) {
+        for stop in self.linked.drain(..) {
+            stop();
+        }
+
+        for (_, timer) in self.schedules.drain() {
+            timer.abort();
+        }
+    }
+
+    /// Sends a notification to the actor itself through the private address
+    pub fn notify<M: Message + 'static>(&self, message: M) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        correlation::notify(&self.private_address, message, self.correlation)
+    }
+
+    /// Sends a notification to the actor itself waiting for room in the
+    /// private mailbox bounded with [Actor::PRIVATE_MAILBOX_CAPACITY].
+    ///
+    /// The returned future doesn't borrow the context. Awaiting it within a
+    /// handler while the mailbox is full waits forever, since the actor
+    /// takes no messages meanwhile, so hand it over to a spawned task
+    /// instead, e.g. with [Context::spawn_scoped].
+    pub fn notify_async<M: Message + 'static>(
+        &self,
+        message: M,
+    ) -> impl Future<Output = Result<(), FailedToDeliver>> + Send + 'static
+    where
+        A: Handler<M>,
+    {
+        let address = self.private_address.clone();
+
+        async move { address.notify_async(message).await }
+    }
+
+    /// Sends a notification to the actor itself through the private address
+    /// once `after` has elapsed
+    pub fn notify_later<M: Message + 'static>(&self, message: M, after: Duration) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        self.private_address.notify_later(message, after)
+    }
+
+    /// Sends a notification built by `create_message` to the actor itself
+    /// through the private address every `period`, starting right away.
+    ///
+    /// Stops once the actor stops, see [Context::schedule_recurring] for a
+    /// schedule that doesn't drift.
+    pub fn notify_interval<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        self.private_address.notify_interval(create_message, period)
+    }
+
+    /// Same as [Context::notify_interval] with control over when the first
+    /// message is sent
+    pub fn notify_interval_with<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &self,
+        create_message: F,
+        period: Duration,
+        first: FirstTick,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        self.private_address
+            .notify_interval_with(create_message, period, first)
+    }
+
+    /// Same as [Context::notify_interval], except that the ticker is owned
+    /// by the actor and aborted as soon as the actor stops.
+    ///
+    /// Meant for recurring work set up in [Actor::on_start], the returned
+    /// handle doesn't have to be stored anywhere.
+    pub fn schedule_interval<M: Message + 'static, F: (Fn() -> M) + Send + 'static>(
+        &mut self,
+        create_message: F,
+        period: Duration,
+    ) -> SpawnHandle
+    where
+        A: Handler<M>,
+    {
+        let handle = self.notify_interval(create_message, period);
+
+        let abort = handle.abort_handle();
+        self.linked.push(Box::new(move || abort.abort()));
+
+        handle
+    }
+
+    /// Sends a notification to the actor itself once `window` has elapsed
+    /// without another debounced notification of the same message type.
+    ///
+    /// Each call restarts the timer and replaces the pending message, so a
+    /// burst of notifications is collapsed into the last one.
+    pub fn notify_debounced<M: Message + 'static>(&mut self, message: M, window: Duration)
+    where
+        A: Handler<M>,
+    {
+        let handle = self.private_address.notify_later(message, window);
+
+        if let Some(pending) = self
+            .debounced
+            .insert(TypeId::of::<M>(), handle.abort_handle())
+        {
+            pending.abort();
+        }
+    }
+
+    /// Sends a notification to the actor itself once `after` has elapsed,
+    /// keyed by `key`.
+    ///
+    /// Scheduling under a key that is still pending replaces the timer. The
+    /// pending timers are aborted as soon as the actor stops.
+    pub fn schedule<M: Message + 'static>(&mut self, key: &str, message: M, after: Duration)
+    where
+        A: Handler<M>,
+    {
+        self.schedules.retain(|_, timer| !timer.is_finished());
+
+        let timer = self.private_address.notify_later(message, after);
+
+        if let Some(pending) = self.schedules.insert(key.to_string(), timer) {
+            pending.abort();
+        }
+    }
+
+    /// Cancels the timer scheduled under `key`, returns `true` if it was
+    /// still pending
+    pub fn cancel_schedule(&mut self, key: &str) -> bool {
+        match self.schedules.remove(key) {
+            Some(timer) if !timer.is_finished() => {
+                timer.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Runs the future in a separate task and notifies the actor with its
+    /// output mapped into a message.
+    ///
+    /// The message loop isn't blocked meanwhile. The message goes through
+    /// the private address and is dropped if the actor has stopped by then.
+    pub fn pipe<T, F, M, G>(&self, future: F, map: G) -> SpawnHandle
+    where
+        F: Future<Output = T> + Send + 'static,
+        G: FnOnce(T) -> M + Send + 'static,
+        M: Message + 'static,
+        A: Handler<M>,
+    {
+        let address = self.private_address.downgrade();
+        let correlation = self.correlation;
+
+        SpawnHandle::new(tokio::spawn(async move {
+            let output = future.await;
+
+            if let Some(address) = address.upgrade() {
+                let _ = correlation::notify(&address, map(output), correlation);
+            }
+        }))
+    }
+
+    /// Same as [Context::pipe] but at most [Actor::MAX_PIPED_TASKS] futures
+    /// piped this way run at once.
+    ///
+    /// Once the limit is hit it waits for one of them to finish before
+    /// spawning, which holds the message loop back and so applies
+    /// backpressure to the senders.
+    pub fn pipe_bounded<T, F, M, G>(
+        &self,
+        future: F,
+        map: G,
+    ) -> impl Future<Output = SpawnHandle> + Send + 'static
+    where
+        F: Future<Output = T> + Send + 'static,
+        G: FnOnce(T) -> M + Send + 'static,
+        M: Message + 'static,
+        A: Handler<M>,
+    {
+        let piped = self.piped.clone();
+        let address = self.private_address.downgrade();
+        let correlation = self.correlation;
+
+        async move {
+            let permit = piped
+                .acquire_owned()
+                .await
+                .expect("piped tasks semaphore is never closed");
+
+            SpawnHandle::new(tokio::spawn(async move {
+                let output = future.await;
+
+                if let Some(address) = address.upgrade() {
+                    let _ = correlation::notify(&address, map(output), correlation);
+                }
+
+                drop(permit);
+            }))
+        }
+    }
+
+    /// Number of futures piped with [Context::pipe_bounded] that are still
+    /// running
+    pub fn piped_task_count(&self) -> usize {
+        A::MAX_PIPED_TASKS - self.piped.available_permits()
+    }
+
+    /// Notifies the actor with a message built by `make_message` every
+    /// `period`, starting one period from now.
+    ///
+    /// Ticks target absolute times, `start + n * period`, so a slow handler
+    /// or a busy runtime never makes the schedule drift. A tick missed
+    /// entirely is delivered once as soon as possible and the following
+    /// ones are aligned to the original schedule again, no burst of missed
+    /// ticks is sent. Messages go through the private address and the
+    /// schedule is cancelled once the actor stops.
+    ///
+    /// Panics if `period` is zero, like `tokio::time::interval` does.
+    pub fn schedule_recurring<M, F>(&mut self, period: Duration, make_message: F) -> SpawnHandle
+    where
+        M: Message + 'static,
+        F: Fn() -> M + Send + 'static,
+        A: Handler<M>,
+    {
+        assert!(period > Duration::ZERO, "`period` must be non-zero.");
+
+        let address = self.private_address.clone();
+
+        let handle = SpawnHandle::new(tokio::spawn(async move {
+            let start = Instant::now();
+            let mut tick: u64 = 1;
+
+            // Ticks too far away to be represented are never reached
+            while let Some(at) =
+                tick_offset(period, tick).and_then(|offset| start.checked_add(offset))
+            {
+                tokio::time::sleep_until(at).await;
+
+                if address.notify(make_message()).is_err() {
+                    return;
+                }
+
+                // Skip ticks that are already in the past
+                let elapsed = start.elapsed().as_nanos() / period.as_nanos();
+                tick = tick
+                    .max(u64::try_from(elapsed).unwrap_or(u64::MAX))
+                    .saturating_add(1);
+            }
+        }));
+
+        let abort = handle.abort_handle();
+        self.linked.push(Box::new(move || abort.abort()));
+
+        handle
+    }
+
+    /// Attaches a stream to the actor.
+    ///
+    /// Each item is delivered through the private address and handled by
+    /// [StreamHandler::handle] in order, one at a time, within the regular
+    /// message loop. The next item is pulled only once there is room for it
+    /// in the private mailbox, see
+    /// [Actor::PRIVATE_MAILBOX_CAPACITY](crate::Actor::PRIVATE_MAILBOX_CAPACITY).
+    /// [StreamHandler::finished] is called once the stream is exhausted.
+    /// Polling stops as soon as the actor stops.
+    pub fn add_stream<I, S>(&mut self, stream: S)
+    where
+        I: Send + 'static,
+        S: Stream<Item = I> + Send + 'static,
+        A: StreamHandler<I>,
+    {
+        let address = self.private_address.clone();
+
+        tokio::spawn(async move {
+            let mut stream = Box::pin(stream);
+
+            loop {
+                select! {
+                    _ = address.tx.closed() => return,
+                    item = stream.next() => match item {
+                        Some(item) => {
+                            if address.deliver_async(Box::new(StreamItem(item))).await.is_err() {
+                                return;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+            }
+
+            let _ = address.deliver(Box::new(StreamFinished::<I>(PhantomData)));
+        });
+    }
+
+    /// Attaches a stream whose items are mapped into a message the actor
+    /// handles like any other notification.
+    ///
+    /// Meant for merging sources of different item types into a single
+    /// [Handler]. Items of each stream keep their order and are pulled with
+    /// the same backpressure as [Context::add_stream]. Nothing is called
+    /// once the stream is exhausted, polling stops as soon as the actor
+    /// stops.
+    pub fn add_stream_mapped<I, S, M, F>(&mut self, stream: S, map: F)
+    where
+        I: Send + 'static,
+        S: Stream<Item = I> + Send + 'static,
+        M: Message + 'static,
+        F: Fn(I) -> M + Send + 'static,
+        A: Handler<M>,
+    {
+        let address = self.private_address.clone();
+
+        tokio::spawn(async move {
+            let mut stream = Box::pin(stream);
+
+            loop {
+                select! {
+                    _ = address.tx.closed() => return,
+                    item = stream.next() => match item {
+                        Some(item) => {
+                            if address.deliver_async(Box::new(map(item))).await.is_err() {
+                                return;
+                            }
+                        }
+                        None => return,
+                    }
+                }
+            }
+        });
+    }
+
+    /// Attaches a broadcast channel to the actor, so that several actors
+    /// could observe the same events.
+    ///
+    /// Items are handled like the ones of [Context::add_stream]. Items lost
+    /// because the actor fell behind the channel are reported to
+    /// [StreamHandler::lagged] and [StreamHandler::finished] is called once
+    /// all senders are dropped.
+    pub fn add_broadcast_stream<I>(&mut self, mut rx: broadcast::Receiver<I>)
+    where
+        I: Clone + Send + 'static,
+        A: StreamHandler<I>,
+    {
+        let address = self.private_address.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let delivered = select! {
+                    _ = address.tx.closed() => return,
+                    received = rx.recv() => match received {
+                        Ok(item) => address.deliver_async(Box::new(StreamItem(item))).await,
+                        Err(RecvError::Lagged(skipped)) => {
+                            address.deliver(Box::new(StreamLagged::<I>(skipped, PhantomData)))
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                };
+
+                if delivered.is_err() {
+                    return;
+                }
+            }
+
+            let _ = address.deliver(Box::new(StreamFinished::<I>(PhantomData)));
+        });
+    }
+}
+
+impl<A: Actor> Drop for Context<A> {
+    fn drop(&mut self) {
+        if self.finalizers.is_empty() {
+            return;
+        }
+
+        // Actor task was aborted before the finalizers had a chance to run
+        let mut finalizers = std::mem::take(&mut self.finalizers);
+
+        if let Ok(handle) = Handle::try_current() {
+            handle.spawn(async move {
+                while let Some(finalizer) = finalizers.pop() {
+                    finalizer().await;
+                }
+            });
+        }
+    }
+}
+
+/// Guard returned by [Context::spawn_guarded]
+///
+/// Stops the child actor on drop. Dereferences to the child address.
+pub struct ChildGuard<B: Actor> {
+    address: Address<B>,
+    control_address: ControlAddress<B>,
+}
+
+impl<B: Actor> Deref for ChildGuard<B> {
+    type Target = Address<B>;
+
+    fn deref(&self) -> &Address<B> {
+        &self.address
+    }
 }
 
-#[derive(PartialEq)]
+impl<B: Actor> Drop for ChildGuard<B> {
+    fn drop(&mut self) {
+        let _ = self.control_address.stop();
+    }
+}
+
+/// Guard returned by [Context::guard]
+///
+/// Keeps the actor alive while held. Dereferences to the actor address.
+pub struct AddressGuard<A: Actor> {
+    address: Address<A>,
+}
+
+impl<A: Actor> Deref for AddressGuard<A> {
+    type Target = Address<A>;
+
+    fn deref(&self) -> &Address<A> {
+        &self.address
+    }
+}
+
+/// Lifecycle stage of an actor, stages are ordered as they go
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
 pub enum ActorState {
     Starting,
     Started,
     Stopping,
     Stopped,
 }
+
+/// Time of the `tick`-th tick of [Context::schedule_recurring] counted from
+/// its start, `None` if it doesn't fit in a [Duration]
+fn tick_offset(period: Duration, tick: u64) -> Option<Duration> {
+    let nanos = period.as_nanos().checked_mul(u128::from(tick))?;
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Why the actor is stopping, passed to [Actor::on_stopping]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StopReason {
+    /// Stop was requested with [Context::stop] or a similar method
+    Requested,
+
+    /// All public addresses of the actor were dropped
+    AllAddressesDropped,
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    address::UnboundedAddress,
+    handler::{Envelope, StopEnvelope},
+    Actor, ActorSendError, Context, FailedToDeliver, Handler, Message,
+};
+
+/// Address of the control mailbox of an actor.
+///
+/// Control messages, like pausing or reconfiguring the actor, are handled
+/// with strict priority over the data ones: a message sent here is handled
+/// right after the current one, ahead of any backlog in the private and
+/// public mailboxes. Like the private address, it is unbounded and doesn't
+/// keep the actor alive.
+///
+/// Obtained from [Actor::run_with_control](crate::Actor::run_with_control)
+/// or [Context::control_address](crate::Context::control_address).
+pub struct ControlAddress<A: Actor> {
+    address: UnboundedAddress<A>,
+}
+
+impl<A: Actor> ControlAddress<A> {
+    pub(crate) fn new(address: UnboundedAddress<A>) -> ControlAddress<A> {
+        ControlAddress { address }
+    }
+
+    /// Sends a control message and waits for the response
+    pub async fn send<M: Message + 'static>(&self, message: M) -> Result<M::Result, ActorSendError>
+    where
+        A: Handler<M>,
+    {
+        self.address.send(message).await
+    }
+
+    /// Sends a control notification
+    pub fn notify<M: Message + 'static>(&self, message: M) -> Result<(), FailedToDeliver>
+    where
+        A: Handler<M>,
+    {
+        self.address.notify(message)
+    }
+
+    /// Applies the new configuration ahead of any queued data message and
+    /// waits until it is applied.
+    ///
+    /// Like any message, [Reconfigure::reconfigure] runs exclusively, so no
+    /// other message is handled halfway between the old and the new config.
+    /// If the actor is draining its mailboxes after
+    /// [Context::stop_graceful], the config is still applied before the
+    /// remaining data messages. Once the actor is stopped it fails with
+    /// [ActorSendError::FailedToDeliver].
+    pub async fn reconfigure<C: Send + 'static>(&self, config: C) -> Result<(), ActorSendError>
+    where
+        A: Reconfigure<C>,
+    {
+        let (tx, rx) = oneshot::channel();
+
+        self.address
+            .deliver(Box::new(ReconfigureEnvelope { config, tx }))
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+    }
+
+    /// Returns the type names of the messages queued in the private
+    /// mailbox without handling them, e.g. to find out why an actor is
+    /// stuck.
+    ///
+    /// The snapshot is taken right after the current message with control
+    /// priority, the messages are put back in the same order. Private
+    /// messages sent from other threads meanwhile may get ahead of them.
+    /// Fails with [ActorSendError::FailedToGetResponse] if the actor is
+    /// stopping.
+    pub async fn queued_private(&self) -> Result<Vec<&'static str>, ActorSendError> {
+        let (tx, rx) = oneshot::channel();
+
+        self.address
+            .deliver(Box::new(PrivateSnapshot(tx)))
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await.map_err(|_| ActorSendError::FailedToGetResponse)
+    }
+
+    /// Returns `true` if the actor do not receive messages any more.
+    pub fn is_closed(&self) -> bool {
+        self.address.is_closed()
+    }
+
+    /// Asks the actor to stop ahead of any queued message, see
+    /// [Address::stop](crate::Address::stop)
+    pub(crate) fn stop(&self) -> Result<(), FailedToDeliver> {
+        self.address.deliver(Box::new(StopEnvelope))
+    }
+}
+
+impl<A: Actor> Clone for ControlAddress<A> {
+    fn clone(&self) -> Self {
+        ControlAddress {
+            address: self.address.clone(),
+        }
+    }
+}
+
+/// Actor whose configuration could be replaced while it runs, see
+/// [ControlAddress::reconfigure]
+#[async_trait]
+pub trait Reconfigure<C: Send + 'static>: Actor {
+    async fn reconfigure(&mut self, config: C, context: &mut Context<Self>);
+}
+
+/// Built-in envelope the message loop answers with the private mailbox
+/// contents, see [ControlAddress::queued_private]
+struct PrivateSnapshot(oneshot::Sender<Vec<&'static str>>);
+
+#[async_trait]
+impl<A: Actor> Envelope<A> for PrivateSnapshot {
+    // Reached only while the actor drains its mailboxes on stop, the
+    // caller gets `FailedToGetResponse` once the sender is dropped
+    async fn handle(self: Box<Self>, _actor: &mut A, _context: &mut Context<A>) {}
+
+    fn is_private_snapshot(&self) -> bool {
+        true
+    }
+
+    fn reply_private_snapshot(self: Box<Self>, queued: Vec<&'static str>) {
+        let _ = self.0.send(queued);
+    }
+}
+
+struct ReconfigureEnvelope<C> {
+    config: C,
+    tx: oneshot::Sender<()>,
+}
+
+#[async_trait]
+impl<C: Send + 'static, A: Reconfigure<C>> Envelope<A> for ReconfigureEnvelope<C> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        actor.reconfigure(self.config, context).await;
+
+        let _ = self.tx.send(());
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<C>()
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    address::UnboundedAddress, handler::Envelope, Actor, Context, FailedToDeliver, Handler, Message,
+};
+
+/// Id shared by the messages of one request flow.
+///
+/// A message carries it if [Message::correlation_id] returns one. While
+/// such a message is handled, the id is available with
+/// [Context::correlation_id] and travels along with the notifications the
+/// actor sends itself with [Context::notify] and [Context::pipe].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    /// Generates a new id, ids are handed out sequentially starting from 1
+    /// within the process
+    pub fn generate() -> CorrelationId {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+
+        CorrelationId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for CorrelationId {
+    fn from(id: u64) -> Self {
+        CorrelationId(id)
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Notification sent while a correlated message is handled, carrying its id
+/// unless the notification has its own
+struct Propagated<M> {
+    message: M,
+    id: CorrelationId,
+}
+
+#[async_trait]
+impl<M: Message + 'static, A: Actor> Envelope<A> for Propagated<M>
+where
+    A: Handler<M>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        Envelope::<A>::handle(Box::new(self.message), actor, context).await
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id().or(Some(self.id))
+    }
+}
+
+/// Sends a notification through the private address propagating the id
+pub(crate) fn notify<A, M>(
+    address: &UnboundedAddress<A>,
+    message: M,
+    id: Option<CorrelationId>,
+) -> Result<(), FailedToDeliver>
+where
+    A: Handler<M>,
+    M: Message + 'static,
+{
+    match id {
+        Some(id) => address.deliver_limited(Box::new(Propagated { message, id })),
+        None => address.notify(message),
+    }
+}
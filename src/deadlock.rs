@@ -0,0 +1,101 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::Actor;
+
+/// Identity of a running actor, the address of its shared status
+pub(crate) type ActorId = usize;
+
+#[derive(Clone, Copy)]
+struct Running {
+    id: ActorId,
+    name: &'static str,
+}
+
+tokio::task_local! {
+    static CURRENT: Running;
+}
+
+/// Actor blocked in a `send`, keyed by the id of the sender
+struct Waiting {
+    name: &'static str,
+    target: ActorId,
+}
+
+/// Runs `f` with the waiting actors, the map is created on first use
+fn with_waiting<R>(f: impl FnOnce(&mut HashMap<ActorId, Waiting>) -> R) -> R {
+    static WAITING: Mutex<Option<HashMap<ActorId, Waiting>>> = Mutex::new(None);
+
+    let mut waiting = WAITING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    f(waiting.get_or_insert_with(HashMap::new))
+}
+
+/// Runs the message loop of an actor with its id attached to the task
+pub(crate) fn scoped<A: Actor, F: Future>(id: ActorId, job: F) -> impl Future<Output = F::Output> {
+    CURRENT.scope(
+        Running {
+            id,
+            name: std::any::type_name::<A>(),
+        },
+        job,
+    )
+}
+
+/// Records that the current actor waits for a response of `target` until
+/// the guard is dropped.
+///
+/// A warning with the cycle is printed to stderr if `target` is, directly
+/// or through other actors, waiting for the current one. Sends made from
+/// outside of an actor are not tracked.
+pub(crate) fn wait_for(target: ActorId) -> Option<WaitGuard> {
+    let current = CURRENT.try_with(|current| *current).ok()?;
+
+    with_waiting(|waiting| {
+        waiting.insert(
+            current.id,
+            Waiting {
+                name: current.name,
+                target,
+            },
+        );
+
+        let mut cycle = vec![current.name];
+        let mut next = target;
+
+        loop {
+            if next == current.id {
+                cycle.push(current.name);
+
+                eprintln!("Possible deadlock: {}", cycle.join(" -> "));
+
+                break;
+            }
+
+            match waiting.get(&next) {
+                // Cycles not involving the current actor were reported by
+                // the actor closing them
+                Some(waits) if cycle.len() <= waiting.len() => {
+                    cycle.push(waits.name);
+                    next = waits.target;
+                }
+                _ => break,
+            }
+        }
+    });
+
+    Some(WaitGuard { id: current.id })
+}
+
+pub(crate) struct WaitGuard {
+    id: ActorId,
+}
+
+impl Drop for WaitGuard {
+    fn drop(&mut self) {
+        with_waiting(|waiting| waiting.remove(&self.id));
+    }
+}
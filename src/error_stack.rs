@@ -1,4 +1,4 @@
-use crate::ActorSendError;
+use crate::{ActorSendError, SendUnpackError};
 use error_stack::{report, Context, ResultExt};
 
 pub trait ActorResultIntoReport<R, C> {
@@ -28,3 +28,22 @@ impl<R, C> ActorResultIntoReport<R, C> for Result<error_stack::Result<R, C>, Act
         }
     }
 }
+
+/// Covers [Address::send_unpack_flat](crate::Address::send_unpack_flat)
+/// results of handlers replying later with an `error_stack::Result`
+impl<R, C> ActorResultIntoReport<R, C> for Result<R, SendUnpackError<error_stack::Report<C>>> {
+    fn change_context<C2: Context>(self, context: C2) -> error_stack::Result<R, C2> {
+        self.change_context_lazy(|| context)
+    }
+
+    fn change_context_lazy<C2: Context, F: FnOnce() -> C2>(
+        self,
+        f: F,
+    ) -> error_stack::Result<R, C2> {
+        match self {
+            Ok(ok) => Ok(ok),
+            Err(SendUnpackError::Send(err)) => Err(report!(err)).change_context_lazy(f),
+            Err(SendUnpackError::Domain(err)) => Err(err).change_context_lazy(f),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::Poll,
+};
+
+use crate::{ActorSendError, Message, Recipient};
+
+/// Sends a clone of the message to every target concurrently and gathers
+/// the responses in the order of targets.
+///
+/// Targets are anything convertible into a [Recipient], so slices of
+/// [Address](crate::Address)es, [UnboundedAddress](crate::UnboundedAddress)es
+/// and recipients all work. A failed send doesn't affect the others.
+///
+/// ```
+/// # use akt::{send_all, Actor, Context, Handler, Message};
+/// # use async_trait::async_trait;
+/// struct Shard(u32);
+///
+/// impl Actor for Shard {}
+///
+/// #[derive(Clone)]
+/// struct Count;
+///
+/// impl Message for Count {
+///     type Result = u32;
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Count> for Shard {
+///     async fn handle(&mut self, _message: Count, _context: &mut Context<Self>) -> u32 {
+///         self.0
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let shards = vec![Shard(1).run(), Shard(2).run()];
+///
+/// assert_eq!(send_all(&shards, Count).await, [Ok(1), Ok(2)]);
+/// # }
+/// ```
+pub async fn send_all<R, M>(targets: &[R], message: M) -> Vec<Result<M::Result, ActorSendError>>
+where
+    R: Clone + Into<Recipient<M>>,
+    M: Message + Clone + 'static,
+{
+    let recipients: Vec<Recipient<M>> = targets.iter().cloned().map(Into::into).collect();
+
+    let mut sends: Vec<_> = recipients
+        .iter()
+        .map(|recipient| Some(Box::pin(recipient.send(message.clone()))))
+        .collect();
+
+    let mut results: Vec<_> = sends.iter().map(|_| None).collect();
+
+    poll_fn(|cx| {
+        let mut done = true;
+
+        for (send, result) in sends.iter_mut().zip(results.iter_mut()) {
+            if let Some(future) = send {
+                match Pin::new(future).poll(cx) {
+                    Poll::Ready(output) => {
+                        *result = Some(output);
+                        *send = None;
+                    }
+                    Poll::Pending => done = false,
+                }
+            }
+        }
+
+        if done {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+
+    results.into_iter().flatten().collect()
+}
+
+/// Same as [send_all], each result is paired with the index of its target.
+///
+/// Handy to retry only the targets that failed. Failures with
+/// [ActorSendError::FailedToDeliver] never reached the actor and are safe
+/// to retry, while [ActorSendError::FailedToGetResponse] ones may have been
+/// handled.
+pub async fn send_all_tracked<R, M>(
+    targets: &[R],
+    message: M,
+) -> Vec<(usize, Result<M::Result, ActorSendError>)>
+where
+    R: Clone + Into<Recipient<M>>,
+    M: Message + Clone + 'static,
+{
+    send_all(targets, message)
+        .await
+        .into_iter()
+        .enumerate()
+        .collect()
+}
@@ -1,7 +1,24 @@
+use std::{
+    any::Any,
+    convert,
+    error::Error,
+    future::{poll_fn, Future},
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    task::{self, Poll},
+};
+
 use async_trait::async_trait;
-use tokio::sync::oneshot;
+use tokio::{
+    sync::{oneshot, watch},
+    task::JoinHandle,
+};
 
-use crate::{Actor, ActorSendError, Context, Message};
+use crate::{Actor, ActorSendError, Context, CorrelationId, Message};
 
 #[async_trait]
 pub trait Handler<M: Message>
@@ -11,31 +28,380 @@ where
     async fn handle(&mut self, message: M, context: &mut Context<Self>) -> M::Result;
 }
 
-pub struct MessageWithSender<M: Message> {
+pub(crate) struct MessageWithSender<M: Message> {
     pub message: M,
-    pub tx: oneshot::Sender<M::Result>,
+    pub tx: Box<dyn ReplySender<M::Result>>,
+}
+
+impl<M: Message> MessageWithSender<M>
+where
+    M::Result: 'static,
+{
+    /// Request replying with a [Response]
+    pub fn new(message: M, tx: oneshot::Sender<Response<M::Result>>) -> MessageWithSender<M> {
+        MessageWithSender {
+            message,
+            tx: Box::new(MapReply::response(tx)),
+        }
+    }
 }
 
+/// What the caller of a request gets back, the error if the message loop
+/// rejected it, see [Envelope::reject]
+pub(crate) type Response<T> = Result<T, ActorSendError>;
+
+type BoxedEnvelope<A> = Box<dyn Envelope<A> + Send>;
+
+/// Concurrent handler resolving to the error of a notification, if any
+pub(crate) type ConcurrentHandling<'a> =
+    Pin<Box<dyn Future<Output = Option<Box<dyn Error + Send>>> + Send + 'a>>;
+
 #[async_trait]
 pub trait Envelope<A: Actor> {
     async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>);
+
+    /// Name of the wrapped message type used in diagnostics
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Returns `true` if the message is handled by a
+    /// [ConcurrentHandler](crate::ConcurrentHandler)
+    fn is_concurrent(&self) -> bool {
+        false
+    }
+
+    /// Handles the message sharing the actor with other concurrent ones,
+    /// called only if [Envelope::is_concurrent] returns `true`. Resolves to
+    /// the error of a notification, which the message loop passes to
+    /// [Actor::on_notification_error] once it has the actor back.
+    fn handle_concurrent<'a>(
+        self: Box<Self>,
+        _actor: &'a A,
+        _critical: Arc<AtomicUsize>,
+    ) -> ConcurrentHandling<'a> {
+        unreachable!("only concurrent envelopes are handled concurrently")
+    }
+
+    /// Returns `true` if the message is handled by a
+    /// [BatchHandler](crate::BatchHandler)
+    fn is_batched(&self) -> bool {
+        false
+    }
+
+    /// Collects the messages `next` yields into a batch envelope while they
+    /// join it, returns the batch and the first message that didn't join.
+    /// Called only if [Envelope::is_batched] returns `true`
+    fn collect_batch(
+        self: Box<Self>,
+        _next: &mut dyn FnMut() -> Option<BoxedEnvelope<A>>,
+    ) -> (BoxedEnvelope<A>, Option<BoxedEnvelope<A>>) {
+        unreachable!("only batched envelopes are collected into batches")
+    }
+
+    /// Moves the message into the batch if it is a `Vec` of the same
+    /// message type
+    fn join_batch(&mut self, _batch: &mut dyn Any) -> bool {
+        false
+    }
+
+    /// Returns `true` if the message is handled by an
+    /// [InlineHandler](crate::InlineHandler)
+    fn is_inline(&self) -> bool {
+        false
+    }
+
+    /// Handles the message without creating a future, called only if
+    /// [Envelope::is_inline] returns `true`
+    fn handle_inline(self: Box<Self>, _actor: &mut A, _context: &mut Context<A>) {
+        unreachable!("only inline envelopes are handled inline")
+    }
+
+    /// Id of the request flow the message belongs to, see
+    /// [Message::correlation_id]
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        None
+    }
+
+    /// Returns `true` if the message asks for the private mailbox contents,
+    /// see `ControlAddress::queued_private`
+    fn is_private_snapshot(&self) -> bool {
+        false
+    }
+
+    /// Replies with the type names of the queued private messages, called
+    /// only if [Envelope::is_private_snapshot] returns `true`
+    fn reply_private_snapshot(self: Box<Self>, _queued: Vec<&'static str>) {
+        unreachable!("only private snapshot envelopes get the snapshot")
+    }
+
+    /// Returns `true` for built-in envelopes steering the actor instead of
+    /// carrying a message, they are never rejected by
+    /// [Actor::REJECT_UNTIL_STARTED](crate::Actor::REJECT_UNTIL_STARTED)
+    fn is_control(&self) -> bool {
+        false
+    }
+
+    /// Drops the message without handling it, telling the caller if it
+    /// waits for a response, see
+    /// [Actor::REJECT_UNTIL_STARTED](crate::Actor::REJECT_UNTIL_STARTED)
+    async fn reject(self: Box<Self>) {}
 }
 
 #[async_trait]
 impl<M: Message, A: Actor> Envelope<A> for MessageWithSender<M>
 where
     A: Handler<M>,
+    M::Result: 'static,
 {
-    async fn handle(mut self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
-        tokio::select! {
-          // Drop computation if receiver is no longer interested in it
-          _ = self.tx.closed() => {}
-          result = actor.handle(self.message, context) => {
-            // It is OK if receiver is not interested in the response any more
-            // and eventualy it was not captured earlier
-            let _ = self.tx.send(result);
-          }
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let MessageWithSender { message, tx } = *self;
+
+        handle_request(actor, message, tx, cancel_on_disconnect::<A, M>(), context).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id()
+    }
+
+    async fn reject(self: Box<Self>) {
+        self.tx.reject();
+    }
+}
+
+/// Handles a request replying through `tx`, shared by every envelope
+/// somebody waits the result of.
+///
+/// The handler may take the reply over with `Context::take_reply`. If
+/// `cancellable`, it is dropped as soon as the caller stops waiting and
+/// [Actor::on_message_cancelled] runs instead.
+pub(crate) async fn handle_request<A, M>(
+    actor: &mut A,
+    message: M,
+    tx: Box<dyn ReplySender<M::Result>>,
+    cancellable: bool,
+    context: &mut Context<A>,
+) where
+    A: Handler<M>,
+    M: Message,
+    M::Result: 'static,
+{
+    let slot = Arc::new(Mutex::new(Some(tx)));
+
+    context.reply = Some(Box::new(slot.clone()));
+    context.expects_reply = true;
+
+    let critical = context.critical.clone();
+
+    let cancelled = tokio::select! {
+      // Drop computation if receiver is no longer interested in it
+      _ = reply_closed(&slot, &critical), if cancellable => true,
+      result = actor.handle(message, context) => {
+        // It is OK if receiver is not interested in the response any more
+        // and eventualy it was not captured earlier
+        let send = lock(&slot).take().map(|tx| move |result| {
+            let _ = tx.send(result);
+        });
+
+        respond::<A, M, _>(result, send, actor, context);
+
+        false
+      }
+    };
+
+    context.reply = None;
+    context.expects_reply = false;
+
+    if cancelled {
+        actor.on_message_cancelled(context).await;
+    }
+}
+
+/// Records the outcome of a request and passes the mapped response to
+/// `send`, none if the reply was taken over by the handler. Shared with the
+/// inline handlers that can't await, see [handle_request].
+pub(crate) fn respond<A, M, S>(
+    mut result: M::Result,
+    send: Option<S>,
+    actor: &mut A,
+    context: &mut Context<A>,
+) where
+    A: Actor,
+    M: Message,
+    M::Result: 'static,
+    S: FnOnce(M::Result),
+{
+    context.failed = M::is_failure(&result);
+
+    if let Some(send) = send {
+        actor.map_response(&mut result, context);
+
+        send(result);
+    }
+}
+
+/// Sending end of a reply, see [handle_request]
+pub(crate) trait ReplySender<T>: Send + Sync {
+    /// Sends the reply, returns it back if the caller is not waiting anymore
+    fn send(self: Box<Self>, value: T) -> Result<(), T>;
+
+    fn poll_closed(&mut self, cx: &mut task::Context<'_>) -> Poll<()>;
+
+    fn is_closed(&self) -> bool;
+
+    /// Tells the caller the request was rejected without being handled
+    fn reject(self: Box<Self>);
+}
+
+/// Reply channel owned by the caller, see `Address::send_with`. It has no
+/// room for an error, so a rejected request just drops it.
+impl<T: Send> ReplySender<T> for oneshot::Sender<T> {
+    fn send(self: Box<Self>, value: T) -> Result<(), T> {
+        (*self).send(value)
+    }
+
+    fn poll_closed(&mut self, cx: &mut task::Context<'_>) -> Poll<()> {
+        oneshot::Sender::poll_closed(self, cx)
+    }
+
+    fn is_closed(&self) -> bool {
+        oneshot::Sender::is_closed(self)
+    }
+
+    fn reject(self: Box<Self>) {}
+}
+
+/// Reply sender wrapping the result into what the caller waits for, e.g.
+/// the `Ok` of a validated message, and into a [Response]
+pub(crate) struct MapReply<T, U> {
+    tx: oneshot::Sender<Response<U>>,
+    map: fn(T) -> U,
+}
+
+impl<T, U> MapReply<T, U> {
+    pub fn new(tx: oneshot::Sender<Response<U>>, map: fn(T) -> U) -> MapReply<T, U> {
+        MapReply { tx, map }
+    }
+}
+
+impl<T> MapReply<T, T> {
+    /// Passes the result as is
+    pub fn response(tx: oneshot::Sender<Response<T>>) -> MapReply<T, T> {
+        MapReply::new(tx, convert::identity)
+    }
+}
+
+impl<T, U: Send> ReplySender<T> for MapReply<T, U> {
+    fn send(self: Box<Self>, value: T) -> Result<(), T> {
+        if self.tx.is_closed() {
+            return Err(value);
         }
+
+        let _ = self.tx.send(Ok((self.map)(value)));
+
+        Ok(())
+    }
+
+    fn poll_closed(&mut self, cx: &mut task::Context<'_>) -> Poll<()> {
+        self.tx.poll_closed(cx)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    fn reject(self: Box<Self>) {
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
+
+pub(crate) type ReplySlot<T> = Arc<Mutex<Option<Box<dyn ReplySender<T>>>>>;
+
+pub(crate) fn lock<T>(slot: &ReplySlot<T>) -> MutexGuard<'_, Option<Box<dyn ReplySender<T>>>> {
+    slot.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Whether a handler of `M` is cancelled once nobody waits for its result,
+/// see [Actor::CANCEL_ON_DISCONNECT] and [Message::CANCEL_ON_DISCONNECT]
+pub(crate) fn cancel_on_disconnect<A: Actor, M: Message>() -> bool {
+    A::CANCEL_ON_DISCONNECT && M::CANCEL_ON_DISCONNECT
+}
+
+/// Resolves once the receiver is dropped outside of critical sections,
+/// never resolves if the sender was taken over by the handler.
+async fn reply_closed<T>(slot: &ReplySlot<T>, critical: &AtomicUsize) {
+    poll_fn(|cx| match lock(slot).as_mut() {
+        Some(tx) => outside_critical(tx.poll_closed(cx), critical),
+        None => Poll::Pending,
+    })
+    .await
+}
+
+/// Resolves once the receiver is dropped outside of critical sections,
+/// shared by the envelopes that own their reply sender
+pub(crate) async fn sender_closed<T>(tx: &mut oneshot::Sender<T>, critical: &AtomicUsize) {
+    poll_fn(|cx| outside_critical(tx.poll_closed(cx), critical)).await
+}
+
+/// Holds a disconnect back while the handler is in a critical section.
+///
+/// No waker is registered for the end of the section, the disconnect is
+/// noticed only because `Context::critical_section` yields once it is left,
+/// which gets the whole `select!` polled again.
+fn outside_critical(closed: Poll<()>, critical: &AtomicUsize) -> Poll<()> {
+    match closed {
+        Poll::Ready(()) if critical.load(Ordering::Acquire) == 0 => Poll::Ready(()),
+        _ => Poll::Pending,
+    }
+}
+
+/// Marks a critical section of a handler for as long as it is alive, see
+/// `Context::critical_section`
+pub(crate) struct CriticalGuard(Arc<AtomicUsize>);
+
+impl CriticalGuard {
+    pub fn enter(depth: &Arc<AtomicUsize>) -> CriticalGuard {
+        depth.fetch_add(1, Ordering::AcqRel);
+
+        CriticalGuard(depth.clone())
+    }
+}
+
+impl Drop for CriticalGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Sender of a reply taken over by the handler with [Context::take_reply]
+pub struct ReplyHandle<T> {
+    tx: Box<dyn ReplySender<T>>,
+    // Dropped along with the handle, see `Context::stop_after_reply`
+    _done: oneshot::Sender<()>,
+}
+
+impl<T> ReplyHandle<T> {
+    pub(crate) fn new(tx: Box<dyn ReplySender<T>>, done: oneshot::Sender<()>) -> ReplyHandle<T> {
+        ReplyHandle { tx, _done: done }
+    }
+
+    /// Sends the reply, returns it back if the caller is not waiting anymore
+    pub fn send(self, value: T) -> Result<(), T> {
+        self.tx.send(value)
+    }
+
+    /// Returns `true` if the caller is not waiting for the reply anymore
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Resolves once the caller is not waiting for the reply anymore
+    pub async fn closed(&mut self) {
+        poll_fn(|cx| self.tx.poll_closed(cx)).await
     }
 }
 
@@ -45,11 +411,191 @@ where
     A: Handler<M>,
 {
     async fn handle(mut self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
-        // It is just a notification, we are not interested in handling the result
-        let _ = actor.handle(*self, context).await;
+        let result = actor.handle(*self, context).await;
+
+        notification_handled::<A, M>(result, actor, context).await;
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        Message::correlation_id(self)
     }
 }
 
+/// Records the outcome of a notification, shared by every envelope nobody
+/// waits the result of.
+///
+/// It is just a notification, nobody but the actor itself is interested in
+/// an error it ended with, so the error goes to
+/// [Actor::on_notification_error].
+pub(crate) async fn notification_handled<A: Actor, M: Message>(
+    result: M::Result,
+    actor: &mut A,
+    context: &mut Context<A>,
+) {
+    context.failed = M::is_failure(&result);
+
+    if let Some(error) = M::notification_error(result) {
+        actor.on_notification_error(error, context).await;
+    }
+}
+
+/// Same as [notification_handled] for handlers that can't await, the error
+/// is reported from the private mailbox once the handler returns
+pub(crate) fn notification_handled_inline<A: Actor, M: Message>(
+    result: M::Result,
+    context: &mut Context<A>,
+) {
+    context.failed = M::is_failure(&result);
+
+    if let Some(error) = M::notification_error(result) {
+        let _ = context
+            .private_address()
+            .deliver(Box::new(NotificationError(error)));
+    }
+}
+
+/// Built-in envelope passing an error of a notification handled elsewhere
+/// to [Actor::on_notification_error]
+pub(crate) struct NotificationError(pub Box<dyn Error + Send>);
+
+#[async_trait]
+impl<A: Actor> Envelope<A> for NotificationError {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        actor.on_notification_error(self.0, context).await;
+    }
+}
+
+/// Built-in envelope stopping the actor it is delivered to
+pub(crate) struct StopEnvelope;
+
+#[async_trait]
+impl<A: Actor> Envelope<A> for StopEnvelope {
+    async fn handle(self: Box<Self>, _actor: &mut A, context: &mut Context<A>) {
+        context.stop();
+    }
+
+    fn is_control(&self) -> bool {
+        true
+    }
+}
+
+/// Built-in envelope replying once every message queued before it in the
+/// same mailbox is handled, see `Address::flush`
+pub(crate) struct Barrier(pub oneshot::Sender<()>);
+
+#[async_trait]
+impl<A: Actor> Envelope<A> for Barrier {
+    async fn handle(self: Box<Self>, _actor: &mut A, _context: &mut Context<A>) {
+        let _ = self.0.send(());
+    }
+
+    fn is_control(&self) -> bool {
+        true
+    }
+}
+
+/// Message handled right before the actor stops, see
+/// `Context::stop_with_final_message`
+pub(crate) struct FinalMessage<M>(pub M);
+
+#[async_trait]
+impl<M: Message, A: Actor> Envelope<A> for FinalMessage<M>
+where
+    A: Handler<M>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        // Takes effect only once the message is dispatched anyway, asked
+        // for first so a panic or a timeout of the handler doesn't lose it
+        context.stop();
+
+        let result = actor.handle(self.0, context).await;
+
+        notification_handled::<A, M>(result, actor, context).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+}
+
+/// Future catching panics of the wrapped one
+pub(crate) struct CatchUnwind<F> {
+    future: F,
+}
+
+impl<F: Future + Unpin> CatchUnwind<F> {
+    pub fn new(future: F) -> CatchUnwind<F> {
+        CatchUnwind { future }
+    }
+}
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let future = &mut self.future;
+
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(future).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Panic caught while handling a message, passed to [Actor::on_panic]
+pub struct PanicReason {
+    payload: Box<dyn Any + Send>,
+}
+
+impl PanicReason {
+    pub(crate) fn new(payload: Box<dyn Any + Send>) -> PanicReason {
+        PanicReason { payload }
+    }
+
+    /// Panic message if the panic was raised with a string
+    pub fn message(&self) -> Option<&str> {
+        if let Some(message) = self.payload.downcast_ref::<&'static str>() {
+            return Some(message);
+        }
+
+        self.payload.downcast_ref::<String>().map(String::as_str)
+    }
+
+    /// Original panic payload
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.payload
+    }
+}
+
+/// Result of a handler that [Address::send_unpack](crate::Address::send_unpack)
+/// waits for on the caller side.
+///
+/// Besides the built-in implementations, it could be implemented for any
+/// result type that gets its value later:
+///
+/// ```
+/// # use akt::{ActorSendError, UnpackableResult};
+/// # use async_trait::async_trait;
+/// # use tokio::sync::mpsc;
+/// /// Last value sent before the channel is closed
+/// struct Latest(mpsc::Receiver<u32>);
+///
+/// #[async_trait]
+/// impl UnpackableResult for Latest {
+///     type UnpackedResult = u32;
+///
+///     async fn unpack_result(mut self) -> Result<u32, ActorSendError> {
+///         let mut latest = None;
+///
+///         while let Some(value) = self.0.recv().await {
+///             latest = Some(value);
+///         }
+///
+///         latest.ok_or(ActorSendError::FailedToGetResponse)
+///     }
+/// }
+/// ```
 #[async_trait]
 pub trait UnpackableResult {
     type UnpackedResult;
@@ -77,3 +623,87 @@ impl<T: Send, E: Send> UnpackableResult for Result<oneshot::Receiver<Result<T, E
         }
     }
 }
+
+/// Result computed after the handler returns.
+///
+/// Returning `Deferred<T>` from a handler immediately frees the actor
+/// message loop, the wrapped future is awaited by
+/// [Address::send_unpack](crate::Address::send_unpack) on the caller side.
+/// Keep in mind that the future can't borrow the actor state.
+///
+/// ```
+/// # use akt::{Actor, Context, Deferred, Handler, Message};
+/// # use async_trait::async_trait;
+/// # struct Fetcher;
+/// # impl Actor for Fetcher {}
+/// struct Fetch;
+///
+/// impl Message for Fetch {
+///     type Result = Deferred<u32>;
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Fetch> for Fetcher {
+///     async fn handle(&mut self, _message: Fetch, _context: &mut Context<Self>) -> Deferred<u32> {
+///         Deferred::new(async { 42 })
+///     }
+/// }
+/// ```
+pub struct Deferred<T> {
+    future: Pin<Box<dyn Future<Output = T> + Send>>,
+}
+
+impl<T> Deferred<T> {
+    pub fn new<F: Future<Output = T> + Send + 'static>(future: F) -> Deferred<T> {
+        Deferred {
+            future: Box::pin(future),
+        }
+    }
+}
+
+impl<T> Future for Deferred<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<T> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+#[async_trait]
+impl<T: Send> UnpackableResult for Deferred<T> {
+    type UnpackedResult = T;
+
+    async fn unpack_result(self) -> Result<Self::UnpackedResult, ActorSendError> {
+        Ok(self.await)
+    }
+}
+
+#[async_trait]
+impl<T: Send> UnpackableResult for Pin<Box<dyn Future<Output = T> + Send>> {
+    type UnpackedResult = T;
+
+    async fn unpack_result(self) -> Result<Self::UnpackedResult, ActorSendError> {
+        Ok(self.await)
+    }
+}
+
+/// Unpacks the latest value, even if the sender is gone
+#[async_trait]
+impl<T: Clone + Send + Sync> UnpackableResult for watch::Receiver<T> {
+    type UnpackedResult = T;
+
+    async fn unpack_result(self) -> Result<Self::UnpackedResult, ActorSendError> {
+        Ok(self.borrow().clone())
+    }
+}
+
+/// Fails with [ActorSendError::FailedToGetResponse] if the task panicked
+/// or was aborted
+#[async_trait]
+impl<T: Send> UnpackableResult for JoinHandle<T> {
+    type UnpackedResult = T;
+
+    async fn unpack_result(self) -> Result<Self::UnpackedResult, ActorSendError> {
+        self.await.map_err(|_| ActorSendError::FailedToGetResponse)
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    handler::{cancel_on_disconnect, notification_handled_inline, respond, Envelope, Response},
+    Actor, ActorSendError, Context, CorrelationId, Message,
+};
+
+/// Handler that completes without awaiting anything.
+///
+/// Messages sent with [Address::send_inline](crate::Address::send_inline)
+/// or [Address::notify_inline](crate::Address::notify_inline) are handled
+/// by a plain function call, saving the allocation of a boxed future a
+/// [Handler](crate::Handler) takes for every message. Worth it for hot
+/// paths handling lots of tiny messages.
+///
+/// Inline handlers can't take the reply over with
+/// [Context::take_reply] and are not cancelled by
+/// [Actor::message_timeout], otherwise they are dispatched as usual.
+pub trait InlineHandler<M: Message>
+where
+    Self: Actor,
+{
+    fn handle(&mut self, message: M, context: &mut Context<Self>) -> M::Result;
+}
+
+pub(crate) struct InlineMessageWithSender<M: Message> {
+    pub message: M,
+    pub tx: oneshot::Sender<Response<M::Result>>,
+}
+
+pub(crate) struct InlineNotification<M>(pub M);
+
+#[async_trait]
+impl<M: Message + 'static, A: InlineHandler<M>> Envelope<A> for InlineMessageWithSender<M>
+where
+    M::Result: 'static,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        self.handle_inline(actor, context)
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn is_inline(&self) -> bool {
+        true
+    }
+
+    fn handle_inline(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let InlineMessageWithSender { message, tx } = *self;
+
+        // Receiver is no longer interested in the response
+        if tx.is_closed() && cancel_on_disconnect::<A, M>() {
+            return;
+        }
+
+        let result = InlineHandler::handle(actor, message, context);
+
+        respond::<A, M, _>(
+            result,
+            Some(|result| {
+                let _ = tx.send(Ok(result));
+            }),
+            actor,
+            context,
+        );
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id()
+    }
+
+    async fn reject(self: Box<Self>) {
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
+
+#[async_trait]
+impl<M: Message + 'static, A: InlineHandler<M>> Envelope<A> for InlineNotification<M> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        self.handle_inline(actor, context)
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn is_inline(&self) -> bool {
+        true
+    }
+
+    fn handle_inline(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let result = InlineHandler::handle(actor, self.0, context);
+
+        notification_handled_inline::<A, M>(result, context);
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.0.correlation_id()
+    }
+}
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use crate::{Actor, Context, Handler, Message};
+
+/// Closure run against the actor state, sent by
+/// [Address::with_state](crate::Address::with_state)
+pub(crate) struct WithState<A, R>(pub Box<dyn FnOnce(&mut A) -> R + Send>);
+
+impl<A: 'static, R: Send + 'static> Message for WithState<A, R> {
+    type Result = R;
+}
+
+#[async_trait]
+impl<A: Actor, R: Send + 'static> Handler<WithState<A, R>> for A {
+    async fn handle(&mut self, message: WithState<A, R>, _context: &mut Context<Self>) -> R {
+        (message.0)(self)
+    }
+}
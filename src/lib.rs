@@ -122,16 +122,106 @@
 
 mod actor;
 mod address;
+mod audit;
+mod batch;
+mod builder;
+mod bus;
+mod cancel;
+mod codec;
+mod concurrent;
 mod context;
+mod control;
+mod correlation;
+mod fanout;
 mod handler;
+mod inline;
+mod inspect;
+mod limiter;
+mod mailbox;
+mod migrate;
+mod persist;
+pub mod prelude;
+mod random;
+mod recipient;
+mod registry;
+mod retry;
+mod shutdown;
+mod sink;
+mod slot;
+mod spawn;
+mod status;
+mod stream;
+mod system;
+mod tap;
+mod validate;
 
 pub use self::{
-    actor::{Actor, ActorSpawner},
-    address::{ActorSendError, Address, FailedToDeliver, Message, UnboundedAddress},
-    context::{ActorState, Context},
-    handler::Handler,
+    actor::{
+        default_mailbox_capacity, Actor, ActorSpawner, MailboxPolicy, MessageSource, Ready,
+        StartError,
+    },
+    address::{
+        ActorSendError, Address, FailedToDeliver, FirstTick, Message, OrderedResponses, PollStream,
+        ResponseFuture, ResponseSet, SendPermit, SendUnpackError, UnboundedAddress, WeakAddress,
+        WeakUnboundedAddress,
+    },
+    audit::{AuditOutcome, AuditRecord},
+    batch::BatchHandler,
+    builder::{ActorBuilder, Spawned},
+    bus::{EventBus, Publish, Subscribe, SubscriptionId, Unsubscribe},
+    cancel::CancellationToken,
+    codec::{Codec, DecodeError},
+    concurrent::ConcurrentHandler,
+    context::{ActorState, AddressGuard, ChildGuard, Context, StopReason},
+    control::{ControlAddress, Reconfigure},
+    correlation::CorrelationId,
+    fanout::{send_all, send_all_tracked},
+    handler::{Deferred, Handler, PanicReason, ReplyHandle, UnpackableResult},
+    inline::InlineHandler,
+    limiter::{ConcurrencyLimiter, SpawnLimiter},
+    mailbox::{Letter, Mailbox},
+    migrate::Migrate,
+    persist::Journal,
+    recipient::{Recipient, WeakRecipient},
+    registry::{registry, Registry},
+    retry::RetryPolicy,
+    shutdown::{ShutdownCoordinator, ShutdownOrder},
+    sink::{Forward, NotifySink, Sink},
+    slot::AddressSlot,
+    spawn::SpawnHandle,
+    stream::{Next, ReceiverStream, Stream, StreamExt, StreamHandler, Subscription},
+    system::System,
+    tap::{TapStream, TappedMessage},
+    validate::{Validate, ValidationError},
 };
 
+/// Result of sending a message and waiting for the response
+pub type Result<T> = std::result::Result<T, ActorSendError>;
+
+#[cfg(feature = "derive")]
+pub use akt_derive::Message;
+
+#[cfg(feature = "unstable")]
+pub use self::handler::Envelope;
+
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "remote")]
+pub use self::remote::{RemoteAddress, RemoteError, RemoteMessage};
+
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use self::testing::{MockContext, TestActor};
+
+#[cfg(feature = "load")]
+mod load;
+#[cfg(feature = "load")]
+pub use self::load::{Load, LoadReport};
+
+#[cfg(feature = "deadlock-detection")]
+mod deadlock;
+
 #[cfg(feature = "error-stack")]
 mod error_stack;
 #[cfg(feature = "error-stack")]
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, SemaphorePermit};
+
+/// Pool of permits shared by actors to bound how many handlers run at once.
+///
+/// Actors returning the limiter from [Actor::limiter](crate::Actor::limiter)
+/// wait for a permit before handling each message and release it once the
+/// handler returns. Clones share the same pool.
+///
+/// ```
+/// # use akt::{Actor, ConcurrencyLimiter};
+/// struct Worker {
+///     limiter: ConcurrencyLimiter,
+/// }
+///
+/// impl Actor for Worker {
+///     fn limiter(&self) -> Option<ConcurrencyLimiter> {
+///         Some(self.limiter.clone())
+///     }
+/// }
+///
+/// let limiter = ConcurrencyLimiter::new(4);
+/// let worker = Worker { limiter: limiter.clone() };
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimiter {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimiter {
+    /// Creates limiter allowing at most `permits` handlers to run at once
+    pub fn new(permits: usize) -> ConcurrencyLimiter {
+        ConcurrencyLimiter {
+            semaphore: Arc::new(Semaphore::new(permits)),
+        }
+    }
+
+    /// Number of handlers that could start right now
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    pub(crate) async fn acquire(&self) -> SemaphorePermit<'_> {
+        self.semaphore
+            .acquire()
+            .await
+            .expect("limiter semaphore is never closed")
+    }
+}
+
+/// Bulkhead bounding how many actors spawned through it exist at once.
+///
+/// [ActorSpawner::spawn_run_limited](crate::ActorSpawner::spawn_run_limited)
+/// waits for a permit before spawning an actor, the permit is released once
+/// the actor task is finished. Clones share the same pool.
+#[derive(Debug, Clone)]
+pub struct SpawnLimiter {
+    semaphore: Arc<Semaphore>,
+}
+
+impl SpawnLimiter {
+    /// Creates limiter allowing at most `actors` actors at once
+    pub fn new(actors: usize) -> SpawnLimiter {
+        SpawnLimiter {
+            semaphore: Arc::new(Semaphore::new(actors)),
+        }
+    }
+
+    /// Number of actors that could be spawned right now
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    pub(crate) async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("limiter semaphore is never closed")
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use tokio::{task::JoinSet, time::Instant};
+
+use crate::{random, Actor, Address, Handler, Message};
+
+type MessageFactory<M> = Box<dyn FnMut() -> M + Send>;
+
+/// One message per nanosecond, the finest period of an interval
+const MAX_RATE: u32 = 1_000_000_000;
+
+/// Load testing helper sending messages to an actor at a fixed rate.
+///
+/// Each message is picked at random among the registered factories
+/// proportionally to their weights. Requests are sent without waiting for
+/// previous responses, so slow handlers show up as growing latencies.
+///
+/// ```
+/// # use akt::{Actor, Context, Handler, Load, Message};
+/// # use async_trait::async_trait;
+/// # use std::time::Duration;
+/// # struct Echo;
+/// # impl Actor for Echo {}
+/// # struct Ping;
+/// # impl Message for Ping { type Result = (); }
+/// # #[async_trait]
+/// # impl Handler<Ping> for Echo {
+/// #     async fn handle(&mut self, _message: Ping, _context: &mut Context<Self>) {}
+/// # }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let report = Load::new(Echo.run())
+///     .message(1, || Ping)
+///     .rate(100)
+///     .duration(Duration::from_millis(50))
+///     .run()
+///     .await;
+///
+/// println!("p99: {:?}", report.percentile(99.0));
+/// # }
+/// ```
+pub struct Load<A: Actor, M> {
+    address: Address<A>,
+    factories: Vec<(u32, MessageFactory<M>)>,
+    rate: u32,
+    duration: Duration,
+}
+
+impl<A: Actor, M: Message + 'static> Load<A, M>
+where
+    A: Handler<M>,
+{
+    /// Creates load sending 100 messages per second for 1 second
+    pub fn new(address: Address<A>) -> Load<A, M> {
+        Load {
+            address,
+            factories: Vec::new(),
+            rate: 100,
+            duration: Duration::from_secs(1),
+        }
+    }
+
+    /// Registers a message factory picked with the given relative weight
+    pub fn message<F: FnMut() -> M + Send + 'static>(mut self, weight: u32, factory: F) -> Self {
+        self.factories.push((weight, Box::new(factory)));
+        self
+    }
+
+    /// Messages sent per second, clamped to `1..=1_000_000_000` so that
+    /// messages are at least a nanosecond apart
+    pub fn rate(mut self, per_second: u32) -> Self {
+        self.rate = per_second.clamp(1, MAX_RATE);
+        self
+    }
+
+    /// How long to keep sending messages
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Runs the load and waits for all the responses
+    pub async fn run(mut self) -> LoadReport {
+        let total_weight: u64 = self.factories.iter().map(|(w, _)| *w as u64).sum();
+
+        let mut requests = JoinSet::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(1) / self.rate);
+        let deadline = Instant::now() + self.duration;
+
+        if total_weight == 0 {
+            return LoadReport {
+                latencies: Vec::new(),
+                failed: 0,
+            };
+        }
+
+        loop {
+            if interval.tick().await >= deadline {
+                break;
+            }
+
+            let mut pick = random::below(total_weight);
+
+            let factory = self
+                .factories
+                .iter_mut()
+                .find(|(weight, _)| {
+                    if pick < *weight as u64 {
+                        return true;
+                    }
+
+                    pick -= *weight as u64;
+                    false
+                })
+                .map(|(_, factory)| factory)
+                .expect("pick is always below the total weight");
+
+            let message = factory();
+            let address = self.address.clone();
+
+            requests.spawn(async move {
+                let started = Instant::now();
+
+                address.send(message).await.map(|_| started.elapsed())
+            });
+        }
+
+        let mut report = LoadReport {
+            latencies: Vec::new(),
+            failed: 0,
+        };
+
+        while let Some(result) = requests.join_next().await {
+            match result {
+                Ok(Ok(latency)) => report.latencies.push(latency),
+                _ => report.failed += 1,
+            }
+        }
+
+        report.latencies.sort();
+
+        report
+    }
+}
+
+/// Statistics collected by [Load::run]
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    latencies: Vec<Duration>,
+    failed: usize,
+}
+
+impl LoadReport {
+    /// Number of messages handled successfully
+    pub fn handled(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Number of messages that were not delivered or not responded
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// Latency percentile, `percentile` is in the `0.0..=100.0` range.
+    ///
+    /// Returns `None` if no message was handled.
+    pub fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (self.latencies.len() - 1) as f64)
+            .round() as usize;
+
+        Some(self.latencies[rank])
+    }
+}
@@ -0,0 +1,100 @@
+use tokio::sync::mpsc;
+
+use crate::{handler::Envelope, Actor};
+
+type BoxedEnvelope<A> = Box<dyn Envelope<A> + Send>;
+
+/// Custom public mailbox of an actor, see [Actor::run_with_mailbox].
+///
+/// Messages are moved from the address channel into the mailbox every time
+/// the actor is about to receive one, and the actor handles whatever the
+/// mailbox yields next. So a mailbox may reorder messages or drop them,
+/// e.g. overwrite the oldest ones to keep only recent telemetry. Senders
+/// still wait for room in the channel only while a single handler runs.
+pub trait Mailbox<A: Actor>: Send + 'static {
+    fn push(&mut self, letter: Letter<A>);
+
+    fn pop(&mut self) -> Option<Letter<A>>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Message waiting in a [Mailbox], opaque apart from its type name.
+///
+/// Dropping it drops the message, its sender gets
+/// [ActorSendError::FailedToGetResponse](crate::ActorSendError::FailedToGetResponse).
+pub struct Letter<A: Actor> {
+    envelope: BoxedEnvelope<A>,
+}
+
+impl<A: Actor> Letter<A> {
+    /// Name of the message type
+    pub fn message_type(&self) -> &'static str {
+        self.envelope.message_type()
+    }
+}
+
+/// Public mailbox of the message loop, the address channel optionally
+/// followed by a custom [Mailbox]
+pub(crate) struct PublicMailbox<A: Actor> {
+    rx: mpsc::Receiver<BoxedEnvelope<A>>,
+    custom: Option<Box<dyn Mailbox<A>>>,
+}
+
+impl<A: Actor> PublicMailbox<A> {
+    pub fn new(rx: mpsc::Receiver<BoxedEnvelope<A>>) -> PublicMailbox<A> {
+        PublicMailbox { rx, custom: None }
+    }
+
+    pub fn set_custom(&mut self, mailbox: Box<dyn Mailbox<A>>) {
+        self.custom = Some(mailbox);
+    }
+
+    /// Waits for the next message, cancel safe
+    pub async fn recv(&mut self) -> Option<BoxedEnvelope<A>> {
+        let custom = match &mut self.custom {
+            Some(custom) => custom,
+            None => return self.rx.recv().await,
+        };
+
+        loop {
+            while let Ok(envelope) = self.rx.try_recv() {
+                custom.push(Letter { envelope });
+            }
+
+            if let Some(letter) = custom.pop() {
+                return Some(letter.envelope);
+            }
+
+            let envelope = self.rx.recv().await?;
+
+            custom.push(Letter { envelope });
+        }
+    }
+
+    pub fn try_recv(&mut self) -> Option<BoxedEnvelope<A>> {
+        let custom = match &mut self.custom {
+            Some(custom) => custom,
+            None => return self.rx.try_recv().ok(),
+        };
+
+        while let Ok(envelope) = self.rx.try_recv() {
+            custom.push(Letter { envelope });
+        }
+
+        custom.pop().map(|letter| letter.envelope)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rx.len() + self.custom.as_ref().map_or(0, |custom| custom.len())
+    }
+
+    /// Stops accepting new messages, queued ones are kept
+    pub fn close(&mut self) {
+        self.rx.close();
+    }
+}
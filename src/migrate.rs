@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{handler::Envelope, Actor, ActorState, Context};
+
+/// Transfers state from the instance replaced by
+/// [Address::hot_swap](crate::Address::hot_swap) to the new one.
+///
+/// The swap follows the rules of
+/// [Context::replace](crate::Context::replace): [Actor::on_stopped] runs on
+/// the previous instance before it is passed to [Migrate::migrate], and
+/// [Actor::try_start] runs on the new one once the state is migrated. The
+/// other actors holding its addresses don't notice the swap.
+#[async_trait]
+pub trait Migrate
+where
+    Self: Actor,
+{
+    async fn migrate(&mut self, previous: Self, context: &mut Context<Self>);
+}
+
+pub(crate) struct SwapEnvelope<A> {
+    pub actor: A,
+    pub tx: oneshot::Sender<()>,
+}
+
+#[async_trait]
+impl<A: Migrate> Envelope<A> for SwapEnvelope<A> {
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let SwapEnvelope { actor: new, tx } = *self;
+
+        let mut previous = std::mem::replace(actor, new);
+
+        previous.on_stopped(context).await;
+        actor.migrate(previous, context).await;
+
+        if actor.try_start(context).await.is_err() {
+            context.start_failed = true;
+            context.state = ActorState::Stopping;
+
+            return;
+        }
+
+        let _ = tx.send(());
+    }
+}
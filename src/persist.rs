@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    handler::{handle_request, notification_handled, Envelope, MapReply, Response},
+    Actor, ActorSendError, Codec, Context, CorrelationId, Handler, Message,
+};
+
+/// Storage of messages sent with
+/// [Address::send_persisted](crate::Address::send_persisted) and
+/// [Address::notify_persisted](crate::Address::notify_persisted).
+///
+/// Messages are persisted by the sender before they are put in the mailbox
+/// and acknowledged by the actor once handled, so entries that are never
+/// acknowledged are the messages a crash interrupted, including the ones
+/// still waiting in the mailbox. They are replayed in [Actor::recover].
+///
+/// Senders may persist messages while the actor is still recovering, only
+/// the entries left by the previous run should be replayed.
+#[async_trait]
+pub trait Journal: Send + Sync + 'static {
+    /// Persists a message, `tag` is the name of the message type and
+    /// `bytes` its [Codec] encoding. Returns the id passed to
+    /// [Journal::acknowledge] once the message is handled.
+    async fn persist(&self, tag: &'static str, bytes: Vec<u8>) -> u64;
+
+    /// Called once the message persisted under `id` is handled, the entry
+    /// could be removed
+    async fn acknowledge(&self, id: u64);
+}
+
+/// Journal entry of an enqueued message, none if the actor has no journal
+#[derive(Clone)]
+pub(crate) struct Receipt {
+    journal: Arc<dyn Journal>,
+    id: u64,
+}
+
+impl Receipt {
+    pub async fn persist<M: Codec>(
+        journal: Option<&Arc<dyn Journal>>,
+        message: &M,
+    ) -> Option<Receipt> {
+        let journal = journal?.clone();
+        let id = journal
+            .persist(std::any::type_name::<M>(), message.encode())
+            .await;
+
+        Some(Receipt { journal, id })
+    }
+
+    /// Acknowledges the entry, the message is handled, rejected or was
+    /// never delivered
+    pub async fn acknowledge(receipt: Option<Receipt>) {
+        if let Some(receipt) = receipt {
+            receipt.journal.acknowledge(receipt.id).await;
+        }
+    }
+}
+
+pub(crate) struct PersistedMessageWithSender<M: Message> {
+    pub message: M,
+    pub tx: oneshot::Sender<Response<M::Result>>,
+    pub receipt: Option<Receipt>,
+}
+
+pub(crate) struct PersistedNotification<M> {
+    pub message: M,
+    pub receipt: Option<Receipt>,
+}
+
+#[async_trait]
+impl<M: Message + Codec, A: Actor> Envelope<A> for PersistedMessageWithSender<M>
+where
+    A: Handler<M>,
+    M::Result: 'static,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let PersistedMessageWithSender {
+            message,
+            tx,
+            receipt,
+        } = *self;
+
+        // Persisted messages are never cancelled, a handler interrupted
+        // halfway would be acknowledged without having done its work
+        handle_request(
+            actor,
+            message,
+            Box::new(MapReply::response(tx)),
+            false,
+            context,
+        )
+        .await;
+
+        Receipt::acknowledge(receipt).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id()
+    }
+
+    async fn reject(self: Box<Self>) {
+        // The caller is told to retry, the entry must not be replayed too
+        Receipt::acknowledge(self.receipt).await;
+
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
+
+#[async_trait]
+impl<M: Message + Codec, A: Actor> Envelope<A> for PersistedNotification<M>
+where
+    A: Handler<M>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let result = actor.handle(self.message, context).await;
+
+        Receipt::acknowledge(self.receipt).await;
+
+        notification_handled::<A, M>(result, actor, context).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id()
+    }
+
+    async fn reject(self: Box<Self>) {
+        Receipt::acknowledge(self.receipt).await;
+    }
+}
@@ -0,0 +1,29 @@
+//! Traits and types needed to define and talk to actors.
+//!
+//! ```
+//! use akt::prelude::*;
+//!
+//! struct Greeter;
+//!
+//! impl Actor for Greeter {}
+//!
+//! struct Greet;
+//!
+//! impl Message for Greet {
+//!     type Result = &'static str;
+//! }
+//!
+//! #[async_trait]
+//! impl Handler<Greet> for Greeter {
+//!     async fn handle(&mut self, _message: Greet, _context: &mut Context<Self>) -> &'static str {
+//!         "Hello"
+//!     }
+//! }
+//! ```
+
+pub use async_trait::async_trait;
+
+pub use crate::{
+    Actor, ActorSendError, Address, Context, FailedToDeliver, Handler, Message, Recipient,
+    StreamHandler,
+};
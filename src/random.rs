@@ -0,0 +1,20 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Random 64 bits, every `RandomState` is seeded differently
+fn bits() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Random number in `[0, 1)`, good enough for jitter
+pub(crate) fn fraction() -> f64 {
+    (bits() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Random number in `[0, bound)`, `bound` must not be zero
+#[cfg(feature = "load")]
+pub(crate) fn below(bound: u64) -> u64 {
+    bits() % bound
+}
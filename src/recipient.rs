@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    address::{Address, UnboundedAddress, WeakAddress, WeakUnboundedAddress},
+    Actor, ActorSendError, FailedToDeliver, Handler, Message,
+};
+
+/// Type-erased address of any actor handling `M`.
+///
+/// It is useful to hand out a reference capable to send only one specific
+/// kind of message, without exposing the actor type.
+pub struct Recipient<M: Message> {
+    sender: Box<dyn RecipientSender<M>>,
+}
+
+impl<M: Message + 'static> Recipient<M> {
+    pub(crate) fn new<S: RecipientSender<M> + 'static>(sender: S) -> Recipient<M> {
+        Recipient {
+            sender: Box::new(sender),
+        }
+    }
+
+    /// Sends a message to the actor
+    pub async fn send(&self, message: M) -> Result<M::Result, ActorSendError> {
+        self.sender.send(message).await
+    }
+
+    /// Sends a notification to the actor
+    pub async fn notify(&self, message: M) -> Result<(), FailedToDeliver> {
+        self.sender.notify(message).await
+    }
+
+    /// Returns `true` if the actor do not receive messages any more.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Returns `true` if actor is still receiving messages.
+    pub fn is_connected(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Creates a recipient that doesn't keep the actor alive
+    pub fn downgrade(&self) -> WeakRecipient<M> {
+        WeakRecipient {
+            sender: self.sender.downgrade(),
+        }
+    }
+}
+
+impl<M: Message> Clone for Recipient<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.boxed_clone(),
+        }
+    }
+}
+
+impl<A: Handler<M>, M: Message + 'static> From<Address<A>> for Recipient<M> {
+    fn from(address: Address<A>) -> Self {
+        Recipient::new(address)
+    }
+}
+
+impl<A: Handler<M>, M: Message + 'static> From<UnboundedAddress<A>> for Recipient<M> {
+    fn from(address: UnboundedAddress<A>) -> Self {
+        Recipient::new(address)
+    }
+}
+
+/// Type-erased weak address of any actor handling `M`, created with
+/// [Recipient::downgrade].
+///
+/// Useful for subscriber lists that should not keep subscribers alive,
+/// recipients that fail to upgrade belong to stopped actors and could be
+/// pruned.
+pub struct WeakRecipient<M: Message> {
+    sender: Box<dyn WeakRecipientSender<M>>,
+}
+
+impl<M: Message + 'static> WeakRecipient<M> {
+    /// Returns the recipient if the actor is still alive
+    pub fn upgrade(&self) -> Option<Recipient<M>> {
+        self.sender.upgrade()
+    }
+}
+
+impl<M: Message> Clone for WeakRecipient<M> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.boxed_clone(),
+        }
+    }
+}
+
+impl<A: Handler<M>, M: Message + 'static> From<WeakAddress<A>> for WeakRecipient<M> {
+    fn from(address: WeakAddress<A>) -> Self {
+        WeakRecipient {
+            sender: Box::new(address),
+        }
+    }
+}
+
+impl<A: Handler<M>, M: Message + 'static> From<WeakUnboundedAddress<A>> for WeakRecipient<M> {
+    fn from(address: WeakUnboundedAddress<A>) -> Self {
+        WeakRecipient {
+            sender: Box::new(address),
+        }
+    }
+}
+
+#[async_trait]
+pub(crate) trait RecipientSender<M: Message>: Send + Sync {
+    async fn send(&self, message: M) -> Result<M::Result, ActorSendError>;
+
+    async fn notify(&self, message: M) -> Result<(), FailedToDeliver>;
+
+    fn is_closed(&self) -> bool;
+
+    fn boxed_clone(&self) -> Box<dyn RecipientSender<M>>;
+
+    fn downgrade(&self) -> Box<dyn WeakRecipientSender<M>>;
+}
+
+pub(crate) trait WeakRecipientSender<M: Message>: Send + Sync {
+    fn upgrade(&self) -> Option<Recipient<M>>;
+
+    fn boxed_clone(&self) -> Box<dyn WeakRecipientSender<M>>;
+}
+
+impl<A: Handler<M>, M: Message + 'static> WeakRecipientSender<M> for WeakAddress<A> {
+    fn upgrade(&self) -> Option<Recipient<M>> {
+        WeakAddress::upgrade(self).map(Recipient::from)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn WeakRecipientSender<M>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<A: Handler<M>, M: Message + 'static> WeakRecipientSender<M> for WeakUnboundedAddress<A> {
+    fn upgrade(&self) -> Option<Recipient<M>> {
+        WeakUnboundedAddress::upgrade(self).map(Recipient::from)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn WeakRecipientSender<M>> {
+        Box::new(self.clone())
+    }
+}
+
+#[async_trait]
+impl<A: Handler<M>, M: Message + 'static> RecipientSender<M> for Address<A> {
+    async fn send(&self, message: M) -> Result<M::Result, ActorSendError> {
+        Address::send(self, message).await
+    }
+
+    async fn notify(&self, message: M) -> Result<(), FailedToDeliver> {
+        Address::notify(self, message).await
+    }
+
+    fn is_closed(&self) -> bool {
+        Address::is_closed(self)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RecipientSender<M>> {
+        Box::new(self.clone())
+    }
+
+    fn downgrade(&self) -> Box<dyn WeakRecipientSender<M>> {
+        Box::new(Address::downgrade(self))
+    }
+}
+
+#[async_trait]
+impl<A: Handler<M>, M: Message + 'static> RecipientSender<M> for UnboundedAddress<A> {
+    async fn send(&self, message: M) -> Result<M::Result, ActorSendError> {
+        UnboundedAddress::send(self, message).await
+    }
+
+    async fn notify(&self, message: M) -> Result<(), FailedToDeliver> {
+        UnboundedAddress::notify(self, message)
+    }
+
+    fn is_closed(&self) -> bool {
+        UnboundedAddress::is_closed(self)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RecipientSender<M>> {
+        Box::new(self.clone())
+    }
+
+    fn downgrade(&self) -> Box<dyn WeakRecipientSender<M>> {
+        Box::new(UnboundedAddress::downgrade(self))
+    }
+}
+
+/// Sender mapping messages into the ones the actor handles, see
+/// [Context::spawn_mapped](crate::Context::spawn_mapped)
+pub(crate) struct MappedSender<A: Actor, E, M> {
+    address: UnboundedAddress<A>,
+    map: Arc<dyn Fn(E) -> M + Send + Sync>,
+}
+
+impl<A: Actor, E, M> MappedSender<A, E, M> {
+    pub(crate) fn new(
+        address: UnboundedAddress<A>,
+        map: Arc<dyn Fn(E) -> M + Send + Sync>,
+    ) -> MappedSender<A, E, M> {
+        MappedSender { address, map }
+    }
+}
+
+#[async_trait]
+impl<A, E, M> RecipientSender<E> for MappedSender<A, E, M>
+where
+    A: Handler<M>,
+    E: Message + 'static,
+    M: Message<Result = E::Result> + 'static,
+{
+    async fn send(&self, message: E) -> Result<E::Result, ActorSendError> {
+        self.address.send((self.map)(message)).await
+    }
+
+    async fn notify(&self, message: E) -> Result<(), FailedToDeliver> {
+        self.address.notify((self.map)(message))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.address.is_closed()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RecipientSender<E>> {
+        Box::new(MappedSender::new(self.address.clone(), self.map.clone()))
+    }
+
+    fn downgrade(&self) -> Box<dyn WeakRecipientSender<E>> {
+        Box::new(WeakMappedSender {
+            address: self.address.downgrade(),
+            map: self.map.clone(),
+        })
+    }
+}
+
+struct WeakMappedSender<A: Actor, E, M> {
+    address: WeakUnboundedAddress<A>,
+    map: Arc<dyn Fn(E) -> M + Send + Sync>,
+}
+
+impl<A, E, M> WeakRecipientSender<E> for WeakMappedSender<A, E, M>
+where
+    A: Handler<M>,
+    E: Message + 'static,
+    M: Message<Result = E::Result> + 'static,
+{
+    fn upgrade(&self) -> Option<Recipient<E>> {
+        let address = self.address.upgrade()?;
+
+        Some(Recipient::new(MappedSender::new(address, self.map.clone())))
+    }
+
+    fn boxed_clone(&self) -> Box<dyn WeakRecipientSender<E>> {
+        Box::new(WeakMappedSender {
+            address: self.address.clone(),
+            map: self.map.clone(),
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{address::WeakAddress, Actor, Address};
+
+type Entries = HashMap<(TypeId, String), Box<dyn Any + Send>>;
+
+/// Global directory of named actors.
+///
+/// Actors returning a name from [Actor::name] are registered automatically
+/// once run. Only weak addresses are stored, so being registered doesn't
+/// keep an actor alive and stopped actors disappear from the registry.
+/// Names are scoped by actor type, so different actor types may share the
+/// same name.
+///
+/// ```
+/// # use akt::{registry, Actor};
+/// struct Database;
+///
+/// impl Actor for Database {
+///     fn name(&self) -> Option<&str> {
+///         Some("db")
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let address = Database.run();
+///
+/// assert!(registry().get::<Database>("db").is_some());
+/// # }
+/// ```
+pub struct Registry {
+    // Created on first use, so the global registry is a plain static
+    entries: Mutex<Option<Entries>>,
+}
+
+/// Returns the global [Registry]
+pub fn registry() -> &'static Registry {
+    static REGISTRY: Registry = Registry {
+        entries: Mutex::new(None),
+    };
+
+    &REGISTRY
+}
+
+impl Registry {
+    /// Registers the address under the given name replacing the one that
+    /// was registered before for the same actor type and name
+    pub fn register_named<A: Actor>(&self, name: impl Into<String>, address: &Address<A>) {
+        self.with_entries(|entries| {
+            entries.insert(
+                (TypeId::of::<A>(), name.into()),
+                Box::new(address.downgrade()),
+            )
+        });
+    }
+
+    /// Returns the address registered under the given name if the actor is
+    /// still running
+    pub fn get<A: Actor>(&self, name: &str) -> Option<Address<A>> {
+        let key = (TypeId::of::<A>(), name.to_string());
+
+        self.with_entries(|entries| {
+            let address = entries
+                .get(&key)?
+                .downcast_ref::<WeakAddress<A>>()?
+                .upgrade()
+                .filter(Address::is_connected);
+
+            if address.is_none() {
+                entries.remove(&key);
+            }
+
+            address
+        })
+    }
+
+    /// Removes the name, returns `false` if nothing was registered under it
+    pub fn unregister<A: Actor>(&self, name: &str) -> bool {
+        self.with_entries(|entries| {
+            entries
+                .remove(&(TypeId::of::<A>(), name.to_string()))
+                .is_some()
+        })
+    }
+
+    fn with_entries<R>(&self, f: impl FnOnce(&mut Entries) -> R) -> R {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        f(entries.get_or_insert_with(HashMap::new))
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashMap, error::Error, fmt::Display, marker::PhantomData};
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    handler::{cancel_on_disconnect, handle_request, Envelope, MapReply, Response},
+    Actor, ActorSendError, Address, Codec, Context, DecodeError, Handler, Message,
+};
+
+/// Message that could be sent as bytes through a [RemoteAddress]
+pub trait RemoteMessage: Message + Codec
+where
+    Self::Result: Codec,
+{
+    /// Name the message is routed by, unique among the messages of an actor
+    const TAG: &'static str;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RemoteError {
+    Send(ActorSendError),
+
+    /// No message is routed under the tag
+    UnknownMessage(String),
+
+    Decode(DecodeError),
+}
+
+impl Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::Send(err) => write!(f, "{}", err),
+            RemoteError::UnknownMessage(tag) => write!(f, "Unknown message {}", tag),
+            RemoteError::Decode(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for RemoteError {}
+
+impl From<ActorSendError> for RemoteError {
+    fn from(err: ActorSendError) -> Self {
+        RemoteError::Send(err)
+    }
+}
+
+type Reply = oneshot::Sender<Response<Result<Vec<u8>, RemoteError>>>;
+
+type Pack<A> = fn(Vec<u8>, Reply) -> Box<dyn Envelope<A> + Send>;
+
+/// Address accepting messages as bytes, the receiving end of a transport.
+///
+/// Each message type has to be routed with [RemoteAddress::route] first.
+/// Bytes are decoded by the actor itself, right before the message is
+/// handled, and the response is encoded back. [RemoteAddress::send] makes
+/// the whole round trip in process, which is what a transport does with a
+/// network in between.
+///
+/// ```
+/// # use akt::{Actor, Codec, Context, DecodeError, Handler, Message, RemoteAddress, RemoteMessage};
+/// # use async_trait::async_trait;
+/// struct Echo;
+///
+/// impl Actor for Echo {}
+///
+/// struct Shout(String);
+///
+/// impl Message for Shout {
+///     type Result = String;
+/// }
+///
+/// impl Codec for Shout {
+///     fn encode(&self) -> Vec<u8> {
+///         self.0.encode()
+///     }
+///
+///     fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+///         String::decode(bytes).map(Shout)
+///     }
+/// }
+///
+/// impl RemoteMessage for Shout {
+///     const TAG: &'static str = "shout";
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Shout> for Echo {
+///     async fn handle(&mut self, message: Shout, _context: &mut Context<Self>) -> String {
+///         message.0.to_uppercase()
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let remote = RemoteAddress::new(Echo.run()).route::<Shout>();
+///
+/// assert_eq!(remote.send(Shout("hi".into())).await, Ok("HI".to_string()));
+/// # }
+/// ```
+pub struct RemoteAddress<A: Actor> {
+    address: Address<A>,
+    routes: HashMap<&'static str, Pack<A>>,
+}
+
+impl<A: Actor> RemoteAddress<A> {
+    pub fn new(address: Address<A>) -> RemoteAddress<A> {
+        RemoteAddress {
+            address,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Accepts `M` under [RemoteMessage::TAG]
+    pub fn route<M>(mut self) -> Self
+    where
+        A: Handler<M>,
+        M: RemoteMessage + 'static,
+        M::Result: Codec,
+    {
+        self.routes.insert(M::TAG, pack::<A, M>);
+        self
+    }
+
+    /// Delivers an encoded message and waits for the encoded response
+    pub async fn send_bytes(&self, tag: &str, bytes: Vec<u8>) -> Result<Vec<u8>, RemoteError> {
+        let pack = self
+            .routes
+            .get(tag)
+            .ok_or_else(|| RemoteError::UnknownMessage(tag.to_string()))?;
+
+        let (tx, rx) = oneshot::channel();
+
+        self.address
+            .tx
+            .send(pack(bytes, tx))
+            .await
+            .map_err(|_| ActorSendError::FailedToDeliver)?;
+
+        rx.await
+            .map_err(|_| ActorSendError::FailedToGetResponse)??
+    }
+
+    /// Encodes the message, sends it as bytes and decodes the response
+    pub async fn send<M>(&self, message: M) -> Result<M::Result, RemoteError>
+    where
+        M: RemoteMessage,
+        M::Result: Codec,
+    {
+        let bytes = self.send_bytes(M::TAG, message.encode()).await?;
+
+        M::Result::decode(&bytes).map_err(RemoteError::Decode)
+    }
+
+    /// Address the messages are delivered to
+    pub fn address(&self) -> &Address<A> {
+        &self.address
+    }
+}
+
+impl<A: Actor> Clone for RemoteAddress<A> {
+    fn clone(&self) -> Self {
+        RemoteAddress {
+            address: self.address.clone(),
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+fn pack<A, M>(bytes: Vec<u8>, tx: Reply) -> Box<dyn Envelope<A> + Send>
+where
+    A: Handler<M>,
+    M: RemoteMessage + 'static,
+    M::Result: Codec,
+{
+    Box::new(EncodedMessage::<M> {
+        bytes,
+        tx,
+        message: PhantomData,
+    })
+}
+
+/// Encodes the response of a remote message
+fn encoded<T: Codec>(result: T) -> Result<Vec<u8>, RemoteError> {
+    Ok(result.encode())
+}
+
+/// Message decoded only once it is about to be handled
+struct EncodedMessage<M> {
+    bytes: Vec<u8>,
+    tx: Reply,
+    message: PhantomData<fn() -> M>,
+}
+
+#[async_trait]
+impl<M, A> Envelope<A> for EncodedMessage<M>
+where
+    A: Handler<M>,
+    M: RemoteMessage + 'static,
+    M::Result: Codec,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        match M::decode(&self.bytes) {
+            Ok(message) => {
+                handle_request(
+                    actor,
+                    message,
+                    Box::new(MapReply::new(self.tx, encoded)),
+                    cancel_on_disconnect::<A, M>(),
+                    context,
+                )
+                .await;
+            }
+            Err(err) => {
+                context.failed = true;
+
+                let _ = self.tx.send(Ok(Err(RemoteError::Decode(err))));
+            }
+        }
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    async fn reject(self: Box<Self>) {
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use crate::{random, Actor, ActorSendError, Address, Handler, Message};
+
+/// How [Context::call_with_retry](crate::Context::call_with_retry) and
+/// [Address::send_retry] retry failed calls.
+///
+/// Delays grow exponentially from the initial backoff up to the maximum
+/// one. Jitter randomly shortens each delay by up to the given fraction so
+/// that many callers failing at once don't retry in lockstep.
+///
+/// ```
+/// # use akt::RetryPolicy;
+/// # use std::time::Duration;
+/// let policy = RetryPolicy::new(5)
+///     .backoff(Duration::from_millis(10), Duration::from_secs(1))
+///     .jitter(0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+}
+
+impl RetryPolicy {
+    /// Creates policy making at most `max_attempts` calls, the first one
+    /// included, with backoff starting at 10ms, doubling up to 1s and no jitter
+    pub fn new(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.0,
+        }
+    }
+
+    /// Delay before the first retry and the upper bound for the next ones
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Factor each next delay is multiplied by
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Fraction of each delay, from 0 to 1, that may be randomly cut off
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay before the given retry, starting from 0
+    fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.powi(retry.min(i32::MAX as u32) as i32);
+        let delay =
+            (self.initial_backoff.as_secs_f64() * factor).min(self.max_backoff.as_secs_f64());
+
+        Duration::from_secs_f64(delay * (1.0 - self.jitter * random::fraction()))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+/// Sends the message retrying the errors `retry_on` returns `true` for
+pub(crate) async fn call_with_retry<B, M>(
+    to: &Address<B>,
+    message: M,
+    policy: RetryPolicy,
+    retry_on: fn(&ActorSendError) -> bool,
+) -> Result<M::Result, ActorSendError>
+where
+    B: Actor + Handler<M>,
+    M: Message + Clone + 'static,
+{
+    let mut retry = 0;
+
+    loop {
+        let err = match to.send(message.clone()).await {
+            Ok(result) => return Ok(result),
+            Err(err) => err,
+        };
+
+        if !retry_on(&err) || retry + 1 >= policy.max_attempts {
+            return Err(err);
+        }
+
+        tokio::time::sleep(policy.delay(retry)).await;
+
+        retry += 1;
+    }
+}
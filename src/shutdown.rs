@@ -0,0 +1,200 @@
+use std::{
+    mem,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use tokio::sync::oneshot::{self, error::TryRecvError};
+
+/// Single entry point stopping a set of actors and waiting until they are
+/// gone.
+///
+/// Actors join with [Context::register_for_shutdown](crate::Context::register_for_shutdown),
+/// usually from [Actor::on_start](crate::Actor::on_start). Only the private
+/// address of each actor is kept, so being registered doesn't keep an actor
+/// alive. The stop goes through [Actor::on_stopping](crate::Actor::on_stopping)
+/// with [StopReason::Requested](crate::StopReason::Requested), an actor
+/// cancelling it keeps the shutdown waiting.
+///
+/// ```
+/// # use akt::{Actor, Context, ShutdownCoordinator};
+/// # use async_trait::async_trait;
+/// struct Worker(ShutdownCoordinator);
+///
+/// #[async_trait]
+/// impl Actor for Worker {
+///     async fn on_start(&mut self, context: &mut Context<Self>) {
+///         context.register_for_shutdown(&self.0);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let coordinator = ShutdownCoordinator::new();
+///
+/// let (worker, ready) = Worker(coordinator.clone()).run_ready();
+/// ready.await.unwrap();
+///
+/// coordinator.shutdown().await;
+/// assert!(worker.is_closed());
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ShutdownCoordinator {
+    registrations: Arc<Mutex<Vec<Registration>>>,
+}
+
+/// Order in which [ShutdownCoordinator::shutdown_ordered] stops actors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownOrder {
+    /// Actors registered first are stopped first
+    Registration,
+
+    /// Actors registered last are stopped first, so actors depending on the
+    /// ones started before them go away before their dependencies
+    Reverse,
+
+    /// Each actor is stopped only after every actor depending on it, as
+    /// declared with
+    /// [Context::register_for_shutdown_named](crate::Context::register_for_shutdown_named),
+    /// otherwise in registration order. Unknown names are ignored, actors
+    /// caught in a dependency cycle are stopped in registration order once
+    /// nothing else is left.
+    Dependencies,
+}
+
+pub(crate) struct Registration {
+    stop: Box<dyn Fn() + Send>,
+    terminated: oneshot::Receiver<()>,
+    name: Option<String>,
+    depends_on: Vec<String>,
+}
+
+impl Registration {
+    pub(crate) fn new(stop: Box<dyn Fn() + Send>, terminated: oneshot::Receiver<()>) -> Self {
+        Registration {
+            stop,
+            terminated,
+            name: None,
+            depends_on: Vec::new(),
+        }
+    }
+
+    /// Names the registration and the ones it depends on, see
+    /// [ShutdownOrder::Dependencies]
+    pub(crate) fn with_dependencies(mut self, name: &str, depends_on: &[&str]) -> Self {
+        self.name = Some(name.to_owned());
+        self.depends_on = depends_on.iter().map(|&name| name.to_owned()).collect();
+
+        self
+    }
+
+    fn is_terminated(&mut self) -> bool {
+        matches!(self.terminated.try_recv(), Err(TryRecvError::Closed))
+    }
+
+    async fn terminated(self) {
+        let _ = self.terminated.await;
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> ShutdownCoordinator {
+        ShutdownCoordinator::default()
+    }
+
+    /// Stops all registered actors at once and waits until every one of
+    /// them is stopped
+    pub async fn shutdown(&self) {
+        let registrations = self.take();
+
+        for registration in &registrations {
+            (registration.stop)();
+        }
+
+        for registration in registrations {
+            registration.terminated().await;
+        }
+    }
+
+    /// Stops registered actors one by one, each next actor is stopped only
+    /// after the previous one is gone
+    pub async fn shutdown_ordered(&self, order: ShutdownOrder) {
+        let mut registrations = self.take();
+
+        match order {
+            ShutdownOrder::Registration => {}
+            ShutdownOrder::Reverse => registrations.reverse(),
+            ShutdownOrder::Dependencies => registrations = by_dependencies(registrations),
+        }
+
+        for registration in registrations {
+            (registration.stop)();
+
+            registration.terminated().await;
+        }
+    }
+
+    /// Number of registered actors that are still running
+    pub fn len(&self) -> usize {
+        let mut registrations = self.registrations();
+
+        registrations.retain_mut(|registration| !registration.is_terminated());
+
+        registrations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn register(&self, registration: Registration) {
+        let mut registrations = self.registrations();
+
+        // Forget actors that already stopped on their own
+        registrations.retain_mut(|registration| !registration.is_terminated());
+        registrations.push(registration);
+    }
+
+    fn take(&self) -> Vec<Registration> {
+        mem::take(&mut *self.registrations())
+    }
+
+    fn registrations(&self) -> MutexGuard<'_, Vec<Registration>> {
+        self.registrations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sorts registrations so each one comes after all the ones depending on
+/// it, keeping the registration order otherwise
+fn by_dependencies(registrations: Vec<Registration>) -> Vec<Registration> {
+    let mut left: Vec<Option<Registration>> = registrations.into_iter().map(Some).collect();
+    let mut sorted = Vec::with_capacity(left.len());
+
+    while sorted.len() < left.len() {
+        let is_depended_on = |name: &Option<String>| match name {
+            Some(name) => left
+                .iter()
+                .flatten()
+                .any(|other| other.depends_on.iter().any(|dependency| dependency == name)),
+            None => false,
+        };
+
+        // Falls back to the first one left if all of them are in a cycle
+        let next = left
+            .iter()
+            .position(|registration| match registration {
+                Some(registration) => !is_depended_on(&registration.name),
+                None => false,
+            })
+            .or_else(|| left.iter().position(Option::is_some));
+
+        match next.and_then(|index| left[index].take()) {
+            Some(registration) => sorted.push(registration),
+            None => break,
+        }
+    }
+
+    sorted
+}
@@ -0,0 +1,199 @@
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{self, ready, Poll},
+};
+
+use tokio::sync::mpsc::{self, error::SendError, OwnedPermit};
+
+use crate::{handler::Envelope, Actor, ActorSendError, Address, Handler, Message, Stream};
+
+/// A value into which other values can be sent asynchronously.
+///
+/// Mirrors `futures_sink::Sink` the same way [Stream] mirrors
+/// `futures_core::Stream`, so adapting it to third-party sinks is a matter
+/// of forwarding the four methods.
+pub trait Sink<Item> {
+    type Error;
+
+    /// Prepares the sink to receive a value
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>>;
+
+    /// Begins sending a value, must be preceded by a successful `poll_ready`
+    fn start_send(self: Pin<&mut Self>, item: Item) -> Result<(), Self::Error>;
+
+    /// Flushes any remaining output
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>>;
+
+    /// Flushes any remaining output and closes the sink
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>>;
+}
+
+type BoxedEnvelope<A> = Box<dyn Envelope<A> + Send>;
+
+type Reserve<A> =
+    Pin<Box<dyn Future<Output = Result<OwnedPermit<BoxedEnvelope<A>>, SendError<()>>> + Send>>;
+
+/// Sink of notifications returned by [Address::into_sink]
+pub struct NotifySink<A: Actor, M> {
+    tx: Option<mpsc::Sender<BoxedEnvelope<A>>>,
+    reserve: Option<Reserve<A>>,
+    permit: Option<OwnedPermit<BoxedEnvelope<A>>>,
+    message: PhantomData<fn(M)>,
+}
+
+impl<A: Actor, M> NotifySink<A, M> {
+    pub(crate) fn new(address: Address<A>) -> NotifySink<A, M> {
+        NotifySink {
+            tx: Some(address.tx),
+            reserve: None,
+            permit: None,
+            message: PhantomData,
+        }
+    }
+}
+
+// Nothing is structurally pinned, the pending reservation is boxed.
+impl<A: Actor, M> Unpin for NotifySink<A, M> {}
+
+impl<A, M> Sink<M> for NotifySink<A, M>
+where
+    A: Handler<M>,
+    M: Message + 'static,
+{
+    type Error = ActorSendError;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        if this.permit.is_some() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let tx = match &this.tx {
+            Some(tx) => tx,
+            None => return Poll::Ready(Err(ActorSendError::FailedToDeliver)),
+        };
+
+        let reserve = this
+            .reserve
+            .get_or_insert_with(|| Box::pin(tx.clone().reserve_owned()));
+
+        let result = ready!(reserve.as_mut().poll(cx));
+
+        this.reserve = None;
+
+        match result {
+            Ok(permit) => {
+                this.permit = Some(permit);
+
+                Poll::Ready(Ok(()))
+            }
+            Err(_) => Poll::Ready(Err(ActorSendError::FailedToDeliver)),
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: M) -> Result<(), Self::Error> {
+        let permit = self
+            .get_mut()
+            .permit
+            .take()
+            .ok_or(ActorSendError::FailedToDeliver)?;
+
+        permit.send(Box::new(message));
+
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Messages are in the mailbox as soon as they are sent
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+
+        // Only this sink's address is dropped, the actor keeps running
+        // while other addresses exist
+        this.permit = None;
+        this.reserve = None;
+        this.tx = None;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Future returned by [StreamExt::forward](crate::StreamExt::forward)
+pub struct Forward<S: Stream, Si> {
+    stream: S,
+    sink: Si,
+    buffered: Option<S::Item>,
+}
+
+impl<S: Stream, Si> Forward<S, Si> {
+    pub(crate) fn new(stream: S, sink: Si) -> Forward<S, Si> {
+        Forward {
+            stream,
+            sink,
+            buffered: None,
+        }
+    }
+}
+
+// Both the stream and the sink are required to be `Unpin`.
+impl<S: Stream, Si> Unpin for Forward<S, Si> {}
+
+impl<S, Si> Future for Forward<S, Si>
+where
+    S: Stream + Unpin,
+    Si: Sink<S::Item> + Unpin,
+{
+    type Output = Result<(), Si::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            if this.buffered.is_some() {
+                ready!(Pin::new(&mut this.sink).poll_ready(cx))?;
+
+                if let Some(item) = this.buffered.take() {
+                    Pin::new(&mut this.sink).start_send(item)?;
+                }
+            }
+
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(item)) => this.buffered = Some(item),
+                Poll::Ready(None) => {
+                    ready!(Pin::new(&mut this.sink).poll_close(cx))?;
+
+                    return Poll::Ready(Ok(()));
+                }
+                Poll::Pending => {
+                    ready!(Pin::new(&mut this.sink).poll_flush(cx))?;
+
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::{Actor, Address};
+
+/// Placeholder for an address that is known only after the actor is run.
+///
+/// Useful to wire pipelines where each stage needs the address of the next
+/// one: create all the slots first, pass them to the stages and fill them
+/// once every stage is running.
+///
+/// ```
+/// # use akt::{Actor, AddressSlot};
+/// # struct Sink;
+/// # impl Actor for Sink {}
+/// # struct Stage { next: AddressSlot<Sink> }
+/// # impl Actor for Stage {}
+/// # #[tokio::main]
+/// # async fn main() {
+/// let sink_slot = AddressSlot::new();
+///
+/// let stage = Stage { next: sink_slot.clone() }.run();
+/// let _ = sink_slot.fill(Sink.run());
+/// # }
+/// ```
+pub struct AddressSlot<A: Actor> {
+    tx: Arc<watch::Sender<Option<Address<A>>>>,
+    rx: watch::Receiver<Option<Address<A>>>,
+}
+
+impl<A: Actor> AddressSlot<A> {
+    pub fn new() -> AddressSlot<A> {
+        let (tx, rx) = watch::channel(None);
+
+        AddressSlot {
+            tx: Arc::new(tx),
+            rx,
+        }
+    }
+
+    /// Fills the slot. Returns the address back if the slot is already filled.
+    pub fn fill(&self, address: Address<A>) -> Result<(), Address<A>> {
+        let mut address = Some(address);
+
+        self.tx.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+
+            *current = address.take();
+
+            true
+        });
+
+        match address {
+            Some(address) => Err(address),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits until the slot is filled and returns the address.
+    ///
+    /// Never resolves if the slot is never filled.
+    pub async fn get(&self) -> Address<A> {
+        let mut rx = self.rx.clone();
+
+        let address = rx
+            .wait_for(Option::is_some)
+            .await
+            .expect("sender lives as long as the slot");
+
+        address.clone().expect("slot is filled")
+    }
+
+    /// Returns the address if the slot is already filled
+    pub fn try_get(&self) -> Option<Address<A>> {
+        self.rx.borrow().clone()
+    }
+}
+
+impl<A: Actor> Default for AddressSlot<A> {
+    fn default() -> Self {
+        AddressSlot::new()
+    }
+}
+
+impl<A: Actor> Clone for AddressSlot<A> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            rx: self.rx.clone(),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// Handle of a task delivering scheduled messages.
+///
+/// Returned by all the scheduling methods like
+/// [Address::notify_later](crate::Address::notify_later). Aborting it
+/// stops any further delivery. Dropping it doesn't.
+pub struct SpawnHandle {
+    handle: JoinHandle<()>,
+}
+
+impl SpawnHandle {
+    pub(crate) fn new(handle: JoinHandle<()>) -> SpawnHandle {
+        SpawnHandle { handle }
+    }
+
+    /// Cancels messages that are not delivered yet
+    pub fn abort(&self) {
+        self.handle.abort();
+    }
+
+    /// Returns `true` once everything is delivered or the task is aborted
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub(crate) fn abort_handle(&self) -> AbortHandle {
+        self.handle.abort_handle()
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::{atomic::AtomicU64, Arc, Mutex, PoisonError};
+
+use tokio::sync::watch;
+
+use crate::{ActorState, Journal};
+
+/// Actor status shared by its addresses and its message loop
+pub(crate) struct Status {
+    /// Type of the message a handler is stuck on, see
+    /// [Actor::watchdog_timeout](crate::Actor::watchdog_timeout)
+    pub stuck: Mutex<Option<&'static str>>,
+    /// Messages shed because the mailbox was full
+    pub dropped: AtomicU64,
+    pub state: watch::Sender<ActorState>,
+    /// Journal of the actor, messages are persisted by the senders
+    pub journal: Option<Arc<dyn Journal>>,
+}
+
+impl Status {
+    /// Publishes the state to the addresses, the published states only
+    /// move forward
+    pub fn set_state(&self, state: ActorState) {
+        self.state.send_if_modified(|current| {
+            let modified = *current < state;
+
+            if modified {
+                *current = state;
+            }
+
+            modified
+        });
+    }
+
+    pub fn set_stuck(&self, message_type: Option<&'static str>) {
+        *self.stuck.lock().unwrap_or_else(PoisonError::into_inner) = message_type;
+    }
+
+    pub fn stuck_on(&self) -> Option<&'static str> {
+        *self.stuck.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status {
+            stuck: Mutex::new(None),
+            dropped: AtomicU64::new(0),
+            state: watch::channel(ActorState::Starting).0,
+            journal: None,
+        }
+    }
+}
+
+/// Publishes [ActorState::Stopped] once dropped along with the actor task,
+/// whether it finished or was aborted
+pub(crate) struct StoppedGuard(pub std::sync::Arc<Status>);
+
+impl Drop for StoppedGuard {
+    fn drop(&mut self) {
+        self.0.set_state(ActorState::Stopped);
+    }
+}
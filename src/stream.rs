@@ -0,0 +1,205 @@
+use std::{
+    future::Future,
+    ops::DerefMut,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{handler::Envelope, Actor, Context, Forward, Message, Sink};
+
+/// An asynchronous sequence of values.
+///
+/// Mirrors `futures_core::Stream` so adapting any third-party stream to it
+/// is a one-line `poll_next` forwarding impl. Tokio channel receivers
+/// implement it out of the box.
+pub trait Stream {
+    type Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>>;
+}
+
+impl<T> Stream for mpsc::Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> Stream for mpsc::UnboundedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<S: Stream + Unpin + ?Sized> Stream for &mut S {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        Pin::new(&mut **self.get_mut()).poll_next(cx)
+    }
+}
+
+impl<S: Stream + Unpin + ?Sized> Stream for Box<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<S::Item>> {
+        Pin::new(&mut **self.get_mut()).poll_next(cx)
+    }
+}
+
+impl<P> Stream for Pin<P>
+where
+    P: DerefMut + Unpin,
+    P::Target: Stream,
+{
+    type Item = <P::Target as Stream>::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().as_mut().poll_next(cx)
+    }
+}
+
+/// Message registering a subscriber, sent by
+/// [Address::subscribe](crate::Address::subscribe).
+///
+/// The handler keeps `events` in the actor state and pushes events through
+/// it as they occur. Up to [Subscription::CAPACITY] events are buffered per
+/// subscriber. The stream of the subscriber ends once the actor drops the
+/// sender, and sends fail once the subscriber drops the stream.
+pub struct Subscription<M, E> {
+    pub message: M,
+    pub events: mpsc::Sender<E>,
+}
+
+impl<M, E> Subscription<M, E> {
+    pub const CAPACITY: usize = 16;
+}
+
+impl<M: Send, E: Send> Message for Subscription<M, E> {
+    type Result = ();
+}
+
+/// Stream of items sent by a handler through an `mpsc` channel, returned
+/// by [Address::send_stream](crate::Address::send_stream)
+pub struct ReceiverStream<T> {
+    rx: mpsc::Receiver<T>,
+}
+
+impl<T> ReceiverStream<T> {
+    pub fn new(rx: mpsc::Receiver<T>) -> ReceiverStream<T> {
+        ReceiverStream { rx }
+    }
+
+    /// Stops the producer from sending more items, the ones already sent
+    /// are still yielded
+    pub fn close(&mut self) {
+        self.rx.close()
+    }
+
+    pub fn into_inner(self) -> mpsc::Receiver<T> {
+        self.rx
+    }
+}
+
+impl<T> Stream for ReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+/// Convenience methods for [Stream]
+pub trait StreamExt: Stream {
+    /// Resolves to the next item of the stream or `None` once it is exhausted
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Next { stream: self }
+    }
+
+    /// Sends every item of the stream into the sink, closes the sink once
+    /// the stream is exhausted
+    fn forward<Si>(self, sink: Si) -> Forward<Self, Si>
+    where
+        Self: Sized + Unpin,
+        Si: Sink<Self::Item> + Unpin,
+    {
+        Forward::new(self, sink)
+    }
+}
+
+impl<S: Stream + ?Sized> StreamExt for S {}
+
+/// Future returned by [StreamExt::next]
+pub struct Next<'a, S: ?Sized> {
+    stream: &'a mut S,
+}
+
+impl<S: Stream + Unpin + ?Sized> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.get_mut().stream).poll_next(cx)
+    }
+}
+
+/// Handles items of a stream attached with [Context::add_stream].
+#[async_trait]
+pub trait StreamHandler<I: Send + 'static>
+where
+    Self: Actor,
+{
+    async fn handle(&mut self, item: I, context: &mut Context<Self>);
+
+    /// Hook that runs once the attached stream is exhausted
+    async fn finished(&mut self, _context: &mut Context<Self>) {}
+
+    /// Hook that runs when a stream attached with
+    /// [Context::add_broadcast_stream] fell behind the channel and `skipped`
+    /// items were lost
+    async fn lagged(&mut self, _skipped: u64, _context: &mut Context<Self>) {}
+}
+
+pub(crate) struct StreamItem<I>(pub I);
+
+pub(crate) struct StreamFinished<I>(pub std::marker::PhantomData<fn() -> I>);
+
+pub(crate) struct StreamLagged<I>(pub u64, pub std::marker::PhantomData<fn() -> I>);
+
+#[async_trait]
+impl<I: Send + 'static, A: Actor> Envelope<A> for StreamItem<I>
+where
+    A: StreamHandler<I>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        StreamHandler::handle(actor, self.0, context).await;
+    }
+}
+
+#[async_trait]
+impl<I: Send + 'static, A: Actor> Envelope<A> for StreamFinished<I>
+where
+    A: StreamHandler<I>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        actor.finished(context).await;
+    }
+}
+
+#[async_trait]
+impl<I: Send + 'static, A: Actor> Envelope<A> for StreamLagged<I>
+where
+    A: StreamHandler<I>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        actor.lagged(self.0, context).await;
+    }
+}
@@ -0,0 +1,79 @@
+use std::{any::Any, ops::Deref, sync::Arc};
+
+/// Shared application state available to every actor run in it.
+///
+/// Actors run with [Actor::run_in_system](crate::Actor::run_in_system) get
+/// it back with [Context::system](crate::Context::system), and so do their
+/// children spawned with [Context::spawn_linked](crate::Context::spawn_linked)
+/// or [Context::spawn_guarded](crate::Context::spawn_guarded). The state is
+/// immutable, use atomics or locks inside it for anything that changes.
+///
+/// ```
+/// # use akt::{Actor, Context, Handler, Message, System};
+/// # use async_trait::async_trait;
+/// struct Config {
+///     greeting: String,
+/// }
+///
+/// struct Greeter;
+///
+/// impl Actor for Greeter {}
+///
+/// struct Greet;
+///
+/// impl Message for Greet {
+///     type Result = String;
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Greet> for Greeter {
+///     async fn handle(&mut self, _message: Greet, context: &mut Context<Self>) -> String {
+///         context.system::<Config>().unwrap().greeting.clone()
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let system = System::new(Config { greeting: "hello".to_string() });
+///
+/// let greeter = Greeter.run_in_system(system);
+/// assert_eq!(greeter.send(Greet).await.unwrap(), "hello");
+/// # }
+/// ```
+pub struct System<S> {
+    state: Arc<S>,
+}
+
+impl<S: Send + Sync + 'static> System<S> {
+    pub fn new(state: S) -> System<S> {
+        System {
+            state: Arc::new(state),
+        }
+    }
+
+    pub(crate) fn erase(&self) -> Arc<dyn Any + Send + Sync> {
+        self.state.clone()
+    }
+
+    pub(crate) fn restore(state: Arc<dyn Any + Send + Sync>) -> Option<System<S>> {
+        Some(System {
+            state: state.downcast().ok()?,
+        })
+    }
+}
+
+impl<S> Clone for System<S> {
+    fn clone(&self) -> Self {
+        System {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S> Deref for System<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.state
+    }
+}
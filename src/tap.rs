@@ -0,0 +1,38 @@
+use std::{
+    pin::Pin,
+    task::{self, Poll},
+    time::SystemTime,
+};
+
+use tokio::sync::mpsc;
+
+use crate::Stream;
+
+/// Description of a message handled by an actor run with
+/// [Actor::run_tapped](crate::Actor::run_tapped)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TappedMessage {
+    /// Type name of the message
+    pub message_type: &'static str,
+    /// When the actor began handling the message
+    pub at: SystemTime,
+}
+
+/// Stream returned by [Actor::run_tapped](crate::Actor::run_tapped)
+pub struct TapStream {
+    rx: mpsc::UnboundedReceiver<TappedMessage>,
+}
+
+impl TapStream {
+    pub(crate) fn new(rx: mpsc::UnboundedReceiver<TappedMessage>) -> TapStream {
+        TapStream { rx }
+    }
+}
+
+impl Stream for TapStream {
+    type Item = TappedMessage;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<TappedMessage>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
@@ -0,0 +1,254 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    actor::{dispatch, open_mailboxes, Mailboxes},
+    Actor, ActorState, Address, Context, ControlAddress, StartError,
+};
+
+/// Actor driven by hand for deterministic tests.
+///
+/// Nothing is spawned, messages sent to [TestActor::address] are queued
+/// and handled one at a time by [TestActor::step], so the actor state can
+/// be inspected in between. Use [Address::ask] to get responses of queued
+/// requests once they are handled.
+///
+/// ```
+/// # use akt::{Actor, Context, Handler, Message, TestActor};
+/// # use async_trait::async_trait;
+/// struct Counter(u32);
+///
+/// impl Actor for Counter {}
+///
+/// struct Increment;
+///
+/// impl Message for Increment {
+///     type Result = u32;
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Increment> for Counter {
+///     async fn handle(&mut self, _message: Increment, _context: &mut Context<Self>) -> u32 {
+///         self.0 += 1;
+///         self.0
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut counter = TestActor::start(Counter(0)).await.unwrap();
+///
+/// let response = counter.address().ask(Increment).await.unwrap();
+/// assert_eq!(counter.actor().0, 0);
+///
+/// assert!(counter.step().await);
+/// assert_eq!(counter.actor().0, 1);
+/// assert_eq!(response.await, Ok(1));
+/// # }
+/// ```
+pub struct TestActor<A: Actor> {
+    actor: A,
+    context: Context<A>,
+    mailboxes: Mailboxes<A>,
+    address: Address<A>,
+}
+
+impl<A: Actor> TestActor<A> {
+    /// Runs [Actor::recover] and [Actor::try_start] and returns the actor
+    /// ready to handle messages
+    pub async fn start(mut actor: A) -> Result<TestActor<A>, StartError> {
+        let (address, private_address, control_address, mailboxes) =
+            open_mailboxes(actor.mailbox_capacity(), actor.journal());
+
+        let mut context = Context::new(
+            address.downgrade(),
+            private_address,
+            ControlAddress::new(control_address),
+            ActorState::Starting,
+        );
+
+        actor.recover(&mut context).await;
+
+        actor
+            .try_start(&mut context)
+            .await
+            .map_err(StartError::Failed)?;
+
+        context.state = ActorState::Started;
+
+        Ok(TestActor {
+            actor,
+            context,
+            mailboxes,
+            address,
+        })
+    }
+
+    /// Public address of the actor
+    pub fn address(&self) -> Address<A> {
+        self.address.clone()
+    }
+
+    pub fn actor(&self) -> &A {
+        &self.actor
+    }
+
+    pub fn actor_mut(&mut self) -> &mut A {
+        &mut self.actor
+    }
+
+    pub fn context(&mut self) -> &mut Context<A> {
+        &mut self.context
+    }
+
+    /// Handles exactly one queued message in the order of mailbox
+    /// priorities, returns `false` if nothing was queued
+    pub async fn step(&mut self) -> bool {
+        match self.mailboxes.try_recv() {
+            Some(message) => {
+                self.context.source = self.mailboxes.source;
+
+                dispatch(message, &mut self.actor, &mut self.context).await;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handles queued messages, including the ones sent by handlers
+    /// meanwhile, until none are left. Returns how many were handled
+    pub async fn step_all(&mut self) -> usize {
+        let mut handled = 0;
+
+        while self.step().await {
+            handled += 1;
+        }
+
+        handled
+    }
+
+    /// Returns `true` if a handler requested the actor to stop
+    pub fn is_stopping(&self) -> bool {
+        self.context.state == ActorState::Stopping
+    }
+
+    /// Runs [Actor::on_stopped] and the finalizers registered with
+    /// [Context::on_finalize], returns the actor
+    pub async fn stop(mut self) -> A {
+        self.context.state = ActorState::Stopping;
+
+        self.actor.on_stopped(&mut self.context).await;
+        self.context.finalize().await;
+
+        self.context.stop_linked();
+
+        self.context.state = ActorState::Stopped;
+
+        self.actor
+    }
+}
+
+/// Context for calling [Handler::handle](crate::Handler::handle) directly,
+/// without an actor running.
+///
+/// Dereferences to [Context], so handlers get `&mut context`. Messages
+/// the handler sends to the actor itself are kept in the mailboxes and can
+/// be inspected with [MockContext::drain_queued].
+///
+/// ```
+/// # use akt::{Actor, Context, Handler, Message, MockContext};
+/// # use async_trait::async_trait;
+/// struct Poller;
+///
+/// impl Actor for Poller {}
+///
+/// struct Poll;
+///
+/// impl Message for Poll {
+///     type Result = bool;
+/// }
+///
+/// #[async_trait]
+/// impl Handler<Poll> for Poller {
+///     async fn handle(&mut self, _message: Poll, context: &mut Context<Self>) -> bool {
+///         context.notify(Poll).is_ok()
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut context = MockContext::new();
+///
+/// assert!(Poller.handle(Poll, &mut context).await);
+/// assert_eq!(context.drain_queued(), [std::any::type_name::<Poll>()]);
+/// # }
+/// ```
+pub struct MockContext<A: Actor> {
+    context: Context<A>,
+    mailboxes: Mailboxes<A>,
+    address: Address<A>,
+}
+
+impl<A: Actor> MockContext<A> {
+    /// Creates context of a started actor
+    pub fn new() -> MockContext<A> {
+        let (address, private_address, control_address, mailboxes) =
+            open_mailboxes(A::DEFAULT_MAILBOX_CAPACITY, None);
+
+        let context = Context::new(
+            address.downgrade(),
+            private_address,
+            ControlAddress::new(control_address),
+            ActorState::Started,
+        );
+
+        MockContext {
+            context,
+            mailboxes,
+            address,
+        }
+    }
+
+    /// Public address messages are queued through, it keeps
+    /// [Context::address] upgradable
+    pub fn address(&self) -> Address<A> {
+        self.address.clone()
+    }
+
+    /// Removes the queued messages returning their type names in the order
+    /// of mailbox priorities
+    pub fn drain_queued(&mut self) -> Vec<&'static str> {
+        let mut queued = Vec::new();
+
+        while let Some(message) = self.mailboxes.try_recv() {
+            queued.push(message.message_type());
+        }
+
+        queued
+    }
+
+    /// Returns `true` if the handler requested the actor to stop
+    pub fn is_stopping(&self) -> bool {
+        self.context.state == ActorState::Stopping
+    }
+}
+
+impl<A: Actor> Default for MockContext<A> {
+    fn default() -> Self {
+        MockContext::new()
+    }
+}
+
+impl<A: Actor> Deref for MockContext<A> {
+    type Target = Context<A>;
+
+    fn deref(&self) -> &Context<A> {
+        &self.context
+    }
+}
+
+impl<A: Actor> DerefMut for MockContext<A> {
+    fn deref_mut(&mut self) -> &mut Context<A> {
+        &mut self.context
+    }
+}
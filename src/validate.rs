@@ -0,0 +1,120 @@
+use std::{error::Error, fmt::Display};
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{
+    handler::{
+        cancel_on_disconnect, handle_request, notification_handled, Envelope, MapReply, Response,
+    },
+    Actor, ActorSendError, Context, CorrelationId, Handler, Message,
+};
+
+/// Checks a message before it reaches the handler.
+///
+/// Validation is applied to messages delivered with
+/// [Address::send_validated](crate::Address::send_validated) and
+/// [Address::notify_validated](crate::Address::notify_validated). The
+/// validation error is reported next to `M::Result` instead of being mixed
+/// into it, so any message type could be validated.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidationError {
+    reason: String,
+}
+
+impl ValidationError {
+    pub fn new<R: Into<String>>(reason: R) -> ValidationError {
+        ValidationError {
+            reason: reason.into(),
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Message validation failed: {}", self.reason)
+    }
+}
+
+impl Error for ValidationError {}
+
+pub(crate) struct ValidatedMessageWithSender<M: Message> {
+    pub message: M,
+    pub tx: oneshot::Sender<Response<Result<M::Result, ValidationError>>>,
+}
+
+pub(crate) struct ValidatedNotification<M>(pub M);
+
+#[async_trait]
+impl<M: Message + Validate, A: Actor> Envelope<A> for ValidatedMessageWithSender<M>
+where
+    A: Handler<M>,
+    M::Result: 'static,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        let ValidatedMessageWithSender { message, tx } = *self;
+
+        if let Err(err) = message.validate() {
+            let _ = tx.send(Ok(Err(err)));
+            context.failed = true;
+
+            return;
+        }
+
+        handle_request(
+            actor,
+            message,
+            Box::new(MapReply::new(tx, Ok)),
+            cancel_on_disconnect::<A, M>(),
+            context,
+        )
+        .await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.message.correlation_id()
+    }
+
+    async fn reject(self: Box<Self>) {
+        let _ = self.tx.send(Err(ActorSendError::NotReady));
+    }
+}
+
+#[async_trait]
+impl<M: Message + Validate, A: Actor> Envelope<A> for ValidatedNotification<M>
+where
+    A: Handler<M>,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, context: &mut Context<A>) {
+        // Invalid notifications are silently dropped
+        if self.0.validate().is_err() {
+            context.failed = true;
+
+            return;
+        }
+
+        let result = actor.handle(self.0, context).await;
+
+        notification_handled::<A, M>(result, actor, context).await;
+    }
+
+    fn message_type(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.0.correlation_id()
+    }
+}
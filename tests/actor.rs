@@ -1,8 +1,19 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use akt::{Actor, Context};
-use tokio::task::yield_now;
+use akt::{
+    Actor, ActorSendError, ActorSpawner, CancellationToken, ConcurrencyLimiter, ConcurrentHandler,
+    Context, Handler, InlineHandler, MailboxPolicy, Message, Migrate, PanicReason, Reconfigure,
+    SpawnLimiter, StartError, StopReason, StreamExt, UnboundedAddress, Validate, ValidationError,
+};
+use tokio::{task::yield_now, time::timeout};
 
 struct DroppingActor {
     is_stopped: &'static AtomicBool,
@@ -28,5 +39,1959 @@ async fn stops_on_no_addresses_left() {
 
     yield_now().await;
 
-    assert_eq!(IS_STOPPED_CALLED.load(Ordering::Acquire), true);
+    assert!(IS_STOPPED_CALLED.load(Ordering::Acquire));
+}
+
+struct Stoppable;
+
+impl Actor for Stoppable {}
+
+struct StopWithReply;
+
+impl Message for StopWithReply {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<StopWithReply> for Stoppable {
+    async fn handle(&mut self, _message: StopWithReply, context: &mut Context<Self>) -> u32 {
+        context.stop();
+
+        42
+    }
+}
+
+#[tokio::test]
+async fn replies_before_stopping() {
+    let address = Stoppable.run();
+
+    assert_eq!(address.send(StopWithReply).await, Ok(42));
+    assert!(address.send(StopWithReply).await.is_err());
+}
+
+struct PauseWithReply;
+
+impl Message for PauseWithReply {
+    type Result = UnboundedAddress<Stoppable>;
+}
+
+#[async_trait]
+impl Handler<PauseWithReply> for Stoppable {
+    async fn handle(
+        &mut self,
+        _message: PauseWithReply,
+        context: &mut Context<Self>,
+    ) -> UnboundedAddress<Stoppable> {
+        context.pause();
+
+        context.private_address()
+    }
+}
+
+struct ResumeStoppable;
+
+impl Message for ResumeStoppable {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<ResumeStoppable> for Stoppable {
+    async fn handle(&mut self, _message: ResumeStoppable, context: &mut Context<Self>) {
+        context.resume();
+    }
+}
+
+#[tokio::test]
+async fn replies_before_pausing() {
+    let address = Stoppable.run();
+
+    let private = address.send(PauseWithReply).await.unwrap();
+    let mut pending = address.ask(StopWithReply).await.unwrap();
+
+    assert!(timeout(Duration::from_millis(10), &mut pending)
+        .await
+        .is_err());
+
+    private.send(ResumeStoppable).await.unwrap();
+
+    assert_eq!(pending.await, Ok(42));
+}
+
+struct Reloading {
+    generation: u32,
+}
+
+#[async_trait]
+impl Actor for Reloading {
+    async fn on_start(&mut self, _context: &mut Context<Self>) {
+        // Replacements take long enough to start for a late reply to stand out
+        if self.generation > 0 {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+    }
+}
+
+struct Reload;
+
+impl Message for Reload {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Reload> for Reloading {
+    async fn handle(&mut self, _message: Reload, context: &mut Context<Self>) -> u32 {
+        context.replace(Reloading {
+            generation: self.generation + 1,
+        });
+
+        self.generation + 1
+    }
+}
+
+#[tokio::test]
+async fn replies_before_replacing() {
+    let address = Reloading { generation: 0 }.run();
+
+    let reply = timeout(Duration::from_secs(1), address.send(Reload)).await;
+
+    assert_eq!(reply, Ok(Ok(1)));
+}
+
+struct Stuck;
+
+impl Actor for Stuck {}
+
+struct Hang;
+
+impl Message for Hang {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Hang> for Stuck {
+    async fn handle(&mut self, _message: Hang, _context: &mut Context<Self>) {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[tokio::test]
+async fn aborts_stuck_actor() {
+    let (address, abort) = Stuck.run_abortable();
+
+    address.notify(Hang).await.unwrap();
+    yield_now().await;
+
+    abort.abort();
+
+    while !abort.is_finished() {
+        yield_now().await;
+    }
+
+    assert!(address.is_closed());
+}
+
+static IS_STOPPED_JOINED: AtomicBool = AtomicBool::new(false);
+
+#[tokio::test]
+async fn join_handle_resolves_after_on_stopped() {
+    let (address, handle) = DroppingActor {
+        is_stopped: &IS_STOPPED_JOINED,
+    }
+    .run_with_handle();
+
+    drop(address);
+
+    handle.await.unwrap();
+
+    assert!(IS_STOPPED_JOINED.load(Ordering::Acquire));
+}
+
+struct Child {
+    stopped: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+#[async_trait]
+impl Actor for Child {
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        if let Some(stopped) = self.stopped.take() {
+            let _ = stopped.send(());
+        }
+    }
+}
+
+struct Parent {
+    child: Option<Child>,
+    child_address: Option<akt::Address<Child>>,
+}
+
+#[async_trait]
+impl Actor for Parent {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        if let Some(child) = self.child.take() {
+            self.child_address = Some(context.spawn_linked(child));
+        }
+    }
+}
+
+#[tokio::test]
+async fn stops_linked_children_with_parent() {
+    let (stopped_tx, stopped_rx) = tokio::sync::oneshot::channel();
+
+    let parent = Parent {
+        child: Some(Child {
+            stopped: Some(stopped_tx),
+        }),
+        child_address: None,
+    }
+    .run();
+
+    drop(parent);
+
+    stopped_rx.await.unwrap();
+}
+
+struct Writer {
+    written: u32,
+    stopped: Option<tokio::sync::oneshot::Sender<u32>>,
+}
+
+#[async_trait]
+impl Actor for Writer {
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        if let Some(stopped) = self.stopped.take() {
+            let _ = stopped.send(self.written);
+        }
+    }
+}
+
+struct Write;
+
+impl Message for Write {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Write> for Writer {
+    async fn handle(&mut self, _message: Write, _context: &mut Context<Self>) {
+        self.written += 1;
+    }
+}
+
+struct StopGraceful;
+
+impl Message for StopGraceful {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<StopGraceful> for Writer {
+    async fn handle(&mut self, _message: StopGraceful, context: &mut Context<Self>) {
+        context.stop_graceful();
+    }
+}
+
+#[tokio::test]
+async fn graceful_stop_drains_mailbox() {
+    let (stopped_tx, stopped_rx) = tokio::sync::oneshot::channel();
+
+    let address = Writer {
+        written: 0,
+        stopped: Some(stopped_tx),
+    }
+    .run();
+
+    address.notify(StopGraceful).await.unwrap();
+
+    for _ in 0..3 {
+        address.notify(Write).await.unwrap();
+    }
+
+    assert_eq!(stopped_rx.await, Ok(3));
+    assert!(address.is_closed());
+}
+
+struct Guardian {
+    children: Vec<Child>,
+    guards: Vec<akt::ChildGuard<Child>>,
+}
+
+#[async_trait]
+impl Actor for Guardian {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        for child in self.children.drain(..) {
+            self.guards.push(context.spawn_guarded(child));
+        }
+    }
+}
+
+struct DropFirstGuard;
+
+impl Message for DropFirstGuard {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<DropFirstGuard> for Guardian {
+    async fn handle(&mut self, _message: DropFirstGuard, _context: &mut Context<Self>) {
+        self.guards.remove(0);
+    }
+}
+
+#[tokio::test]
+async fn stops_guarded_children() {
+    let (first_tx, first_rx) = tokio::sync::oneshot::channel();
+    let (second_tx, mut second_rx) = tokio::sync::oneshot::channel();
+
+    let guardian = Guardian {
+        children: vec![
+            Child {
+                stopped: Some(first_tx),
+            },
+            Child {
+                stopped: Some(second_tx),
+            },
+        ],
+        guards: Vec::new(),
+    }
+    .run();
+
+    guardian.send(DropFirstGuard).await.unwrap();
+    first_rx.await.unwrap();
+    assert!(second_rx.try_recv().is_err());
+
+    drop(guardian);
+    second_rx.await.unwrap();
+}
+
+struct Stubborn {
+    reasons: Vec<StopReason>,
+    stopped: Option<tokio::sync::oneshot::Sender<Vec<StopReason>>>,
+}
+
+#[async_trait]
+impl Actor for Stubborn {
+    async fn on_stopping(&mut self, reason: StopReason, _context: &mut Context<Self>) -> bool {
+        self.reasons.push(reason);
+
+        reason != StopReason::Requested
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        if let Some(stopped) = self.stopped.take() {
+            let _ = stopped.send(self.reasons.clone());
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<StopWithReply> for Stubborn {
+    async fn handle(&mut self, _message: StopWithReply, context: &mut Context<Self>) -> u32 {
+        context.stop();
+
+        1
+    }
+}
+
+#[tokio::test]
+async fn passes_stop_reason() {
+    let (stopped_tx, stopped_rx) = tokio::sync::oneshot::channel();
+
+    let address = Stubborn {
+        reasons: Vec::new(),
+        stopped: Some(stopped_tx),
+    }
+    .run();
+
+    assert_eq!(address.send(StopWithReply).await, Ok(1));
+    assert_eq!(address.send(StopWithReply).await, Ok(1));
+
+    drop(address);
+
+    assert_eq!(
+        stopped_rx.await.unwrap(),
+        vec![
+            StopReason::Requested,
+            StopReason::Requested,
+            StopReason::AllAddressesDropped
+        ]
+    );
+}
+
+#[tokio::test]
+async fn external_stop_may_be_cancelled() {
+    let (stopped_tx, stopped_rx) = tokio::sync::oneshot::channel();
+
+    let address = Stubborn {
+        reasons: Vec::new(),
+        stopped: Some(stopped_tx),
+    }
+    .run();
+
+    address.stop().await.unwrap();
+    assert_eq!(address.send(StopWithReply).await, Ok(1));
+
+    drop(address);
+
+    assert_eq!(
+        stopped_rx.await.unwrap(),
+        vec![
+            StopReason::Requested,
+            StopReason::Requested,
+            StopReason::AllAddressesDropped
+        ]
+    );
+}
+
+struct Fragile {
+    panics: Vec<String>,
+}
+
+#[async_trait]
+impl Actor for Fragile {
+    async fn on_panic(&mut self, reason: PanicReason, _context: &mut Context<Self>) {
+        self.panics.push(reason.message().unwrap_or_default().to_string());
+    }
+}
+
+struct Explode;
+
+impl Message for Explode {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Explode> for Fragile {
+    async fn handle(&mut self, _message: Explode, _context: &mut Context<Self>) {
+        panic!("boom");
+    }
+}
+
+struct Panics;
+
+impl Message for Panics {
+    type Result = Vec<String>;
+}
+
+#[async_trait]
+impl Handler<Panics> for Fragile {
+    async fn handle(&mut self, _message: Panics, _context: &mut Context<Self>) -> Vec<String> {
+        self.panics.clone()
+    }
+}
+
+#[tokio::test]
+async fn survives_handler_panic() {
+    let address = Fragile { panics: Vec::new() }.run();
+
+    assert_eq!(
+        address.send(Explode).await,
+        Err(ActorSendError::FailedToGetResponse)
+    );
+    assert_eq!(address.send(Panics).await, Ok(vec!["boom".to_string()]));
+}
+
+struct Versioned {
+    version: u32,
+    handled: u32,
+}
+
+impl Actor for Versioned {}
+
+#[async_trait]
+impl Migrate for Versioned {
+    async fn migrate(&mut self, previous: Self, _context: &mut Context<Self>) {
+        self.handled = previous.handled;
+    }
+}
+
+struct Hit;
+
+impl Message for Hit {
+    type Result = (u32, u32);
+}
+
+#[async_trait]
+impl Handler<Hit> for Versioned {
+    async fn handle(&mut self, _message: Hit, _context: &mut Context<Self>) -> (u32, u32) {
+        self.handled += 1;
+
+        (self.version, self.handled)
+    }
+}
+
+#[tokio::test]
+async fn hot_swaps_actor_keeping_state() {
+    let address = Versioned {
+        version: 1,
+        handled: 0,
+    }
+    .run();
+
+    assert_eq!(address.send(Hit).await, Ok((1, 1)));
+
+    address
+        .hot_swap(Versioned {
+            version: 2,
+            handled: 0,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(address.send(Hit).await, Ok((2, 2)));
+}
+
+struct Phase {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Phase {
+    fn log(&self, event: &str) {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", event, self.name));
+    }
+}
+
+#[async_trait]
+impl Actor for Phase {
+    async fn try_start(
+        &mut self,
+        _context: &mut Context<Self>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.log("start");
+
+        if self.name == "broken" {
+            return Err("phase is broken".into());
+        }
+
+        Ok(())
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.log("stop");
+    }
+}
+
+#[async_trait]
+impl Migrate for Phase {
+    async fn migrate(&mut self, previous: Self, _context: &mut Context<Self>) {
+        self.log(&format!("migrate {} to", previous.name));
+    }
+}
+
+#[tokio::test]
+async fn hot_swaps_actor_through_its_lifecycle() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let address = Phase {
+        name: "old",
+        log: log.clone(),
+    }
+    .run();
+
+    let swapped = address
+        .hot_swap(Phase {
+            name: "new",
+            log: log.clone(),
+        })
+        .await;
+
+    assert_eq!(swapped, Ok(()));
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["start old", "stop old", "migrate old to new", "start new"]
+    );
+}
+
+#[tokio::test]
+async fn stops_when_hot_swapped_actor_fails_to_start() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let address = Phase {
+        name: "old",
+        log: log.clone(),
+    }
+    .run();
+
+    let swapped = address
+        .hot_swap(Phase {
+            name: "broken",
+            log: log.clone(),
+        })
+        .await;
+
+    assert_eq!(swapped, Err(ActorSendError::FailedToGetResponse));
+    assert!(timeout(Duration::from_secs(1), address.stopped())
+        .await
+        .is_ok());
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "start old",
+            "stop old",
+            "migrate old to broken",
+            "start broken",
+            "stop broken"
+        ]
+    );
+}
+
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+static MAX_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+struct Limited {
+    limiter: ConcurrencyLimiter,
+}
+
+impl Actor for Limited {
+    fn limiter(&self) -> Option<ConcurrencyLimiter> {
+        Some(self.limiter.clone())
+    }
+}
+
+struct Work;
+
+impl Message for Work {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Work> for Limited {
+    async fn handle(&mut self, _message: Work, _context: &mut Context<Self>) {
+        let running = RUNNING.fetch_add(1, Ordering::AcqRel) + 1;
+        MAX_RUNNING.fetch_max(running, Ordering::AcqRel);
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        RUNNING.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[tokio::test]
+async fn limits_concurrent_handlers_across_actors() {
+    let limiter = ConcurrencyLimiter::new(2);
+
+    let addresses: Vec<_> = (0..4)
+        .map(|_| {
+            Limited {
+                limiter: limiter.clone(),
+            }
+            .run()
+        })
+        .collect();
+
+    let mut calls = tokio::task::JoinSet::new();
+
+    for address in &addresses {
+        for _ in 0..3 {
+            let address = address.clone();
+            calls.spawn(async move { address.send(Work).await });
+        }
+    }
+
+    while let Some(result) = calls.join_next().await {
+        assert_eq!(result.unwrap(), Ok(()));
+    }
+
+    assert_eq!(MAX_RUNNING.load(Ordering::Acquire), 2);
+    assert_eq!(limiter.available(), 2);
+}
+
+struct Impatient;
+
+impl Actor for Impatient {
+    fn message_timeout(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_millis(10))
+    }
+}
+
+struct Sleep(u64);
+
+impl Message for Sleep {
+    type Result = u64;
+}
+
+#[async_trait]
+impl Handler<Sleep> for Impatient {
+    async fn handle(&mut self, message: Sleep, _context: &mut Context<Self>) -> u64 {
+        tokio::time::sleep(std::time::Duration::from_millis(message.0)).await;
+
+        message.0
+    }
+}
+
+#[tokio::test]
+async fn cancels_handlers_running_too_long() {
+    let address = Impatient.run();
+
+    assert_eq!(
+        address.send(Sleep(1000)).await,
+        Err(ActorSendError::FailedToGetResponse)
+    );
+    assert_eq!(address.send(Sleep(0)).await, Ok(0));
+}
+
+struct Recorder {
+    log: Vec<&'static str>,
+}
+
+impl Actor for Recorder {}
+
+struct Record(&'static str);
+
+impl Message for Record {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Record> for Recorder {
+    async fn handle(&mut self, message: Record, _context: &mut Context<Self>) {
+        self.log.push(message.0);
+    }
+}
+
+struct Log;
+
+impl Message for Log {
+    type Result = Vec<&'static str>;
+}
+
+#[async_trait]
+impl Handler<Log> for Recorder {
+    async fn handle(&mut self, _message: Log, _context: &mut Context<Self>) -> Vec<&'static str> {
+        self.log.clone()
+    }
+}
+
+#[tokio::test]
+async fn handles_control_messages_first() {
+    let (address, control) = Recorder { log: Vec::new() }.run_with_control();
+
+    // Nothing is handled before the first await
+    address.try_notify(Record("data")).unwrap();
+    address.try_notify(Record("data")).unwrap();
+    control.notify(Record("pause")).unwrap();
+
+    assert_eq!(address.send(Log).await, Ok(vec!["pause", "data", "data"]));
+}
+
+#[test]
+fn runs_on_provided_runtime() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .build()
+        .unwrap();
+
+    // No runtime is entered here
+    let address = Recorder { log: Vec::new() }.run_on(runtime.handle().clone());
+
+    let log = runtime.block_on(async {
+        address.notify(Record("remote")).await.unwrap();
+        address.send(Log).await
+    });
+
+    assert_eq!(log, Ok(vec!["remote"]));
+}
+
+struct Hanging;
+
+impl Actor for Hanging {
+    fn watchdog_timeout(&self) -> Option<std::time::Duration> {
+        Some(std::time::Duration::from_millis(10))
+    }
+}
+
+#[async_trait]
+impl Handler<Hang> for Hanging {
+    async fn handle(&mut self, _message: Hang, _context: &mut Context<Self>) {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[tokio::test]
+async fn watchdog_reports_stuck_handlers() {
+    let address = Hanging.run();
+
+    address.notify(Hang).await.unwrap();
+    assert!(!address.is_stuck());
+    assert_eq!(address.stuck_on(), None);
+
+    tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+
+    assert!(address.is_stuck());
+    assert_eq!(address.stuck_on(), Some(std::any::type_name::<Hang>()));
+}
+
+#[tokio::test]
+async fn handles_priority_messages_ahead_of_backlog() {
+    let address = Recorder { log: Vec::new() }.run();
+
+    address.try_notify(Record("data")).unwrap();
+    address.try_notify(Record("data")).unwrap();
+
+    let (urgent, log) = tokio::join!(address.send_priority(Record("urgent")), address.send(Log));
+
+    assert_eq!(urgent, Ok(()));
+    assert_eq!(log, Ok(vec!["urgent", "data", "data"]));
+}
+
+struct SlowStarter {
+    started: &'static AtomicBool,
+}
+
+#[async_trait]
+impl Actor for SlowStarter {
+    async fn on_start(&mut self, _context: &mut Context<Self>) {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        self.started.store(true, Ordering::Release);
+    }
+}
+
+static IS_STARTED: AtomicBool = AtomicBool::new(false);
+
+#[tokio::test]
+async fn resolves_ready_once_started() {
+    let (_address, ready) = SlowStarter {
+        started: &IS_STARTED,
+    }
+    .run_ready();
+
+    assert!(!IS_STARTED.load(Ordering::Acquire));
+
+    ready.await.unwrap();
+
+    assert!(IS_STARTED.load(Ordering::Acquire));
+}
+
+struct Misconfigured;
+
+#[async_trait]
+impl Actor for Misconfigured {
+    async fn try_start(
+        &mut self,
+        _context: &mut Context<Self>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err("missing config".into())
+    }
+}
+
+#[async_trait]
+impl Handler<Hang> for Misconfigured {
+    async fn handle(&mut self, _message: Hang, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn reports_failed_start() {
+    let (address, ready) = Misconfigured.run_ready();
+
+    match ready.await {
+        Err(StartError::Failed(err)) => assert_eq!(err.to_string(), "missing config"),
+        other => panic!("unexpected startup result: {:?}", other),
+    }
+
+    assert_eq!(
+        address.send(Hang).await,
+        Err(ActorSendError::FailedToDeliver)
+    );
+}
+
+struct Token;
+
+impl Message for Token {
+    type Result = CancellationToken;
+}
+
+#[async_trait]
+impl Handler<Token> for Stoppable {
+    async fn handle(&mut self, _message: Token, context: &mut Context<Self>) -> CancellationToken {
+        context.cancellation_token()
+    }
+}
+
+#[tokio::test]
+async fn cancels_token_on_stop() {
+    let address = Stoppable.run();
+
+    let token = address.send(Token).await.unwrap();
+    assert!(!token.is_cancelled());
+
+    address.send(StopWithReply).await.unwrap();
+    token.cancelled().await;
+
+    let token = Stoppable.run().send(Token).await.unwrap();
+    token.cancelled().await;
+}
+
+struct Billing {
+    cancelled: usize,
+}
+
+#[async_trait]
+impl Actor for Billing {
+    async fn on_message_cancelled(&mut self, _context: &mut Context<Self>) {
+        self.cancelled += 1;
+    }
+}
+
+#[async_trait]
+impl Handler<Hang> for Billing {
+    async fn handle(&mut self, _message: Hang, _context: &mut Context<Self>) {
+        std::future::pending::<()>().await;
+    }
+}
+
+struct Cancelled;
+
+impl Message for Cancelled {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Cancelled> for Billing {
+    async fn handle(&mut self, _message: Cancelled, _context: &mut Context<Self>) -> usize {
+        self.cancelled
+    }
+}
+
+#[tokio::test]
+async fn reports_handlers_cancelled_by_caller() {
+    let address = Billing { cancelled: 0 }.run();
+
+    let call = tokio::time::timeout(std::time::Duration::from_millis(10), address.send(Hang));
+    assert!(call.await.is_err());
+
+    assert_eq!(address.send(Cancelled).await, Ok(1));
+}
+
+enum Light {
+    Red(Arc<Mutex<Vec<String>>>),
+    Green(Arc<Mutex<Vec<String>>>),
+    Off(Arc<Mutex<Vec<String>>>),
+}
+
+impl Light {
+    fn log(&self, event: &str) {
+        let (color, log) = match self {
+            Light::Red(log) => ("red", log),
+            Light::Green(log) => ("green", log),
+            Light::Off(log) => ("off", log),
+        };
+
+        log.lock().unwrap().push(format!("{} {}", event, color));
+    }
+}
+
+#[async_trait]
+impl Actor for Light {
+    async fn on_start(&mut self, _context: &mut Context<Self>) {
+        self.log("start");
+    }
+
+    async fn try_start(
+        &mut self,
+        context: &mut Context<Self>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Light::Off(_) = self {
+            self.log("fail");
+
+            return Err("bulb is broken".into());
+        }
+
+        self.on_start(context).await;
+
+        Ok(())
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.log("stop");
+    }
+}
+
+struct Switch;
+
+impl Message for Switch {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Switch> for Light {
+    async fn handle(&mut self, _message: Switch, context: &mut Context<Self>) {
+        context.replace(match self {
+            Light::Red(log) => Light::Green(log.clone()),
+            Light::Green(log) | Light::Off(log) => Light::Red(log.clone()),
+        });
+    }
+}
+
+struct Break;
+
+impl Message for Break {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Break> for Light {
+    async fn handle(&mut self, _message: Break, context: &mut Context<Self>) {
+        let log = match self {
+            Light::Red(log) | Light::Green(log) | Light::Off(log) => log.clone(),
+        };
+
+        context.replace(Light::Off(log));
+    }
+}
+
+#[tokio::test]
+async fn replaces_actor_between_messages() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let address = Light::Red(log.clone()).run();
+
+    address.send(Switch).await.unwrap();
+    address.send(Switch).await.unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "start red",
+            "stop red",
+            "start green",
+            "stop green",
+            "start red"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stops_when_replacement_fails_to_start() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let address = Light::Red(log.clone()).run();
+
+    address.send(Break).await.unwrap();
+    address.stopped().await;
+
+    assert!(address.send(Switch).await.is_err());
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["start red", "stop red", "fail off", "stop off"]
+    );
+}
+
+struct Backlogged {
+    depths: Vec<usize>,
+}
+
+#[async_trait]
+impl Actor for Backlogged {
+    const HIGH_WATER_MARK: Option<usize> = Some(2);
+
+    async fn on_mailbox_pressure(&mut self, depth: usize, _context: &mut Context<Self>) {
+        self.depths.push(depth);
+    }
+}
+
+struct Depths;
+
+impl Message for Depths {
+    type Result = Vec<usize>;
+}
+
+#[async_trait]
+impl Handler<Depths> for Backlogged {
+    async fn handle(&mut self, _message: Depths, _context: &mut Context<Self>) -> Vec<usize> {
+        self.depths.clone()
+    }
+}
+
+#[tokio::test]
+async fn reports_mailbox_pressure() {
+    let address = Backlogged { depths: Vec::new() }.run();
+
+    // Queued before the actor task gets to run
+    let mut responses = Vec::new();
+
+    for _ in 0..5 {
+        responses.push(address.ask(Depths).await.unwrap());
+    }
+
+    for response in responses {
+        response.await.unwrap();
+    }
+
+    assert_eq!(address.send(Depths).await, Ok(vec![4, 3]));
+}
+
+struct Flusher {
+    abandoned: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Flusher {
+    const SHUTDOWN_TIMEOUT: Option<Duration> = Some(Duration::from_millis(20));
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        // Flush that never completes
+        std::future::pending::<()>().await;
+    }
+
+    async fn on_shutdown_timeout(&mut self, hook: &'static str, _context: &mut Context<Self>) {
+        self.abandoned.lock().unwrap().push(hook);
+    }
+}
+
+#[tokio::test]
+async fn abandons_stuck_stop_hooks() {
+    let abandoned = Arc::new(Mutex::new(Vec::new()));
+    let (address, handle) = Flusher {
+        abandoned: abandoned.clone(),
+    }
+    .run_with_handle();
+
+    drop(address);
+
+    let finished = tokio::time::timeout(Duration::from_secs(1), handle).await;
+    assert!(matches!(finished, Ok(Ok(()))));
+    assert_eq!(*abandoned.lock().unwrap(), vec!["on_stopped"]);
+}
+
+struct Aggregator {
+    buffered: Vec<u32>,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Actor for Aggregator {}
+
+struct Buffer(u32);
+
+impl Message for Buffer {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Buffer> for Aggregator {
+    async fn handle(&mut self, message: Buffer, _context: &mut Context<Self>) {
+        self.buffered.push(message.0);
+    }
+}
+
+struct Flush;
+
+impl Message for Flush {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Flush> for Aggregator {
+    async fn handle(&mut self, _message: Flush, _context: &mut Context<Self>) {
+        let flushed = format!("flushed {:?}", std::mem::take(&mut self.buffered));
+
+        self.log.lock().unwrap().push(flushed);
+    }
+}
+
+struct Shutdown;
+
+impl Message for Shutdown {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Shutdown> for Aggregator {
+    async fn handle(&mut self, _message: Shutdown, context: &mut Context<Self>) {
+        context.stop_with_final_message(Flush);
+    }
+}
+
+#[tokio::test]
+async fn handles_final_message_before_stopping() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (address, handle) = Aggregator {
+        buffered: Vec::new(),
+        log: log.clone(),
+    }
+    .run_with_handle();
+
+    address.notify(Buffer(1)).await.unwrap();
+    address.notify(Shutdown).await.unwrap();
+    address.notify(Buffer(2)).await.unwrap();
+
+    handle.await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), ["flushed [1]"]);
+}
+
+#[tokio::test]
+async fn handles_public_messages_before_final_one_if_public_first() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (address, handle) = Aggregator {
+        buffered: Vec::new(),
+        log: log.clone(),
+    }
+    .builder()
+    .mailbox_policy(MailboxPolicy::PublicFirst)
+    .run_with_handle();
+
+    address.notify(Buffer(1)).await.unwrap();
+    address.notify(Shutdown).await.unwrap();
+    address.notify(Buffer(2)).await.unwrap();
+
+    handle.await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), ["flushed [1, 2]"]);
+}
+
+struct BrokenFlush;
+
+impl Message for BrokenFlush {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<BrokenFlush> for Aggregator {
+    async fn handle(&mut self, _message: BrokenFlush, _context: &mut Context<Self>) {
+        panic!("flush failed");
+    }
+}
+
+struct Abandon;
+
+impl Message for Abandon {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Abandon> for Aggregator {
+    async fn handle(&mut self, _message: Abandon, context: &mut Context<Self>) {
+        context.stop_with_final_message(BrokenFlush);
+    }
+}
+
+#[tokio::test]
+async fn stops_even_if_final_message_panics() {
+    let (address, handle) = Aggregator {
+        buffered: Vec::new(),
+        log: Arc::default(),
+    }
+    .run_with_handle();
+
+    address.notify(Abandon).await.unwrap();
+
+    let stopped = timeout(Duration::from_secs(1), handle).await;
+    assert!(matches!(stopped, Ok(Ok(()))));
+}
+
+struct Courteous {
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Actor for Courteous {
+    const MAILBOX_POLICY: MailboxPolicy = MailboxPolicy::PublicFirst;
+}
+
+struct Hold(tokio::sync::oneshot::Receiver<()>);
+
+impl Message for Hold {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Hold> for Courteous {
+    async fn handle(&mut self, message: Hold, context: &mut Context<Self>) {
+        context.notify(Entry("private")).unwrap();
+
+        let _ = message.0.await;
+    }
+}
+
+struct Entry(&'static str);
+
+impl Message for Entry {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Entry> for Courteous {
+    async fn handle(&mut self, message: Entry, _context: &mut Context<Self>) {
+        self.log.lock().unwrap().push(message.0);
+    }
+}
+
+#[tokio::test]
+async fn prefers_public_mailbox() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let address = Courteous { log: log.clone() }.run();
+
+    let (release, hold) = tokio::sync::oneshot::channel();
+    address.notify(Hold(hold)).await.unwrap();
+    address.notify(Entry("public")).await.unwrap();
+    yield_now().await;
+
+    release.send(()).unwrap();
+    drop(address);
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(*log.lock().unwrap(), vec!["public", "private"]);
+}
+
+#[tokio::test]
+async fn taps_handled_messages() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (address, mut tap) = Courteous { log }.run_tapped();
+
+    address.notify(Entry("first")).await.unwrap();
+    address.notify(Entry("second")).await.unwrap();
+    drop(address);
+
+    let mut tapped = Vec::new();
+
+    while let Some(message) = tap.next().await {
+        tapped.push(message.message_type);
+    }
+
+    assert_eq!(tapped, vec![std::any::type_name::<Entry>(); 2]);
+}
+
+#[tokio::test]
+async fn combines_options_of_builder() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let spawned = Courteous { log: log.clone() }
+        .builder()
+        .mailbox_policy(MailboxPolicy::PrivateFirst)
+        .tapped()
+        .spawn();
+
+    spawned.ready.await.unwrap();
+
+    let (release, hold) = tokio::sync::oneshot::channel();
+    spawned.address.notify(Hold(hold)).await.unwrap();
+    spawned.address.notify(Entry("public")).await.unwrap();
+    yield_now().await;
+
+    release.send(()).unwrap();
+    drop(spawned.address);
+    spawned.handle.await.unwrap();
+
+    let mut tap = spawned.tap.unwrap();
+    let mut tapped = Vec::new();
+
+    while let Some(message) = tap.next().await {
+        tapped.push(message.message_type);
+    }
+
+    assert_eq!(*log.lock().unwrap(), vec!["private", "public"]);
+    assert_eq!(tapped.len(), 3);
+}
+
+struct Ledger {
+    entries: Arc<AtomicUsize>,
+}
+
+impl Actor for Ledger {}
+
+struct Append;
+
+impl Message for Append {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Append> for Ledger {
+    async fn handle(&mut self, _message: Append, _context: &mut Context<Self>) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        self.entries.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+struct Commit;
+
+impl Message for Commit {
+    type Result = ();
+
+    const CANCEL_ON_DISCONNECT: bool = false;
+}
+
+#[async_trait]
+impl Handler<Commit> for Ledger {
+    async fn handle(&mut self, _message: Commit, _context: &mut Context<Self>) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        self.entries.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[tokio::test]
+async fn completes_handlers_of_guaranteed_messages() {
+    let entries = Arc::new(AtomicUsize::new(0));
+    let address = Ledger {
+        entries: entries.clone(),
+    }
+    .run();
+
+    let wait = Duration::from_millis(5);
+
+    assert!(timeout(wait, address.send(Append)).await.is_err());
+    assert!(timeout(wait, address.send(Commit)).await.is_err());
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+
+    assert_eq!(entries.load(Ordering::Acquire), 1);
+}
+
+#[tokio::test]
+async fn bounds_actors_spawned_through_limiter() {
+    let limiter = SpawnLimiter::new(1);
+    let spawner = ActorSpawner::from(|| Ledger {
+        entries: Arc::new(AtomicUsize::new(0)),
+    });
+
+    let first = spawner.spawn_run_limited(&limiter).await;
+    assert_eq!(limiter.available(), 0);
+
+    let second = spawner.spawn_run_limited(&limiter);
+    tokio::pin!(second);
+    let waiting = timeout(Duration::from_millis(10), &mut second).await;
+    assert!(waiting.is_err());
+
+    drop(first);
+
+    let second = timeout(Duration::from_millis(100), second).await.unwrap();
+    assert!(second.is_connected());
+}
+
+#[tokio::test]
+async fn sets_up_actors_spawned_by_spawner() {
+    let registered = Arc::new(Mutex::new(Vec::new()));
+    let registry = registered.clone();
+
+    let spawner = ActorSpawner::from_with_setup(
+        || Ledger {
+            entries: Arc::new(AtomicUsize::new(0)),
+        },
+        move |address| registry.lock().unwrap().push(address.clone()),
+    );
+
+    let _first = spawner.spawn_run();
+    let _second = spawner.spawn_run_limited(&SpawnLimiter::new(1)).await;
+
+    let registered = registered.lock().unwrap();
+    assert_eq!(registered.len(), 2);
+    assert!(registered.iter().all(|address| address.is_connected()));
+}
+
+struct Importer {
+    errors: Vec<String>,
+}
+
+#[async_trait]
+impl Actor for Importer {
+    async fn on_notification_error(
+        &mut self,
+        error: Box<dyn std::error::Error + Send>,
+        _context: &mut Context<Self>,
+    ) {
+        self.errors.push(error.to_string());
+    }
+}
+
+struct Import(&'static str);
+
+impl Message for Import {
+    type Result = Result<u32, std::num::ParseIntError>;
+
+    fn notification_error(result: Self::Result) -> Option<Box<dyn std::error::Error + Send>> {
+        result.err().map(|err| Box::new(err) as _)
+    }
+}
+
+#[async_trait]
+impl Handler<Import> for Importer {
+    async fn handle(
+        &mut self,
+        message: Import,
+        _context: &mut Context<Self>,
+    ) -> Result<u32, std::num::ParseIntError> {
+        message.0.parse()
+    }
+}
+
+struct Errors;
+
+impl Message for Errors {
+    type Result = Vec<String>;
+}
+
+#[async_trait]
+impl Handler<Errors> for Importer {
+    async fn handle(&mut self, _message: Errors, _context: &mut Context<Self>) -> Vec<String> {
+        self.errors.clone()
+    }
+}
+
+#[tokio::test]
+async fn reports_errors_of_notifications() {
+    let address = Importer { errors: Vec::new() }.run();
+
+    address.notify(Import("1")).await.unwrap();
+    address.notify(Import("x")).await.unwrap();
+    assert!(address.send(Import("y")).await.unwrap().is_err());
+
+    let errors = address.send(Errors).await.unwrap();
+    assert_eq!(errors, vec!["invalid digit found in string".to_string()]);
+}
+
+impl Validate for Import {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+impl InlineHandler<Import> for Importer {
+    fn handle(
+        &mut self,
+        message: Import,
+        _context: &mut Context<Self>,
+    ) -> Result<u32, std::num::ParseIntError> {
+        message.0.parse()
+    }
+}
+
+#[async_trait]
+impl ConcurrentHandler<Import> for Importer {
+    async fn handle(&self, message: Import) -> Result<u32, std::num::ParseIntError> {
+        message.0.parse()
+    }
+}
+
+#[tokio::test]
+async fn reports_errors_of_every_kind_of_notification() {
+    let address = Importer { errors: Vec::new() }.run();
+
+    address.notify_validated(Import("a")).await.unwrap();
+    address.notify_inline(Import("b")).await.unwrap();
+    address.notify_concurrent(Import("c")).await.unwrap();
+    address.notify_inline(Import("2")).await.unwrap();
+
+    let errors = address.send(Errors).await.unwrap();
+    assert_eq!(errors.len(), 3);
+}
+
+struct Narrow;
+
+impl Actor for Narrow {
+    fn mailbox_capacity(&self) -> usize {
+        1
+    }
+}
+
+struct Nudge;
+
+impl Message for Nudge {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Nudge> for Narrow {
+    async fn handle(&mut self, _message: Nudge, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn runs_with_actor_defined_mailbox_capacity() {
+    let address = Narrow.run();
+
+    address.try_notify(Nudge).unwrap();
+    assert!(address.try_notify(Nudge).is_err());
+
+    assert!((16..=1024).contains(&akt::default_mailbox_capacity()));
+}
+
+#[tokio::test]
+async fn runs_with_options_of_builder() {
+    let (address, handle) = Narrow
+        .builder()
+        .mailbox_capacity(2)
+        .on(tokio::runtime::Handle::current())
+        .run_with_handle();
+
+    address.try_notify(Nudge).unwrap();
+    address.try_notify(Nudge).unwrap();
+    assert!(address.try_notify(Nudge).is_err());
+
+    drop(address);
+    handle.await.unwrap();
+}
+
+struct Warehouse {
+    stock: u32,
+}
+
+#[async_trait]
+impl Actor for Warehouse {
+    const REJECT_UNTIL_STARTED: bool = true;
+
+    async fn on_start(&mut self, _context: &mut Context<Self>) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        self.stock = 5;
+    }
+}
+
+struct Stock;
+
+impl Message for Stock {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Stock> for Warehouse {
+    async fn handle(&mut self, _message: Stock, _context: &mut Context<Self>) -> u32 {
+        self.stock
+    }
+}
+
+#[tokio::test]
+async fn rejects_messages_until_started() {
+    let address = Warehouse { stock: 0 }.run();
+
+    assert_eq!(address.send(Stock).await, Err(ActorSendError::NotReady));
+
+    address.wait_for_state(akt::ActorState::Started).await;
+
+    assert_eq!(address.send(Stock).await, Ok(5));
+}
+
+impl Validate for Stock {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+impl InlineHandler<Stock> for Warehouse {
+    fn handle(&mut self, _message: Stock, _context: &mut Context<Self>) -> u32 {
+        self.stock
+    }
+}
+
+struct Spill;
+
+impl Message for Spill {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Spill> for Warehouse {
+    async fn handle(&mut self, _message: Spill, _context: &mut Context<Self>) {
+        panic!("spilled");
+    }
+}
+
+#[tokio::test]
+async fn rejects_requests_of_every_kind_until_started() {
+    let address = Warehouse { stock: 0 }.run();
+    let blocking = address.clone();
+
+    let (asked, validated, inline, blocked) = tokio::join!(
+        async { address.ask(Stock).await.unwrap().await },
+        address.send_validated(Stock),
+        address.send_inline(Stock),
+        tokio::task::spawn_blocking(move || blocking.blocking_send(Stock)),
+    );
+
+    assert_eq!(asked, Err(ActorSendError::NotReady));
+    assert_eq!(validated, Err(ActorSendError::NotReady));
+    assert_eq!(inline, Err(ActorSendError::NotReady));
+    assert_eq!(blocked.unwrap(), Err(ActorSendError::NotReady));
+    assert_eq!(address.send_inline(Stock).await, Ok(5));
+}
+
+#[tokio::test]
+async fn reports_panics_after_started_as_usual() {
+    let address = Warehouse { stock: 0 }.run();
+
+    address.wait_for_state(akt::ActorState::Started).await;
+
+    assert_eq!(
+        address.send(Spill).await,
+        Err(ActorSendError::FailedToGetResponse)
+    );
+}
+
+#[tokio::test]
+async fn keeps_control_messages_sent_before_started() {
+    let address = Warehouse { stock: 0 }.run();
+
+    let (stock, flushed, stopped) =
+        tokio::join!(address.send(Stock), address.flush(), address.stop());
+
+    assert_eq!(stock, Err(ActorSendError::NotReady));
+    assert_eq!(flushed, Ok(()));
+    assert_eq!(stopped, Ok(()));
+    assert!(timeout(Duration::from_secs(1), address.stopped())
+        .await
+        .is_ok());
+}
+
+#[async_trait]
+impl Reconfigure<&'static str> for Recorder {
+    async fn reconfigure(&mut self, config: &'static str, _context: &mut Context<Self>) {
+        self.log.push(config);
+    }
+}
+
+#[tokio::test]
+async fn reconfigures_ahead_of_queued_messages() {
+    let (address, control) = Recorder { log: Vec::new() }.run_with_control();
+
+    address.try_notify(Record("data")).unwrap();
+    address.try_notify(Record("data")).unwrap();
+    control.reconfigure("config").await.unwrap();
+
+    assert_eq!(address.send(Log).await, Ok(vec!["config", "data", "data"]));
+}
+
+#[derive(Debug, PartialEq)]
+struct Tagged {
+    request: u64,
+    body: &'static str,
+}
+
+struct Api {
+    requests: u64,
+}
+
+impl Actor for Api {
+    fn map_response(&mut self, response: &mut dyn std::any::Any, _context: &mut Context<Self>) {
+        if let Some(tagged) = response.downcast_mut::<Tagged>() {
+            self.requests += 1;
+            tagged.request = self.requests;
+        }
+    }
+}
+
+struct Greet;
+
+impl Message for Greet {
+    type Result = Tagged;
+}
+
+#[async_trait]
+impl Handler<Greet> for Api {
+    async fn handle(&mut self, _message: Greet, _context: &mut Context<Self>) -> Tagged {
+        Tagged {
+            request: 0,
+            body: "hello",
+        }
+    }
+}
+
+struct Version;
+
+impl Message for Version {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Version> for Api {
+    async fn handle(&mut self, _message: Version, _context: &mut Context<Self>) -> u32 {
+        3
+    }
+}
+
+#[tokio::test]
+async fn maps_responses_before_sending_them() {
+    let address = Api { requests: 0 }.run();
+
+    assert_eq!(
+        address.send(Greet).await,
+        Ok(Tagged {
+            request: 1,
+            body: "hello"
+        })
+    );
+    assert_eq!(address.send(Version).await, Ok(3));
+    assert_eq!(
+        address.send(Greet).await.map(|tagged| tagged.request),
+        Ok(2)
+    );
+}
+
+impl Validate for Greet {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn maps_responses_of_validated_messages() {
+    let address = Api { requests: 0 }.run();
+
+    assert_eq!(
+        address.send_validated(Greet).await,
+        Ok(Ok(Tagged {
+            request: 1,
+            body: "hello"
+        }))
+    );
+}
+
+struct Autoscaled {
+    events: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Autoscaled {
+    async fn on_idle(&mut self, _context: &mut Context<Self>) {
+        self.events.lock().unwrap().push("idle");
+    }
+
+    async fn on_busy(&mut self, _context: &mut Context<Self>) {
+        self.events.lock().unwrap().push("busy");
+    }
+}
+
+struct Job;
+
+impl Message for Job {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Job> for Autoscaled {
+    async fn handle(&mut self, _message: Job, _context: &mut Context<Self>) {
+        self.events.lock().unwrap().push("job");
+    }
+}
+
+#[tokio::test]
+async fn signals_mailbox_turning_idle_and_busy() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let address = Autoscaled {
+        events: events.clone(),
+    }
+    .run();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    for _ in 0..3 {
+        address.notify(Job).await.unwrap();
+    }
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        ["idle", "busy", "job", "job", "job", "idle"]
+    );
+}
+
+struct Transfers {
+    debited: u32,
+    credited: u32,
+    settled: u32,
+}
+
+impl Actor for Transfers {}
+
+struct Transfer;
+
+impl Message for Transfer {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Transfer> for Transfers {
+    async fn handle(&mut self, _message: Transfer, context: &mut Context<Self>) {
+        context
+            .critical_section(async {
+                self.debited += 1;
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                self.credited += 1;
+            })
+            .await;
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        self.settled += 1;
+    }
+}
+
+struct Totals;
+
+impl Message for Totals {
+    type Result = (u32, u32, u32);
+}
+
+#[async_trait]
+impl Handler<Totals> for Transfers {
+    async fn handle(&mut self, _message: Totals, _context: &mut Context<Self>) -> (u32, u32, u32) {
+        (self.debited, self.credited, self.settled)
+    }
+}
+
+#[tokio::test]
+async fn completes_critical_sections_despite_disconnect() {
+    let address = Transfers {
+        debited: 0,
+        credited: 0,
+        settled: 0,
+    }
+    .run();
+
+    let waiting = timeout(Duration::from_millis(10), address.send(Transfer)).await;
+    assert!(waiting.is_err());
+
+    tokio::time::sleep(Duration::from_millis(80)).await;
+
+    // The section is completed, the rest of the handler is cancelled
+    assert_eq!(address.send(Totals).await, Ok((1, 1, 0)));
+}
+
+impl Validate for Transfer {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn completes_critical_sections_of_validated_messages_despite_disconnect() {
+    let address = Transfers {
+        debited: 0,
+        credited: 0,
+        settled: 0,
+    }
+    .run();
+
+    let waiting = timeout(Duration::from_millis(10), address.send_validated(Transfer)).await;
+    assert!(waiting.is_err());
+
+    tokio::time::sleep(Duration::from_millis(80)).await;
+
+    assert_eq!(address.send(Totals).await, Ok((1, 1, 0)));
+}
+
+struct Heartbeat {
+    beats: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Actor for Heartbeat {
+    const INTERVAL: Option<Duration> = Some(Duration::from_millis(20));
+
+    async fn on_interval(&mut self, _context: &mut Context<Self>) {
+        self.beats.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+#[tokio::test]
+async fn calls_interval_hook_periodically() {
+    let beats = Arc::new(AtomicUsize::new(0));
+    let _address = Heartbeat {
+        beats: beats.clone(),
+    }
+    .run();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(beats.load(Ordering::Acquire), 0);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!((2..=4).contains(&beats.load(Ordering::Acquire)));
 }
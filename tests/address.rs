@@ -0,0 +1,846 @@
+use akt::{
+    send_all, send_all_tracked, Actor, ActorSendError, ActorState, Address, Context, Deferred,
+    FailedToDeliver, FirstTick, Handler, Message, Recipient, ResponseSet, SendUnpackError,
+    StreamExt, Subscription, Validate, ValidationError,
+};
+use async_trait::async_trait;
+use std::time::Duration;
+
+struct Counter {
+    value: u32,
+}
+
+impl Actor for Counter {}
+
+struct Next;
+
+impl Message for Next {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Next> for Counter {
+    async fn handle(&mut self, _message: Next, _context: &mut Context<Self>) -> u32 {
+        self.value += 1;
+
+        self.value
+    }
+}
+
+#[tokio::test]
+async fn poll_stream_yields_responses() {
+    let address = Counter { value: 0 }.run();
+
+    let mut stream = address.poll_stream(|| Next);
+
+    assert_eq!(stream.next().await, Some(1));
+    assert_eq!(stream.next().await, Some(2));
+    assert_eq!(stream.next().await, Some(3));
+}
+
+struct Increment;
+
+impl Message for Increment {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Increment> for Counter {
+    async fn handle(&mut self, _message: Increment, _context: &mut Context<Self>) {
+        self.value += 1;
+    }
+}
+
+#[tokio::test]
+async fn address_notifies() {
+    let address = Counter { value: 0 }.run();
+
+    address.notify(Increment).await.unwrap();
+    address.try_notify(Increment).unwrap();
+    address.do_send(Increment).await.unwrap();
+
+    assert_eq!(address.send(Next).await, Ok(4));
+}
+
+#[tokio::test]
+async fn sends_through_reserved_permit() {
+    let address = Counter { value: 0 }.run_with_capacity(1);
+
+    let permit = address.ready().await.unwrap();
+    permit.notify(Increment);
+
+    let permit = address.ready().await.unwrap();
+    assert_eq!(permit.send(Next).await, Ok(2));
+}
+
+#[tokio::test]
+#[should_panic(expected = "mailbox capacity must be greater than zero")]
+async fn rejects_zero_mailbox_capacity() {
+    let _address = Counter { value: 0 }.run_with_capacity(0);
+}
+
+struct Add {
+    amount: u32,
+}
+
+impl Message for Add {
+    type Result = u32;
+}
+
+impl Validate for Add {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.amount == 0 {
+            return Err(ValidationError::new("amount should be positive"));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Add> for Counter {
+    async fn handle(&mut self, message: Add, _context: &mut Context<Self>) -> u32 {
+        self.value += message.amount;
+
+        self.value
+    }
+}
+
+#[tokio::test]
+async fn rejects_invalid_messages() {
+    let address = Counter { value: 0 }.run();
+
+    assert_eq!(
+        address.send_validated(Add { amount: 0 }).await,
+        Ok(Err(ValidationError::new("amount should be positive")))
+    );
+
+    address.notify_validated(Add { amount: 0 }).await.unwrap();
+
+    assert_eq!(address.send_validated(Add { amount: 2 }).await, Ok(Ok(2)));
+}
+
+struct Wait(tokio::sync::oneshot::Receiver<u32>);
+
+impl Message for Wait {
+    type Result = Deferred<u32>;
+}
+
+#[async_trait]
+impl Handler<Wait> for Counter {
+    async fn handle(&mut self, message: Wait, _context: &mut Context<Self>) -> Deferred<u32> {
+        Deferred::new(async move { message.0.await.unwrap_or_default() })
+    }
+}
+
+struct Release(tokio::sync::oneshot::Sender<u32>);
+
+impl Message for Release {
+    type Result = Deferred<()>;
+}
+
+#[async_trait]
+impl Handler<Release> for Counter {
+    async fn handle(&mut self, message: Release, _context: &mut Context<Self>) -> Deferred<()> {
+        Deferred::new(async move {
+            let _ = message.0.send(7);
+        })
+    }
+}
+
+#[tokio::test]
+async fn deferred_results_do_not_block_loop() {
+    let address = Counter { value: 0 }.run();
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let (waited, released) = tokio::join!(
+        address.send_unpack(Wait(rx)),
+        address.send_unpack(Release(tx))
+    );
+
+    assert_eq!(waited, Ok(7));
+    assert_eq!(released, Ok(()));
+}
+
+struct Reserve {
+    amount: u32,
+}
+
+impl Message for Reserve {
+    type Result = Result<tokio::sync::oneshot::Receiver<Result<u32, String>>, String>;
+}
+
+#[async_trait]
+impl Handler<Reserve> for Counter {
+    async fn handle(
+        &mut self,
+        message: Reserve,
+        _context: &mut Context<Self>,
+    ) -> Result<tokio::sync::oneshot::Receiver<Result<u32, String>>, String> {
+        if message.amount > self.value {
+            return Err("not enough".to_string());
+        }
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = tx.send(Ok(self.value - message.amount));
+
+        Ok(rx)
+    }
+}
+
+#[tokio::test]
+async fn flattens_unpacked_errors() {
+    let address = Counter { value: 5 }.run();
+
+    assert_eq!(address.send_unpack_flat(Reserve { amount: 2 }).await, Ok(3));
+    assert_eq!(
+        address.send_unpack_flat(Reserve { amount: 9 }).await,
+        Err(SendUnpackError::Domain("not enough".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn reports_mailbox_length() {
+    let address = Counter { value: 0 }.run_with_capacity(4);
+
+    // The actor task has no chance to run before the first await
+    for _ in 0..3 {
+        address.try_notify(Increment).unwrap();
+    }
+
+    assert_eq!(address.len(), 3);
+    assert_eq!(address.capacity(), 1);
+    assert_eq!(address.max_capacity(), 4);
+
+    address.send(Next).await.unwrap();
+
+    assert!(address.is_empty());
+}
+
+#[tokio::test]
+async fn counts_strong_and_weak_addresses() {
+    let address = Counter { value: 0 }.run();
+
+    // Make sure the actor has started and holds its own weak address
+    address.send(Next).await.unwrap();
+
+    let clone = address.clone();
+    let weak = address.downgrade();
+    let weak_clone = weak.clone();
+
+    assert_eq!(address.strong_count(), 2);
+    assert_eq!(address.weak_count(), 3);
+    assert_eq!(weak.strong_count(), 2);
+
+    drop(clone);
+    drop(weak_clone);
+
+    assert_eq!(weak.strong_count(), 1);
+    assert_eq!(weak.weak_count(), 2);
+}
+
+#[tokio::test]
+async fn cancels_scheduled_notifications() {
+    let address = Counter { value: 0 }.run();
+
+    let cancelled = address.notify_later(Increment, Duration::from_millis(10));
+    let delivered = address.notify_later(Increment, Duration::from_millis(10));
+
+    cancelled.abort();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert!(cancelled.is_finished());
+    assert!(delivered.is_finished());
+    assert_eq!(address.send(Next).await, Ok(2));
+}
+
+struct Search(u32);
+
+impl Message for Search {
+    type Result = tokio::sync::mpsc::Receiver<u32>;
+}
+
+#[async_trait]
+impl Handler<Search> for Counter {
+    async fn handle(
+        &mut self,
+        message: Search,
+        _context: &mut Context<Self>,
+    ) -> tokio::sync::mpsc::Receiver<u32> {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        tokio::spawn(async move {
+            for i in 0..message.0 {
+                if tx.send(i).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        rx
+    }
+}
+
+#[tokio::test]
+async fn streams_responses() {
+    let address = Counter { value: 0 }.run();
+
+    let mut results = address.send_stream(Search(3)).await.unwrap();
+
+    // The loop is free while the results are still being produced
+    assert_eq!(address.send(Next).await, Ok(1));
+
+    let mut received = Vec::new();
+    while let Some(result) = results.next().await {
+        received.push(result);
+    }
+
+    assert_eq!(received, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+// Only the identity of the actor is hashed
+#[allow(clippy::mutable_key_type)]
+async fn compares_addresses_by_actor() {
+    let first = Counter { value: 0 }.run();
+    let second = Counter { value: 0 }.run();
+
+    let subscribers: std::collections::HashSet<_> =
+        vec![first.clone(), second.clone(), first.clone()]
+            .into_iter()
+            .collect();
+
+    assert_eq!(first, first.clone());
+    assert_ne!(first, second);
+    assert_eq!(subscribers.len(), 2);
+    assert!(format!("{:?}", first).contains("Counter"));
+}
+
+#[tokio::test]
+async fn separates_delivery_from_waiting() {
+    let address = Counter { value: 0 }.run();
+
+    let first = address.ask(Next).await.unwrap();
+    let second = address.ask(Next).await.unwrap();
+
+    assert_eq!(second.await, Ok(2));
+    assert_eq!(first.await, Ok(1));
+}
+
+#[derive(Clone)]
+struct Peek;
+
+impl Message for Peek {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Peek> for Counter {
+    async fn handle(&mut self, _message: Peek, _context: &mut Context<Self>) -> u32 {
+        self.value
+    }
+}
+
+#[tokio::test]
+async fn sends_to_all_addresses() {
+    let addresses = vec![Counter { value: 1 }.run(), Counter { value: 2 }.run()];
+    assert_eq!(send_all(&addresses, Peek).await, [Ok(1), Ok(2)]);
+
+    let (gone, handle) = Counter { value: 3 }.run_with_handle();
+    handle.abort();
+    let _ = handle.await;
+
+    let recipients: Vec<Recipient<Peek>> = vec![addresses[0].clone().into(), gone.into()];
+    assert_eq!(
+        send_all(&recipients, Peek).await,
+        [Ok(1), Err(ActorSendError::FailedToDeliver)]
+    );
+}
+
+#[tokio::test]
+async fn tracks_targets_of_fan_out_results() {
+    let (gone, handle) = Counter { value: 3 }.run_with_handle();
+    handle.abort();
+    let _ = handle.await;
+
+    let addresses = vec![gone, Counter { value: 2 }.run()];
+
+    assert_eq!(
+        send_all_tracked(&addresses, Peek).await,
+        [(0, Err(ActorSendError::FailedToDeliver)), (1, Ok(2))]
+    );
+}
+
+#[tokio::test]
+async fn delays_first_interval_tick() {
+    let immediate = Counter { value: 0 }.run();
+    let delayed = Counter { value: 0 }.run();
+
+    let period = Duration::from_millis(40);
+    let _immediate = immediate.notify_interval_with(|| Increment, period, FirstTick::Immediate);
+    let _delayed = delayed.notify_interval_with(|| Increment, period, FirstTick::Delayed);
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(immediate.send(Peek).await, Ok(1));
+    assert_eq!(delayed.send(Peek).await, Ok(0));
+
+    tokio::time::sleep(period).await;
+
+    assert_eq!(delayed.send(Peek).await, Ok(1));
+}
+
+#[tokio::test]
+async fn runs_closures_against_actor_state() {
+    let address = Counter { value: 1 }.run();
+
+    let doubled = address
+        .with_state(|counter| {
+            counter.value *= 2;
+            counter.value
+        })
+        .await;
+
+    assert_eq!(doubled, Ok(2));
+    assert_eq!(address.send(Peek).await, Ok(2));
+}
+
+#[tokio::test]
+async fn weak_recipient_does_not_keep_actor_alive() {
+    let address = Counter { value: 1 }.run();
+    let weak = Recipient::<Peek>::from(address.clone()).downgrade();
+
+    let recipient = weak.upgrade().unwrap();
+    assert_eq!(recipient.send(Peek).await, Ok(1));
+    drop(recipient);
+
+    drop(address);
+    assert!(weak.upgrade().is_none());
+}
+
+struct Halt;
+
+impl Message for Halt {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Halt> for Counter {
+    async fn handle(&mut self, _message: Halt, context: &mut Context<Self>) {
+        context.stop();
+    }
+}
+
+#[tokio::test]
+async fn exposes_actor_state() {
+    let (address, ready) = Counter { value: 0 }.run_ready();
+    assert_eq!(address.state(), ActorState::Starting);
+
+    ready.await.unwrap();
+    assert_eq!(address.state(), ActorState::Started);
+
+    address.notify(Halt).await.unwrap();
+    address.wait_for_state(ActorState::Stopping).await;
+
+    address.wait_for_state(ActorState::Stopped).await;
+    assert_eq!(address.state(), ActorState::Stopped);
+}
+
+#[tokio::test]
+async fn sends_with_caller_owned_reply_channel() {
+    let address = Counter { value: 0 }.run();
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    address.send_with(Next, tx).await.unwrap();
+
+    assert_eq!(rx.await, Ok(1));
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    address.send_with(Next, tx).await.unwrap();
+    address.notify(Increment).await.unwrap();
+
+    // The reply is awaited only when the caller chooses to
+    assert_eq!(address.send(Peek).await, Ok(3));
+    assert_eq!(rx.await, Ok(2));
+}
+
+#[tokio::test]
+async fn counts_messages_dropped_on_full_mailbox() {
+    let address = Counter { value: 0 }.run_with_capacity(1);
+
+    address.try_notify(Increment).unwrap();
+    assert!(address.try_notify(Increment).is_err());
+    assert!(address.clone().try_notify(Increment).is_err());
+
+    assert_eq!(address.dropped_count(), 2);
+    assert_eq!(address.send(Peek).await, Ok(1));
+}
+
+#[tokio::test]
+async fn yields_responses_in_send_order() {
+    let address = Counter { value: 0 }.run_with_capacity(1);
+
+    let mut responses = address.send_ordered(vec![Next, Next, Next]).await;
+
+    assert_eq!(responses.next().await, Some(Ok(1)));
+    assert_eq!(responses.next().await, Some(Ok(2)));
+    assert_eq!(responses.next().await, Some(Ok(3)));
+    assert_eq!(responses.next().await, None);
+}
+
+#[tokio::test]
+async fn stops_actor_from_outside() {
+    let address = Counter { value: 0 }.run();
+    let clone = address.clone();
+
+    address.stop().await.unwrap();
+    clone.wait_for_state(ActorState::Stopped).await;
+
+    assert!(address.is_closed());
+    assert_eq!(clone.send(Peek).await, Err(ActorSendError::FailedToDeliver));
+}
+
+#[tokio::test]
+async fn sends_blocking_from_sync_code() {
+    let address = Counter { value: 0 }.run();
+    let clone = address.clone();
+
+    let result = tokio::task::spawn_blocking(move || clone.blocking_send(Next)).await;
+
+    assert_eq!(result.unwrap(), Ok(1));
+    assert_eq!(address.send(Peek).await, Ok(1));
+}
+
+#[tokio::test]
+#[should_panic]
+async fn panics_on_blocking_send_within_async_context() {
+    let address = Counter { value: 0 }.run();
+
+    let _ = address.blocking_send(Next);
+}
+
+struct Notifier {
+    subscribers: Vec<(u32, tokio::sync::mpsc::Sender<u32>)>,
+}
+
+impl Actor for Notifier {}
+
+struct Topic(u32);
+
+#[async_trait]
+impl Handler<Subscription<Topic, u32>> for Notifier {
+    async fn handle(&mut self, message: Subscription<Topic, u32>, _context: &mut Context<Self>) {
+        self.subscribers.push((message.message.0, message.events));
+    }
+}
+
+struct Publish(u32, u32);
+
+impl Message for Publish {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Publish> for Notifier {
+    async fn handle(&mut self, message: Publish, _context: &mut Context<Self>) {
+        for (topic, events) in &self.subscribers {
+            if *topic == message.0 {
+                let _ = events.send(message.1).await;
+            }
+        }
+    }
+}
+
+struct Unsubscribe;
+
+impl Message for Unsubscribe {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Unsubscribe> for Notifier {
+    async fn handle(&mut self, _message: Unsubscribe, _context: &mut Context<Self>) {
+        self.subscribers.clear();
+    }
+}
+
+#[tokio::test]
+async fn streams_events_to_subscribers() {
+    let address = Notifier {
+        subscribers: Vec::new(),
+    }
+    .run();
+
+    let mut first = address.subscribe(Topic(1)).await.unwrap();
+    let mut second = address.subscribe(Topic(2)).await.unwrap();
+
+    address.send(Publish(1, 10)).await.unwrap();
+    address.send(Publish(2, 20)).await.unwrap();
+    address.send(Publish(1, 11)).await.unwrap();
+    address.send(Unsubscribe).await.unwrap();
+
+    assert_eq!(first.next().await, Some(10));
+    assert_eq!(first.next().await, Some(11));
+    assert_eq!(first.next().await, None);
+    assert_eq!(second.next().await, Some(20));
+    assert_eq!(second.next().await, None);
+}
+
+#[tokio::test]
+async fn converts_send_errors_into_io_errors() {
+    async fn peek(address: &Address<Counter>) -> std::io::Result<u32> {
+        Ok(address.send(Peek).await?)
+    }
+
+    let address = Counter { value: 0 }.run();
+    assert_eq!(peek(&address).await.unwrap(), 0);
+
+    address.stop().await.unwrap();
+    address.wait_for_state(ActorState::Stopped).await;
+
+    let err = peek(&address).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+    assert_eq!(
+        ActorSendError::from(FailedToDeliver),
+        ActorSendError::FailedToDeliver
+    );
+}
+
+struct Doubler;
+
+impl Actor for Doubler {}
+
+struct Double {
+    value: u32,
+    reply_to: Recipient<Next>,
+}
+
+impl Message for Double {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Double> for Doubler {
+    async fn handle(&mut self, message: Double, _context: &mut Context<Self>) {
+        for _ in 0..message.value * 2 {
+            message.reply_to.notify(Next).await.unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn replies_to_another_recipient() {
+    let counter = Counter { value: 0 }.run();
+    let doubler = Doubler.run();
+
+    doubler
+        .send_and_reply_to(|reply_to| Double { value: 2, reply_to }, counter.clone())
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(counter.send(Peek).await, Ok(4));
+}
+
+struct Delayed(u64);
+
+impl Message for Delayed {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Delayed> for Counter {
+    async fn handle(&mut self, message: Delayed, _context: &mut Context<Self>) -> u32 {
+        tokio::time::sleep(Duration::from_millis(message.0)).await;
+
+        self.value
+    }
+}
+
+#[tokio::test]
+async fn collects_responses_available_so_far() {
+    let fast = Counter { value: 1 }.run();
+    let slow = Counter { value: 2 }.run();
+
+    let mut responses: ResponseSet<u32> = vec![
+        fast.ask(Delayed(0)).await.unwrap(),
+        slow.ask(Delayed(50)).await.unwrap(),
+    ]
+    .into_iter()
+    .collect();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(responses.poll_ready(), vec![Ok(1)]);
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses.await_all().await, vec![Ok(2)]);
+}
+
+#[tokio::test]
+async fn sends_within_deadline() {
+    let address = Counter { value: 0 }.run();
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(20);
+
+    assert_eq!(address.send_deadline(Delayed(5), deadline).await, Ok(0));
+    assert_eq!(
+        address.send_deadline(Delayed(50), deadline).await,
+        Err(ActorSendError::Timeout)
+    );
+
+    // Nothing is sent once the deadline has passed
+    assert_eq!(
+        address.send_deadline(Next, deadline).await,
+        Err(ActorSendError::Timeout)
+    );
+    assert_eq!(address.send(Peek).await, Ok(0));
+}
+
+#[tokio::test]
+async fn weak_interval_does_not_keep_actor_alive() {
+    let (address, job) = Counter { value: 0 }.run_with_handle();
+
+    let ticker = address.notify_interval_weak(|| Next, Duration::from_millis(5));
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(address.send(Peek).await.unwrap() > 0);
+
+    drop(address);
+
+    let stopped = tokio::time::timeout(Duration::from_millis(100), job).await;
+    assert!(stopped.is_ok());
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(ticker.is_finished());
+}
+
+#[tokio::test]
+async fn flushes_queued_messages() {
+    let address = Counter { value: 0 }.run();
+
+    for _ in 0..5 {
+        address.notify(Next).await.unwrap();
+    }
+    assert_eq!(address.len(), 5);
+
+    address.flush().await.unwrap();
+
+    assert!(address.is_empty());
+    assert_eq!(address.send(Peek).await, Ok(5));
+}
+
+struct Watched;
+
+impl Message for Watched {
+    type Result = tokio::sync::watch::Receiver<u32>;
+}
+
+#[async_trait]
+impl Handler<Watched> for Counter {
+    async fn handle(
+        &mut self,
+        _message: Watched,
+        _context: &mut Context<Self>,
+    ) -> tokio::sync::watch::Receiver<u32> {
+        let (tx, rx) = tokio::sync::watch::channel(self.value);
+
+        tx.send(self.value + 1).unwrap();
+
+        rx
+    }
+}
+
+struct Joined;
+
+impl Message for Joined {
+    type Result = tokio::task::JoinHandle<u32>;
+}
+
+#[async_trait]
+impl Handler<Joined> for Counter {
+    async fn handle(
+        &mut self,
+        _message: Joined,
+        _context: &mut Context<Self>,
+    ) -> tokio::task::JoinHandle<u32> {
+        let value = self.value;
+
+        tokio::spawn(async move { value * 2 })
+    }
+}
+
+struct Boxed;
+
+impl Message for Boxed {
+    type Result = std::pin::Pin<Box<dyn std::future::Future<Output = u32> + Send>>;
+}
+
+#[async_trait]
+impl Handler<Boxed> for Counter {
+    async fn handle(
+        &mut self,
+        _message: Boxed,
+        _context: &mut Context<Self>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = u32> + Send>> {
+        let value = self.value;
+
+        Box::pin(async move { value * 3 })
+    }
+}
+
+#[tokio::test]
+async fn unpacks_watched_joined_and_boxed_results() {
+    let address = Counter { value: 5 }.run();
+
+    assert_eq!(address.send_unpack(Watched).await, Ok(6));
+    assert_eq!(address.send_unpack(Joined).await, Ok(10));
+    assert_eq!(address.send_unpack(Boxed).await, Ok(15));
+}
+
+struct Closing {
+    closed: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[async_trait]
+impl Actor for Closing {
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        self.closed
+            .store(true, std::sync::atomic::Ordering::Release);
+    }
+}
+
+#[tokio::test]
+async fn notifies_observers_once_stopped() {
+    let closed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let address = Closing {
+        closed: closed.clone(),
+    }
+    .run();
+
+    let observers: Vec<_> = (0..2)
+        .map(|_| {
+            let stopped = address.stopped();
+            let closed = closed.clone();
+
+            tokio::spawn(async move {
+                stopped.await;
+
+                closed.load(std::sync::atomic::Ordering::Acquire)
+            })
+        })
+        .collect();
+
+    address.stop().await.unwrap();
+
+    for observer in observers {
+        assert!(observer.await.unwrap());
+    }
+
+    // Resolves right away once the actor is stopped
+    tokio::time::timeout(Duration::from_millis(10), address.stopped())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,158 @@
+use akt::{Actor, Address, AuditOutcome, AuditRecord, Context, Handler, Message, Recipient};
+use async_trait::async_trait;
+use std::time::Duration;
+
+struct Auditor {
+    records: Vec<AuditRecord>,
+}
+
+impl Actor for Auditor {}
+
+#[async_trait]
+impl Handler<AuditRecord> for Auditor {
+    async fn handle(&mut self, record: AuditRecord, _context: &mut Context<Self>) {
+        self.records.push(record);
+    }
+}
+
+struct Records;
+
+impl Message for Records {
+    type Result = Vec<(&'static str, AuditOutcome)>;
+}
+
+#[async_trait]
+impl Handler<Records> for Auditor {
+    async fn handle(
+        &mut self,
+        _message: Records,
+        _context: &mut Context<Self>,
+    ) -> Vec<(&'static str, AuditOutcome)> {
+        self.records
+            .iter()
+            .map(|record| (record.message_type, record.outcome))
+            .collect()
+    }
+}
+
+struct Bank {
+    auditor: Address<Auditor>,
+}
+
+impl Actor for Bank {
+    fn audit_recipient(&self) -> Option<Recipient<AuditRecord>> {
+        Some(Recipient::from(self.auditor.clone()))
+    }
+}
+
+struct Balance;
+
+impl Message for Balance {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Balance> for Bank {
+    async fn handle(&mut self, _message: Balance, _context: &mut Context<Self>) -> u32 {
+        10
+    }
+}
+
+struct Withdraw(u32);
+
+impl Message for Withdraw {
+    type Result = Result<u32, String>;
+
+    fn is_failure(result: &Self::Result) -> bool {
+        result.is_err()
+    }
+}
+
+#[async_trait]
+impl Handler<Withdraw> for Bank {
+    async fn handle(
+        &mut self,
+        message: Withdraw,
+        _context: &mut Context<Self>,
+    ) -> Result<u32, String> {
+        if message.0 > 10 {
+            return Err("insufficient funds".to_string());
+        }
+
+        Ok(10 - message.0)
+    }
+}
+
+#[tokio::test]
+async fn audits_handled_messages() {
+    let auditor = Auditor {
+        records: Vec::new(),
+    }
+    .run();
+
+    let bank = Bank {
+        auditor: auditor.clone(),
+    }
+    .run();
+
+    bank.send(Balance).await.unwrap();
+    bank.send(Withdraw(5)).await.unwrap().unwrap();
+    bank.send(Withdraw(50)).await.unwrap().unwrap_err();
+    bank.notify(Balance).await.unwrap();
+
+    // Records are sent after the reply, so only the ones preceding the
+    // last request are guaranteed to be delivered by now
+    bank.send(Balance).await.unwrap();
+
+    let records = auditor.send(Records).await.unwrap();
+
+    assert_eq!(
+        records[..4],
+        [
+            ("audit::Balance", AuditOutcome::Success),
+            ("audit::Withdraw", AuditOutcome::Success),
+            ("audit::Withdraw", AuditOutcome::Failure),
+            ("audit::Balance", AuditOutcome::Success),
+        ]
+    );
+}
+
+struct Timed {
+    durations: Vec<Duration>,
+}
+
+#[async_trait]
+impl Actor for Timed {
+    async fn on_handled(&mut self, duration: Duration, _context: &mut Context<Self>) {
+        self.durations.push(duration);
+    }
+}
+
+struct Nap(u64);
+
+impl Message for Nap {
+    type Result = Vec<Duration>;
+}
+
+#[async_trait]
+impl Handler<Nap> for Timed {
+    async fn handle(&mut self, message: Nap, _context: &mut Context<Self>) -> Vec<Duration> {
+        tokio::time::sleep(Duration::from_millis(message.0)).await;
+
+        self.durations.clone()
+    }
+}
+
+#[tokio::test]
+async fn reports_handler_latency() {
+    let address = Timed {
+        durations: Vec::new(),
+    }
+    .run();
+
+    address.send(Nap(20)).await.unwrap();
+    let durations = address.send(Nap(0)).await.unwrap();
+
+    assert_eq!(durations.len(), 1);
+    assert!(durations[0] >= Duration::from_millis(20));
+}
@@ -0,0 +1,75 @@
+use akt::{Actor, BatchHandler, Context, Handler, Message};
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+struct Storage {
+    log: Vec<Vec<u32>>,
+}
+
+impl Actor for Storage {}
+
+struct Write(u32);
+
+impl Message for Write {
+    type Result = ();
+}
+
+#[async_trait]
+impl BatchHandler<Write> for Storage {
+    const MAX_BATCH: usize = 3;
+
+    async fn handle_batch(&mut self, messages: Vec<Write>, _context: &mut Context<Self>) {
+        self.log
+            .push(messages.into_iter().map(|message| message.0).collect());
+    }
+}
+
+struct Wait(oneshot::Receiver<()>);
+
+impl Message for Wait {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Wait> for Storage {
+    async fn handle(&mut self, message: Wait, _context: &mut Context<Self>) {
+        let _ = message.0.await;
+    }
+}
+
+struct Log;
+
+impl Message for Log {
+    type Result = Vec<Vec<u32>>;
+}
+
+#[async_trait]
+impl Handler<Log> for Storage {
+    async fn handle(&mut self, _message: Log, _context: &mut Context<Self>) -> Vec<Vec<u32>> {
+        self.log.clone()
+    }
+}
+
+#[tokio::test]
+async fn handles_queued_messages_in_batches() {
+    let address = Storage { log: Vec::new() }.run();
+
+    // Keep the actor busy until everything is queued
+    let (release, wait) = oneshot::channel();
+    address.notify(Wait(wait)).await.unwrap();
+
+    for i in 1..=4 {
+        address.notify_batched(Write(i)).await.unwrap();
+    }
+
+    // Any other message ends the batch
+    address.notify(Log).await.unwrap();
+    address.notify_batched(Write(5)).await.unwrap();
+
+    release.send(()).unwrap();
+
+    assert_eq!(
+        address.send(Log).await,
+        Ok(vec![vec![1, 2, 3], vec![4], vec![5]])
+    );
+}
@@ -0,0 +1,62 @@
+use akt::{Actor, Context, EventBus, Handler, Message, Publish, Subscribe, Unsubscribe};
+use async_trait::async_trait;
+
+#[derive(Clone)]
+struct Event(u32);
+
+impl Message for Event {
+    type Result = ();
+}
+
+struct Subscriber {
+    events: Vec<u32>,
+}
+
+impl Actor for Subscriber {}
+
+#[async_trait]
+impl Handler<Event> for Subscriber {
+    async fn handle(&mut self, message: Event, _context: &mut Context<Self>) {
+        self.events.push(message.0);
+    }
+}
+
+struct Events;
+
+impl Message for Events {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Events> for Subscriber {
+    async fn handle(&mut self, _message: Events, _context: &mut Context<Self>) -> Vec<u32> {
+        self.events.clone()
+    }
+}
+
+#[tokio::test]
+async fn broadcasts_to_subscribers() {
+    let bus = EventBus::new().run();
+
+    let first = Subscriber { events: Vec::new() }.run();
+    let second = Subscriber { events: Vec::new() }.run();
+    let (gone, handle) = Subscriber { events: Vec::new() }.run_with_handle();
+
+    bus.send(Subscribe(first.clone().into())).await.unwrap();
+    let id = bus.send(Subscribe(second.clone().into())).await.unwrap();
+    bus.send(Subscribe(gone.into())).await.unwrap();
+
+    // Subscription keeps the address alive, so the actor has to be stopped
+    handle.abort();
+    let _ = handle.await;
+
+    assert_eq!(bus.send(Publish(Event(1))).await, Ok(2));
+
+    assert_eq!(bus.send(Unsubscribe(id)).await, Ok(true));
+    assert_eq!(bus.send(Unsubscribe(id)).await, Ok(false));
+
+    assert_eq!(bus.send(Publish(Event(2))).await, Ok(1));
+
+    assert_eq!(first.send(Events).await, Ok(vec![1, 2]));
+    assert_eq!(second.send(Events).await, Ok(vec![1]));
+}
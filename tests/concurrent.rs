@@ -0,0 +1,90 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use akt::{Actor, ConcurrentHandler, Context, Handler, Message};
+use async_trait::async_trait;
+
+struct Cache {
+    value: u32,
+    reading: AtomicUsize,
+    max_reading: AtomicUsize,
+}
+
+impl Actor for Cache {
+    const MAX_CONCURRENCY: usize = 3;
+}
+
+struct Read;
+
+impl Message for Read {
+    type Result = u32;
+}
+
+#[async_trait]
+impl ConcurrentHandler<Read> for Cache {
+    async fn handle(&self, _message: Read) -> u32 {
+        let reading = self.reading.fetch_add(1, Ordering::AcqRel) + 1;
+        self.max_reading.fetch_max(reading, Ordering::AcqRel);
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        self.reading.fetch_sub(1, Ordering::AcqRel);
+
+        self.value
+    }
+}
+
+struct Write(u32);
+
+impl Message for Write {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Write> for Cache {
+    async fn handle(&mut self, message: Write, _context: &mut Context<Self>) -> usize {
+        self.value = message.0;
+
+        // Reads in flight while writing
+        self.reading.load(Ordering::Acquire)
+    }
+}
+
+struct MaxReading;
+
+impl Message for MaxReading {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<MaxReading> for Cache {
+    async fn handle(&mut self, _message: MaxReading, _context: &mut Context<Self>) -> usize {
+        self.max_reading.load(Ordering::Acquire)
+    }
+}
+
+#[tokio::test]
+async fn handles_reads_concurrently_and_writes_exclusively() {
+    let address = Cache {
+        value: 1,
+        reading: AtomicUsize::new(0),
+        max_reading: AtomicUsize::new(0),
+    }
+    .run();
+
+    let (a, b, c, d, write, e) = tokio::join!(
+        address.send_concurrent(Read),
+        address.send_concurrent(Read),
+        address.send_concurrent(Read),
+        address.send_concurrent(Read),
+        address.send(Write(2)),
+        address.send_concurrent(Read),
+    );
+
+    assert_eq!((a, b, c, d), (Ok(1), Ok(1), Ok(1), Ok(1)));
+    assert_eq!(write, Ok(0));
+    assert_eq!(e, Ok(2));
+    assert_eq!(address.send(MaxReading).await, Ok(3));
+}
@@ -0,0 +1,1727 @@
+use akt::{
+    Actor, ActorSendError, Address, Context, CorrelationId, Handler, Message, MessageSource,
+    Recipient, RetryPolicy, SpawnHandle, System, UnboundedAddress, Validate, ValidationError,
+    WeakUnboundedAddress,
+};
+use async_trait::async_trait;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+struct Listener {
+    received: Vec<u32>,
+}
+
+impl Actor for Listener {}
+
+struct Callback(u32);
+
+impl Message for Callback {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Callback> for Listener {
+    async fn handle(&mut self, message: Callback, _context: &mut Context<Self>) {
+        self.received.push(message.0);
+    }
+}
+
+struct Register;
+
+impl Message for Register {
+    type Result = Recipient<Callback>;
+}
+
+#[async_trait]
+impl Handler<Register> for Listener {
+    async fn handle(
+        &mut self,
+        _message: Register,
+        context: &mut Context<Self>,
+    ) -> Recipient<Callback> {
+        context.recipient()
+    }
+}
+
+struct Received;
+
+impl Message for Received {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Received> for Listener {
+    async fn handle(&mut self, _message: Received, _context: &mut Context<Self>) -> Vec<u32> {
+        self.received.clone()
+    }
+}
+
+#[tokio::test]
+async fn calls_back_through_own_recipient() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let recipient = address.send(Register).await.unwrap();
+
+    recipient.notify(Callback(1)).await.unwrap();
+    recipient.send(Callback(2)).await.unwrap();
+
+    assert_eq!(address.send(Received).await, Ok(vec![1, 2]));
+}
+
+struct RegisterPublic;
+
+impl Message for RegisterPublic {
+    type Result = Option<Recipient<Callback>>;
+}
+
+#[async_trait]
+impl Handler<RegisterPublic> for Listener {
+    async fn handle(
+        &mut self,
+        _message: RegisterPublic,
+        context: &mut Context<Self>,
+    ) -> Option<Recipient<Callback>> {
+        context.public_recipient()
+    }
+}
+
+#[tokio::test]
+async fn public_recipient_keeps_actor_alive() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let recipient = address.send(RegisterPublic).await.unwrap().unwrap();
+
+    drop(address);
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert!(recipient.is_connected());
+    assert_eq!(recipient.send(Callback(1)).await, Ok(()));
+}
+
+struct Slow;
+
+impl Message for Slow {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Slow> for Listener {
+    async fn handle(&mut self, _message: Slow, context: &mut Context<Self>) -> u32 {
+        let reply = context.take_reply::<Slow>().unwrap();
+        let recipient = context.recipient::<Callback>();
+
+        tokio::spawn(async move {
+            // Loop is free to handle other messages meanwhile
+            recipient.send(Callback(10)).await.unwrap();
+
+            let _ = reply.send(100);
+        });
+
+        0
+    }
+}
+
+#[tokio::test]
+async fn replies_later_through_taken_handle() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    assert_eq!(address.send(Slow).await, Ok(100));
+    assert_eq!(address.send(Received).await, Ok(vec![10]));
+}
+
+impl Validate for Slow {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn replies_later_to_validated_messages() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    assert_eq!(address.send_validated(Slow).await, Ok(Ok(100)));
+    assert_eq!(address.send(Received).await, Ok(vec![10]));
+}
+
+struct Report;
+
+impl Message for Report {
+    type Result = Option<String>;
+}
+
+#[async_trait]
+impl Handler<Report> for Listener {
+    async fn handle(&mut self, _message: Report, context: &mut Context<Self>) -> Option<String> {
+        if !context.expects_reply() {
+            return None;
+        }
+
+        Some(format!("{:?}", self.received))
+    }
+}
+
+#[tokio::test]
+async fn knows_whether_reply_is_expected() {
+    let address = Listener { received: vec![1] }.run();
+
+    address.notify(Report).await.unwrap();
+
+    assert_eq!(address.send(Report).await, Ok(Some("[1]".to_string())));
+}
+
+struct Flaky {
+    failures_left: u32,
+}
+
+impl Actor for Flaky {}
+
+#[derive(Clone)]
+struct Fetch;
+
+impl Message for Fetch {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Fetch> for Flaky {
+    async fn handle(&mut self, _message: Fetch, context: &mut Context<Self>) -> u32 {
+        if self.failures_left > 0 {
+            self.failures_left -= 1;
+
+            // Dropping the taken reply fails the call
+            drop(context.take_reply::<Fetch>());
+        }
+
+        42
+    }
+}
+
+struct FetchFrom(Address<Flaky>);
+
+impl Message for FetchFrom {
+    type Result = Result<u32, ActorSendError>;
+}
+
+#[async_trait]
+impl Handler<FetchFrom> for Listener {
+    async fn handle(
+        &mut self,
+        message: FetchFrom,
+        context: &mut Context<Self>,
+    ) -> Result<u32, ActorSendError> {
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_millis(1), Duration::from_millis(5))
+            .jitter(0.5);
+
+        context.call_with_retry(&message.0, Fetch, policy).await
+    }
+}
+
+#[tokio::test]
+async fn retries_failed_calls() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let flaky = Flaky { failures_left: 2 }.run();
+    assert_eq!(address.send(FetchFrom(flaky)).await, Ok(Ok(42)));
+
+    let flaky = Flaky { failures_left: 3 }.run();
+    assert_eq!(
+        address.send(FetchFrom(flaky)).await,
+        Ok(Err(ActorSendError::FailedToGetResponse))
+    );
+}
+
+#[tokio::test]
+async fn retries_only_undelivered_sends() {
+    let policy = RetryPolicy::new(3).backoff(Duration::from_millis(1), Duration::from_millis(5));
+
+    let flaky = Flaky { failures_left: 1 }.run();
+    assert_eq!(
+        flaky.send_retry(Fetch, policy.clone()).await,
+        Err(ActorSendError::FailedToGetResponse)
+    );
+    assert_eq!(flaky.send_retry(Fetch, policy.clone()).await, Ok(42));
+
+    let (stopped, handle) = Flaky { failures_left: 0 }.run_with_handle();
+    handle.abort();
+    let _ = handle.await;
+
+    let started = Instant::now();
+    assert_eq!(
+        stopped.send_retry(Fetch, policy).await,
+        Err(ActorSendError::FailedToDeliver)
+    );
+    // Two retries were made before giving up
+    assert!(started.elapsed() >= Duration::from_millis(3));
+}
+
+struct Ticker {
+    ticks: Vec<Instant>,
+}
+
+impl Actor for Ticker {}
+
+struct Tick(Instant);
+
+impl Message for Tick {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Tick> for Ticker {
+    async fn handle(&mut self, message: Tick, _context: &mut Context<Self>) {
+        if self.ticks.is_empty() {
+            // Simulate load blocking the runtime for more than two periods
+            std::thread::sleep(Duration::from_millis(70));
+        }
+
+        self.ticks.push(message.0);
+    }
+}
+
+struct Start;
+
+impl Message for Start {
+    type Result = SpawnHandle;
+}
+
+#[async_trait]
+impl Handler<Start> for Ticker {
+    async fn handle(&mut self, _message: Start, context: &mut Context<Self>) -> SpawnHandle {
+        context.schedule_recurring(Duration::from_millis(30), || Tick(Instant::now()))
+    }
+}
+
+struct Ticks;
+
+impl Message for Ticks {
+    type Result = Vec<Instant>;
+}
+
+#[async_trait]
+impl Handler<Ticks> for Ticker {
+    async fn handle(&mut self, _message: Ticks, _context: &mut Context<Self>) -> Vec<Instant> {
+        self.ticks.clone()
+    }
+}
+
+#[tokio::test]
+async fn recurring_schedule_does_not_drift() {
+    let address = Ticker { ticks: Vec::new() }.run();
+
+    let start = Instant::now();
+    let schedule = address.send(Start).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(170)).await;
+    schedule.abort();
+
+    let ticks = address.send(Ticks).await.unwrap();
+    let offsets: Vec<_> = ticks
+        .iter()
+        .map(|tick| tick.duration_since(start).as_millis() % 30)
+        .collect();
+
+    // The tick delayed by the load is sent once, late, then the schedule
+    // is aligned to multiples of the period again
+    assert!(ticks.len() >= 4, "{:?}", ticks);
+    assert!(ticks[2] - ticks[1] < Duration::from_millis(30));
+    assert!(
+        offsets[2..].iter().all(|offset| *offset < 10),
+        "{:?}",
+        offsets
+    );
+}
+
+struct StartZero;
+
+impl Message for StartZero {
+    type Result = SpawnHandle;
+}
+
+#[async_trait]
+impl Handler<StartZero> for Ticker {
+    async fn handle(&mut self, _message: StartZero, context: &mut Context<Self>) -> SpawnHandle {
+        context.schedule_recurring(Duration::ZERO, || Tick(Instant::now()))
+    }
+}
+
+#[tokio::test]
+async fn recurring_schedule_rejects_zero_period() {
+    let address = Ticker { ticks: Vec::new() }.run();
+
+    assert_eq!(
+        address.send(StartZero).await.err(),
+        Some(ActorSendError::FailedToGetResponse)
+    );
+    assert_eq!(address.send(Ticks).await.map(|ticks| ticks.len()), Ok(0));
+}
+
+struct StartJittered;
+
+impl Message for StartJittered {
+    type Result = SpawnHandle;
+}
+
+#[async_trait]
+impl Handler<StartJittered> for Listener {
+    async fn handle(
+        &mut self,
+        _message: StartJittered,
+        context: &mut Context<Self>,
+    ) -> SpawnHandle {
+        context.private_address().notify_interval_jittered(
+            || Callback(1),
+            Duration::from_millis(10),
+            Duration::from_millis(5),
+        )
+    }
+}
+
+#[tokio::test]
+async fn ticks_with_jitter_until_aborted() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let ticker = address.send(StartJittered).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    ticker.abort();
+
+    let received = address.send(Received).await.unwrap().len();
+    assert!((3..=7).contains(&received), "{}", received);
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(address.send(Received).await.unwrap().len(), received);
+}
+
+struct Decode(u64);
+
+impl Message for Decode {
+    type Result = tokio::sync::oneshot::Receiver<u64>;
+}
+
+#[async_trait]
+impl Handler<Decode> for Listener {
+    async fn handle(
+        &mut self,
+        message: Decode,
+        context: &mut Context<Self>,
+    ) -> tokio::sync::oneshot::Receiver<u64> {
+        context.run_blocking(move || {
+            std::thread::sleep(Duration::from_millis(20));
+
+            message.0 * 2
+        })
+    }
+}
+
+#[tokio::test]
+async fn offloads_blocking_work() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let (decoded, received) = tokio::join!(address.send_unpack(Decode(21)), async {
+        address.notify(Callback(1)).await.unwrap();
+        address.send(Received).await
+    });
+
+    assert_eq!(decoded, Ok(42));
+    assert_eq!(received, Ok(vec![1]));
+}
+
+struct Downgrade;
+
+impl Message for Downgrade {
+    type Result = WeakUnboundedAddress<Listener>;
+}
+
+#[async_trait]
+impl Handler<Downgrade> for Listener {
+    async fn handle(
+        &mut self,
+        _message: Downgrade,
+        context: &mut Context<Self>,
+    ) -> WeakUnboundedAddress<Listener> {
+        context.private_address().downgrade()
+    }
+}
+
+#[tokio::test]
+async fn weak_private_address_does_not_outlive_actor() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let weak = address.send(Downgrade).await.unwrap();
+    let upgraded = weak.clone().upgrade().unwrap();
+
+    upgraded.notify(Callback(5)).unwrap();
+    drop(upgraded);
+
+    assert_eq!(address.send(Received).await, Ok(vec![5]));
+
+    drop(address);
+    tokio::task::yield_now().await;
+
+    assert!(weak.upgrade().is_none());
+}
+
+struct Pause;
+
+impl Message for Pause {
+    type Result = UnboundedAddress<Listener>;
+}
+
+#[async_trait]
+impl Handler<Pause> for Listener {
+    async fn handle(
+        &mut self,
+        _message: Pause,
+        context: &mut Context<Self>,
+    ) -> UnboundedAddress<Listener> {
+        context.pause();
+
+        context.private_address()
+    }
+}
+
+struct Resume;
+
+impl Message for Resume {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Resume> for Listener {
+    async fn handle(&mut self, _message: Resume, context: &mut Context<Self>) {
+        context.resume();
+    }
+}
+
+#[tokio::test]
+async fn buffers_public_messages_while_paused() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let private = address.send(Pause).await.unwrap();
+
+    address.notify(Callback(1)).await.unwrap();
+    private.send(Callback(2)).await.unwrap();
+    assert_eq!(address.len(), 1);
+
+    private.send(Resume).await.unwrap();
+
+    assert_eq!(address.send(Received).await, Ok(vec![2, 1]));
+}
+
+#[tokio::test]
+async fn replies_to_requests_sent_while_paused_once_resumed() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let private = address.send(Pause).await.unwrap();
+
+    let pending = tokio::spawn({
+        let address = address.clone();
+
+        async move { address.send(Received).await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!pending.is_finished());
+
+    private.send(Resume).await.unwrap();
+
+    assert_eq!(pending.await.unwrap(), Ok(Vec::new()));
+}
+
+#[tokio::test]
+async fn times_out_requests_to_paused_actor() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let private = address.send(Pause).await.unwrap();
+
+    assert_eq!(
+        address
+            .send_timeout(Received, Duration::from_millis(10))
+            .await,
+        Err(ActorSendError::Timeout)
+    );
+
+    private.send(Resume).await.unwrap();
+
+    assert_eq!(
+        address.send_timeout(Received, Duration::from_secs(1)).await,
+        Ok(Vec::new())
+    );
+}
+
+#[tokio::test]
+async fn stops_paused_actor_once_addresses_are_dropped() {
+    let (address, handle) = Listener {
+        received: Vec::new(),
+    }
+    .run_with_handle();
+
+    let private = address.send(Pause).await.unwrap();
+
+    address.notify(Callback(1)).await.unwrap();
+    drop(address);
+
+    let stopped = tokio::time::timeout(Duration::from_secs(1), handle).await;
+    assert!(matches!(stopped, Ok(Ok(()))));
+    assert!(private.is_closed());
+}
+
+#[tokio::test]
+async fn stops_paused_actor_from_outside() {
+    let (address, handle) = Listener {
+        received: Vec::new(),
+    }
+    .run_with_handle();
+
+    let _private = address.send(Pause).await.unwrap();
+
+    address.stop().await.unwrap();
+
+    let stopped = tokio::time::timeout(Duration::from_secs(1), handle).await;
+    assert!(matches!(stopped, Ok(Ok(()))));
+}
+
+struct Countdown(u32);
+
+impl Message for Countdown {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Countdown> for Listener {
+    async fn handle(&mut self, message: Countdown, context: &mut Context<Self>) {
+        self.received.push(message.0);
+
+        match message.0 {
+            0 => {}
+            1 => drop(context.notify_later(Countdown(0), Duration::from_millis(10))),
+            n => context.notify(Countdown(n - 1)).unwrap(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn notifies_itself() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    address.notify(Countdown(3)).await.unwrap();
+    assert_eq!(address.send(Received).await, Ok(vec![3, 2, 1]));
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(address.send(Received).await, Ok(vec![3, 2, 1, 0]));
+}
+
+struct Lookup(u32);
+
+impl Message for Lookup {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Lookup> for Listener {
+    async fn handle(&mut self, message: Lookup, context: &mut Context<Self>) {
+        let lookup = async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            message.0 * 2
+        };
+
+        context.pipe(lookup, Callback);
+    }
+}
+
+#[tokio::test]
+async fn pipes_future_output_back() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    address.notify(Lookup(21)).await.unwrap();
+    assert_eq!(address.send(Received).await, Ok(vec![]));
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(address.send(Received).await, Ok(vec![42]));
+}
+
+struct Bounded;
+
+impl Actor for Bounded {
+    const PRIVATE_MAILBOX_CAPACITY: Option<usize> = Some(2);
+}
+
+struct Flood;
+
+impl Message for Flood {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Flood> for Bounded {
+    async fn handle(&mut self, _message: Flood, context: &mut Context<Self>) -> usize {
+        (0..5)
+            .filter(|_| context.notify(Callback(0)).is_ok())
+            .count()
+    }
+}
+
+#[async_trait]
+impl Handler<Callback> for Bounded {
+    async fn handle(&mut self, _message: Callback, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn rejects_messages_to_full_private_mailbox() {
+    let address = Bounded.run();
+
+    assert_eq!(address.send(Flood).await, Ok(2));
+    // Queued notifications are handled before the next public message
+    assert_eq!(address.send(Flood).await, Ok(2));
+}
+
+struct Throttled {
+    handled: usize,
+}
+
+impl Actor for Throttled {
+    const PRIVATE_MAILBOX_CAPACITY: Option<usize> = Some(2);
+}
+
+struct Trickle;
+
+impl Message for Trickle {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Trickle> for Throttled {
+    async fn handle(&mut self, _message: Trickle, context: &mut Context<Self>) {
+        let notifications: Vec<_> = (0..5).map(|_| context.notify_async(Callback(0))).collect();
+
+        context.spawn_scoped(async move {
+            for notification in notifications {
+                notification.await.unwrap();
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Handler<Callback> for Throttled {
+    async fn handle(&mut self, _message: Callback, _context: &mut Context<Self>) {
+        self.handled += 1;
+    }
+}
+
+struct Handled;
+
+impl Message for Handled {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Handled> for Throttled {
+    async fn handle(&mut self, _message: Handled, _context: &mut Context<Self>) -> usize {
+        self.handled
+    }
+}
+
+#[tokio::test]
+async fn waits_for_room_in_full_private_mailbox() {
+    let address = Throttled { handled: 0 }.run();
+
+    address.send(Trickle).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    assert_eq!(address.send(Handled).await, Ok(5));
+}
+
+struct Session;
+
+#[async_trait]
+impl Actor for Session {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        context.stop_after(Duration::from_millis(30));
+    }
+}
+
+#[async_trait]
+impl Handler<Callback> for Session {
+    async fn handle(&mut self, _message: Callback, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn stops_after_idle_period() {
+    let (address, handle) = Session.run_with_handle();
+    let started = Instant::now();
+
+    for _ in 0..4 {
+        tokio::time::sleep(Duration::from_millis(15)).await;
+        address.send(Callback(0)).await.unwrap();
+    }
+
+    handle.await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(90));
+    assert!(address.is_closed());
+}
+
+struct Settings {
+    region: &'static str,
+}
+
+struct Tenant;
+
+impl Actor for Tenant {}
+
+struct Region;
+
+impl Message for Region {
+    type Result = Option<&'static str>;
+}
+
+#[async_trait]
+impl Handler<Region> for Tenant {
+    async fn handle(
+        &mut self,
+        _message: Region,
+        context: &mut Context<Self>,
+    ) -> Option<&'static str> {
+        context.system::<Settings>().map(|system| system.region)
+    }
+}
+
+struct Landlord;
+
+impl Actor for Landlord {}
+
+struct SpawnTenant;
+
+impl Message for SpawnTenant {
+    type Result = Address<Tenant>;
+}
+
+#[async_trait]
+impl Handler<SpawnTenant> for Landlord {
+    async fn handle(
+        &mut self,
+        _message: SpawnTenant,
+        context: &mut Context<Self>,
+    ) -> Address<Tenant> {
+        context.spawn_linked(Tenant)
+    }
+}
+
+#[tokio::test]
+async fn shares_system_state() {
+    let landlord = Landlord.run_in_system(System::new(Settings { region: "eu" }));
+    let tenant = landlord.send(SpawnTenant).await.unwrap();
+
+    assert_eq!(tenant.send(Region).await, Ok(Some("eu")));
+    assert_eq!(Tenant.run().send(Region).await, Ok(None));
+}
+
+struct Pipeline;
+
+impl Actor for Pipeline {}
+
+struct Stage {
+    last: bool,
+}
+
+impl Message for Stage {
+    type Result = bool;
+}
+
+#[async_trait]
+impl Handler<Stage> for Pipeline {
+    async fn handle(&mut self, message: Stage, context: &mut Context<Self>) -> bool {
+        if message.last {
+            context.stop();
+        }
+
+        context.strong_address().is_some()
+    }
+}
+
+#[tokio::test]
+async fn hands_out_strong_address_until_stopping() {
+    let address = Pipeline.run();
+
+    assert_eq!(address.send(Stage { last: false }).await, Ok(true));
+    assert_eq!(address.send(Stage { last: true }).await, Ok(false));
+}
+
+struct Background;
+
+impl Message for Background {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Background> for Listener {
+    async fn handle(&mut self, _message: Background, context: &mut Context<Self>) {
+        let guard = context.guard().unwrap();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            let _ = guard.notify(Callback(7)).await;
+        });
+    }
+}
+
+#[tokio::test]
+async fn guard_keeps_actor_alive_for_background_work() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+    let weak = address.downgrade();
+
+    address.send(Background).await.unwrap();
+    drop(address);
+
+    // Only the guard moved into the task keeps the actor running
+    assert!(weak.upgrade().is_some());
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(weak.upgrade().is_none());
+}
+
+struct Heartbeat {
+    schedule: Arc<Mutex<Option<SpawnHandle>>>,
+}
+
+#[async_trait]
+impl Actor for Heartbeat {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        let handle = context.schedule_interval(|| Beat, Duration::from_secs(3600));
+
+        *self.schedule.lock().unwrap() = Some(handle);
+    }
+}
+
+struct Beat;
+
+impl Message for Beat {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Beat> for Heartbeat {
+    async fn handle(&mut self, _message: Beat, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn aborts_scheduled_interval_on_stop() {
+    let schedule = Arc::new(Mutex::new(None));
+
+    let (address, join) = Heartbeat {
+        schedule: schedule.clone(),
+    }
+    .run_with_handle();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    drop(address);
+    join.await.unwrap();
+    tokio::task::yield_now().await;
+
+    let schedule = schedule.lock().unwrap().take().unwrap();
+    assert!(schedule.is_finished());
+}
+
+struct Introspect;
+
+impl Message for Introspect {
+    type Result = (u64, Duration);
+}
+
+#[async_trait]
+impl Handler<Introspect> for Listener {
+    async fn handle(
+        &mut self,
+        _message: Introspect,
+        context: &mut Context<Self>,
+    ) -> (u64, Duration) {
+        (context.messages_handled(), context.uptime())
+    }
+}
+
+#[tokio::test]
+async fn reports_uptime_and_messages_handled() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    address.notify(Callback(1)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let (handled, uptime) = address.send(Introspect).await.unwrap();
+
+    assert_eq!(handled, 1);
+    assert!(uptime >= Duration::from_millis(20));
+    assert_eq!(address.send(Introspect).await.unwrap().0, 2);
+}
+
+struct Cleanup {
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Cleanup {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        for name in ["first", "second"] {
+            let log = self.log.clone();
+
+            context.on_finalize(move || async move {
+                log.lock().unwrap().push(name);
+            });
+        }
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.log.lock().unwrap().push("stopped");
+    }
+}
+
+#[tokio::test]
+async fn runs_finalizers_in_reverse_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let (address, join) = Cleanup { log: log.clone() }.run_with_handle();
+
+    drop(address);
+    join.await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["stopped", "second", "first"]);
+}
+
+#[tokio::test]
+async fn runs_finalizers_of_aborted_actor() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let (_address, abort) = Cleanup { log: log.clone() }.run_abortable();
+
+    // Let the actor start and register its finalizers
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    abort.abort();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(*log.lock().unwrap(), vec!["second", "first"]);
+}
+
+struct Cruncher {
+    ticks: Arc<AtomicUsize>,
+}
+
+impl Actor for Cruncher {
+    const YIELD_BUDGET: Duration = Duration::from_millis(2);
+}
+
+struct Crunch;
+
+impl Message for Crunch {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Crunch> for Cruncher {
+    async fn handle(&mut self, _message: Crunch, context: &mut Context<Self>) -> usize {
+        let before = self.ticks.load(Ordering::Acquire);
+
+        for _ in 0..10 {
+            std::thread::sleep(Duration::from_millis(1));
+
+            context.yield_if_needed().await;
+        }
+
+        self.ticks.load(Ordering::Acquire) - before
+    }
+}
+
+#[tokio::test]
+async fn yields_to_other_tasks_once_budget_is_spent() {
+    let ticks = Arc::new(AtomicUsize::new(0));
+
+    let ticker = tokio::spawn({
+        let ticks = ticks.clone();
+
+        async move {
+            loop {
+                ticks.fetch_add(1, Ordering::AcqRel);
+                tokio::task::yield_now().await;
+            }
+        }
+    });
+
+    let address = Cruncher { ticks }.run();
+    let ticked = address.send(Crunch).await.unwrap();
+
+    ticker.abort();
+
+    // The ticker got to run in the middle of the handler
+    assert!(ticked > 0);
+}
+
+struct Foreman {
+    reports: Vec<u32>,
+}
+
+impl Actor for Foreman {}
+
+struct Worker {
+    events: Recipient<Progress>,
+}
+
+impl Actor for Worker {}
+
+struct Progress(u32);
+
+impl Message for Progress {
+    type Result = ();
+}
+
+struct Work(u32);
+
+impl Message for Work {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Work> for Worker {
+    async fn handle(&mut self, message: Work, _context: &mut Context<Self>) {
+        self.events.send(Progress(message.0)).await.unwrap();
+    }
+}
+
+struct Hire;
+
+impl Message for Hire {
+    type Result = Address<Worker>;
+}
+
+#[async_trait]
+impl Handler<Hire> for Foreman {
+    async fn handle(&mut self, _message: Hire, context: &mut Context<Self>) -> Address<Worker> {
+        context.spawn_mapped(
+            |events| Worker { events },
+            |progress: Progress| Tally(progress.0 * 10),
+        )
+    }
+}
+
+struct Tally(u32);
+
+impl Message for Tally {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Tally> for Foreman {
+    async fn handle(&mut self, message: Tally, _context: &mut Context<Self>) {
+        self.reports.push(message.0);
+    }
+}
+
+struct Reports;
+
+impl Message for Reports {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Reports> for Foreman {
+    async fn handle(&mut self, _message: Reports, _context: &mut Context<Self>) -> Vec<u32> {
+        self.reports.clone()
+    }
+}
+
+#[tokio::test]
+async fn maps_child_events_into_parent_messages() {
+    let foreman = Foreman {
+        reports: Vec::new(),
+    }
+    .run();
+    let worker = foreman.send(Hire).await.unwrap();
+
+    worker.send(Work(1)).await.unwrap();
+    worker.send(Work(2)).await.unwrap();
+
+    assert_eq!(foreman.send(Reports).await, Ok(vec![10, 20]));
+}
+
+struct Closing {
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Closing {
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.log.lock().unwrap().push("stopped");
+    }
+}
+
+struct Finish;
+
+impl Message for Finish {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Finish> for Closing {
+    async fn handle(&mut self, _message: Finish, context: &mut Context<Self>) -> u32 {
+        let reply = context.take_reply::<Finish>().unwrap();
+        let log = self.log.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+
+            log.lock().unwrap().push("replied");
+            let _ = reply.send(7);
+        });
+
+        context.stop_after_reply();
+
+        0
+    }
+}
+
+#[tokio::test]
+async fn stops_after_taken_reply_is_sent() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (address, handle) = Closing { log: log.clone() }.run_with_handle();
+
+    assert_eq!(address.send(Finish).await, Ok(7));
+    handle.await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), ["replied", "stopped"]);
+}
+
+struct Watcher {
+    changes: Vec<u32>,
+}
+
+impl Actor for Watcher {}
+
+struct Changed(u32);
+
+impl Message for Changed {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Changed> for Watcher {
+    async fn handle(&mut self, message: Changed, context: &mut Context<Self>) {
+        context.notify_debounced(Reload(message.0), Duration::from_millis(30));
+    }
+}
+
+struct Reload(u32);
+
+impl Message for Reload {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Reload> for Watcher {
+    async fn handle(&mut self, message: Reload, _context: &mut Context<Self>) {
+        self.changes.push(message.0);
+    }
+}
+
+struct Reloads;
+
+impl Message for Reloads {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Reloads> for Watcher {
+    async fn handle(&mut self, _message: Reloads, _context: &mut Context<Self>) -> Vec<u32> {
+        self.changes.clone()
+    }
+}
+
+#[tokio::test]
+async fn debounces_notifications() {
+    let address = Watcher {
+        changes: Vec::new(),
+    }
+    .run();
+
+    for change in 1..=3 {
+        address.send(Changed(change)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(address.send(Reloads).await, Ok(vec![]));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    address.send(Changed(4)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(address.send(Reloads).await, Ok(vec![3, 4]));
+}
+
+struct Gateway {
+    answers: Vec<u32>,
+}
+
+impl Actor for Gateway {
+    const MAX_PIPED_TASKS: usize = 2;
+}
+
+struct Query(u32);
+
+impl Message for Query {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Query> for Gateway {
+    async fn handle(&mut self, message: Query, context: &mut Context<Self>) -> usize {
+        let query = async move {
+            tokio::time::sleep(Duration::from_millis(30) * message.0).await;
+
+            message.0
+        };
+
+        context.pipe_bounded(query, Answer).await;
+
+        context.piped_task_count()
+    }
+}
+
+struct Answer(u32);
+
+impl Message for Answer {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Answer> for Gateway {
+    async fn handle(&mut self, message: Answer, _context: &mut Context<Self>) {
+        self.answers.push(message.0);
+    }
+}
+
+struct Answers;
+
+impl Message for Answers {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Answers> for Gateway {
+    async fn handle(&mut self, _message: Answers, _context: &mut Context<Self>) -> Vec<u32> {
+        self.answers.clone()
+    }
+}
+
+#[tokio::test]
+async fn bounds_piped_futures() {
+    let address = Gateway {
+        answers: Vec::new(),
+    }
+    .run();
+    let started = Instant::now();
+
+    assert_eq!(address.send(Query(1)).await, Ok(1));
+    assert_eq!(address.send(Query(2)).await, Ok(2));
+    assert!(started.elapsed() < Duration::from_millis(30));
+
+    // Waits for the first query to finish
+    assert_eq!(address.send(Query(3)).await, Ok(2));
+    assert!(started.elapsed() >= Duration::from_millis(30));
+
+    tokio::time::sleep(Duration::from_millis(120)).await;
+
+    let mut answers = address.send(Answers).await.unwrap();
+    answers.sort();
+    assert_eq!(answers, [1, 2, 3]);
+}
+
+struct Arm {
+    key: &'static str,
+    value: u32,
+}
+
+impl Message for Arm {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Arm> for Listener {
+    async fn handle(&mut self, message: Arm, context: &mut Context<Self>) {
+        context.schedule(
+            message.key,
+            Callback(message.value),
+            Duration::from_millis(30),
+        );
+    }
+}
+
+struct Disarm(&'static str);
+
+impl Message for Disarm {
+    type Result = bool;
+}
+
+#[async_trait]
+impl Handler<Disarm> for Listener {
+    async fn handle(&mut self, message: Disarm, context: &mut Context<Self>) -> bool {
+        context.cancel_schedule(message.0)
+    }
+}
+
+#[tokio::test]
+async fn replaces_and_cancels_keyed_schedules() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    address.send(Arm { key: "a", value: 1 }).await.unwrap();
+    address.send(Arm { key: "a", value: 2 }).await.unwrap();
+    address.send(Arm { key: "b", value: 3 }).await.unwrap();
+
+    assert_eq!(address.send(Disarm("b")).await, Ok(true));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(address.send(Received).await, Ok(vec![2]));
+    assert_eq!(address.send(Disarm("a")).await, Ok(false));
+}
+
+struct SideTask;
+
+impl Message for SideTask {
+    type Result = tokio::task::JoinHandle<()>;
+}
+
+#[async_trait]
+impl Handler<SideTask> for Listener {
+    async fn handle(
+        &mut self,
+        _message: SideTask,
+        context: &mut Context<Self>,
+    ) -> tokio::task::JoinHandle<()> {
+        context.spawn_scoped(std::future::pending())
+    }
+}
+
+#[tokio::test]
+async fn ends_scoped_tasks_once_actor_stops() {
+    let address = Listener {
+        received: Vec::new(),
+    }
+    .run();
+
+    let task = address.send(SideTask).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!task.is_finished());
+
+    address.stop().await.unwrap();
+
+    let ended = tokio::time::timeout(Duration::from_millis(100), task).await;
+    assert!(ended.is_ok());
+}
+
+struct Origins {
+    sources: Vec<MessageSource>,
+}
+
+impl Actor for Origins {}
+
+struct Trace;
+
+impl Message for Trace {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Trace> for Origins {
+    async fn handle(&mut self, _message: Trace, context: &mut Context<Self>) {
+        self.sources.push(context.message_source());
+    }
+}
+
+struct Echo;
+
+impl Message for Echo {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Echo> for Origins {
+    async fn handle(&mut self, _message: Echo, context: &mut Context<Self>) {
+        context.notify(Trace).unwrap();
+    }
+}
+
+struct Sources;
+
+impl Message for Sources {
+    type Result = Vec<MessageSource>;
+}
+
+#[async_trait]
+impl Handler<Sources> for Origins {
+    async fn handle(
+        &mut self,
+        _message: Sources,
+        _context: &mut Context<Self>,
+    ) -> Vec<MessageSource> {
+        self.sources.clone()
+    }
+}
+
+#[tokio::test]
+async fn tells_mailbox_message_came_from() {
+    let (address, control) = Origins {
+        sources: Vec::new(),
+    }
+    .run_with_control();
+
+    address.send(Trace).await.unwrap();
+    address.send(Echo).await.unwrap();
+    control.send(Trace).await.unwrap();
+
+    assert_eq!(
+        address.send(Sources).await,
+        Ok(vec![
+            MessageSource::Public,
+            MessageSource::Private,
+            MessageSource::Control
+        ])
+    );
+}
+
+struct Stall;
+
+impl Message for Stall {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Stall> for Origins {
+    async fn handle(&mut self, _message: Stall, context: &mut Context<Self>) {
+        context.notify(Trace).unwrap();
+        context.notify(Trace).unwrap();
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn snapshots_private_mailbox_without_handling_it() {
+    let (address, control) = Origins {
+        sources: Vec::new(),
+    }
+    .run_with_control();
+
+    address.notify(Stall).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    let trace = std::any::type_name::<Trace>();
+    assert_eq!(control.queued_private().await, Ok(vec![trace, trace]));
+
+    assert_eq!(
+        address.send(Sources).await,
+        Ok(vec![MessageSource::Private, MessageSource::Private])
+    );
+}
+
+#[derive(Clone)]
+struct Transcoder {
+    handled: u32,
+}
+
+impl Actor for Transcoder {}
+
+struct Transcode;
+
+impl Message for Transcode {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Transcode> for Transcoder {
+    async fn handle(&mut self, _message: Transcode, _context: &mut Context<Self>) -> u32 {
+        self.handled += 1;
+
+        self.handled
+    }
+}
+
+struct Replicate;
+
+impl Message for Replicate {
+    type Result = Option<Address<Transcoder>>;
+}
+
+#[async_trait]
+impl Handler<Replicate> for Transcoder {
+    async fn handle(
+        &mut self,
+        _message: Replicate,
+        context: &mut Context<Self>,
+    ) -> Option<Address<Transcoder>> {
+        context.spawn_self()
+    }
+}
+
+#[tokio::test]
+async fn spawns_replicas_from_initial_state() {
+    let address = Transcoder { handled: 0 }.run_replicable();
+
+    assert_eq!(address.send(Transcode).await, Ok(1));
+    assert_eq!(address.send(Transcode).await, Ok(2));
+
+    let replica = address.send(Replicate).await.unwrap().unwrap();
+
+    assert_eq!(replica.send(Transcode).await, Ok(1));
+    assert_eq!(address.send(Transcode).await, Ok(3));
+
+    let unreplicable = Transcoder { handled: 0 }.run();
+    assert!(unreplicable.send(Replicate).await.unwrap().is_none());
+}
+
+struct Tracer {
+    traced: Vec<(&'static str, Option<CorrelationId>)>,
+}
+
+impl Actor for Tracer {}
+
+struct Request(CorrelationId);
+
+impl Message for Request {
+    type Result = ();
+
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        Some(self.0)
+    }
+}
+
+#[async_trait]
+impl Handler<Request> for Tracer {
+    async fn handle(&mut self, _message: Request, context: &mut Context<Self>) {
+        self.traced.push(("request", context.correlation_id()));
+
+        context.notify(FollowUp("notified")).unwrap();
+        context.pipe(async {}, |_| FollowUp("piped"));
+    }
+}
+
+struct FollowUp(&'static str);
+
+impl Message for FollowUp {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<FollowUp> for Tracer {
+    async fn handle(&mut self, message: FollowUp, context: &mut Context<Self>) {
+        self.traced.push((message.0, context.correlation_id()));
+    }
+}
+
+struct Traced;
+
+impl Message for Traced {
+    type Result = Vec<(&'static str, Option<CorrelationId>)>;
+}
+
+#[async_trait]
+impl Handler<Traced> for Tracer {
+    async fn handle(
+        &mut self,
+        _message: Traced,
+        _context: &mut Context<Self>,
+    ) -> Vec<(&'static str, Option<CorrelationId>)> {
+        self.traced.clone()
+    }
+}
+
+#[tokio::test]
+async fn propagates_correlation_ids_to_own_notifications() {
+    let address = Tracer { traced: Vec::new() }.run();
+    let id = CorrelationId::generate();
+
+    address.send(Request(id)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    address.send(FollowUp("unrelated")).await.unwrap();
+
+    assert_eq!(
+        address.send(Traced).await,
+        Ok(vec![
+            ("request", Some(id)),
+            ("notified", Some(id)),
+            ("piped", Some(id)),
+            ("unrelated", None),
+        ])
+    );
+}
+
+impl Validate for Request {
+    fn validate(&self) -> Result<(), ValidationError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn keeps_correlation_ids_of_validated_messages() {
+    let address = Tracer { traced: Vec::new() }.run();
+    let id = CorrelationId::generate();
+
+    assert_eq!(address.send_validated(Request(id)).await, Ok(Ok(())));
+
+    let traced = address.send(Traced).await.unwrap();
+    assert_eq!(traced[0], ("request", Some(id)));
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "deadlock-detection")]
+
+use akt::{Actor, Address, Context, Handler, Message};
+use async_trait::async_trait;
+
+struct Leaf;
+
+impl Actor for Leaf {}
+
+struct Relay {
+    next: Option<Address<Relay>>,
+    leaf: Address<Leaf>,
+}
+
+impl Actor for Relay {}
+
+struct Ping;
+
+impl Message for Ping {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Ping> for Leaf {
+    async fn handle(&mut self, _message: Ping, _context: &mut Context<Self>) -> u32 {
+        1
+    }
+}
+
+#[async_trait]
+impl Handler<Ping> for Relay {
+    async fn handle(&mut self, message: Ping, _context: &mut Context<Self>) -> u32 {
+        let hops = match &self.next {
+            Some(next) => next.send(message).await.unwrap(),
+            None => self.leaf.send(message).await.unwrap(),
+        };
+
+        hops + 1
+    }
+}
+
+#[tokio::test]
+async fn tracks_nested_sends_without_interfering() {
+    let leaf = Leaf.run();
+    let last = Relay {
+        next: None,
+        leaf: leaf.clone(),
+    }
+    .run();
+    let first = Relay {
+        next: Some(last),
+        leaf,
+    }
+    .run();
+
+    assert_eq!(first.send(Ping).await, Ok(3));
+    assert_eq!(first.send(Ping).await, Ok(3));
+}
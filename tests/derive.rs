@@ -0,0 +1,47 @@
+#![cfg(feature = "derive")]
+
+use akt::{Actor, Context, Handler, Message};
+use async_trait::async_trait;
+
+#[derive(Message)]
+#[rtype(result = "Option<u64>")]
+struct Lookup(&'static str);
+
+#[derive(Message)]
+struct Forget;
+
+#[derive(Message)]
+#[rtype(result = "Vec<T>")]
+struct Repeat<T: Clone + Send + 'static>(T, usize);
+
+struct Directory;
+
+impl Actor for Directory {}
+
+#[async_trait]
+impl Handler<Lookup> for Directory {
+    async fn handle(&mut self, message: Lookup, _context: &mut Context<Self>) -> Option<u64> {
+        (message.0 == "alice").then_some(1)
+    }
+}
+
+#[async_trait]
+impl Handler<Forget> for Directory {
+    async fn handle(&mut self, _message: Forget, _context: &mut Context<Self>) {}
+}
+
+#[async_trait]
+impl<T: Clone + Send + 'static> Handler<Repeat<T>> for Directory {
+    async fn handle(&mut self, message: Repeat<T>, _context: &mut Context<Self>) -> Vec<T> {
+        vec![message.0; message.1]
+    }
+}
+
+#[tokio::test]
+async fn derives_message() {
+    let address = Directory.run();
+
+    assert_eq!(address.send(Lookup("alice")).await, Ok(Some(1)));
+    assert_eq!(address.send(Forget).await, Ok(()));
+    assert_eq!(address.send(Repeat('a', 2)).await, Ok(vec!['a', 'a']));
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "error-stack")]
+
+use std::fmt::{self, Display};
+
+use akt::{Actor, ActorResultIntoReport, Context, Handler, Message};
+use async_trait::async_trait;
+use error_stack::{report, Report};
+use tokio::sync::oneshot;
+
+#[derive(Debug)]
+struct LookupError;
+
+impl Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("lookup failed")
+    }
+}
+
+impl error_stack::Context for LookupError {}
+
+#[derive(Debug)]
+struct AppError;
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("app failed")
+    }
+}
+
+impl error_stack::Context for AppError {}
+
+struct Directory;
+
+impl Actor for Directory {}
+
+struct Lookup(u32);
+
+type Reply = oneshot::Receiver<error_stack::Result<u32, LookupError>>;
+
+impl Message for Lookup {
+    type Result = Result<Reply, Report<LookupError>>;
+}
+
+#[async_trait]
+impl Handler<Lookup> for Directory {
+    async fn handle(
+        &mut self,
+        message: Lookup,
+        _context: &mut Context<Self>,
+    ) -> Result<Reply, Report<LookupError>> {
+        if message.0 == 0 {
+            return Err(report!(LookupError));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let _ = tx.send(Ok(message.0 * 2));
+
+        Ok(rx)
+    }
+}
+
+#[tokio::test]
+async fn changes_context_of_unpacked_results() {
+    let address = Directory.run();
+
+    let found = address
+        .send_unpack_flat(Lookup(21))
+        .await
+        .change_context(AppError);
+    assert_eq!(found.unwrap(), 42);
+
+    let missing = address
+        .send_unpack_flat(Lookup(0))
+        .await
+        .change_context(AppError);
+    let report = missing.unwrap_err();
+    assert!(report.contains::<LookupError>());
+}
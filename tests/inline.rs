@@ -0,0 +1,61 @@
+use akt::{Actor, Context, InlineHandler, Message, PanicReason};
+use async_trait::async_trait;
+
+#[derive(Default)]
+struct Tally {
+    total: u64,
+    panics: usize,
+}
+
+#[async_trait]
+impl Actor for Tally {
+    async fn on_panic(&mut self, _reason: PanicReason, _context: &mut Context<Self>) {
+        self.panics += 1;
+    }
+}
+
+struct Add(u64);
+
+impl Message for Add {
+    type Result = u64;
+}
+
+impl InlineHandler<Add> for Tally {
+    fn handle(&mut self, message: Add, _context: &mut Context<Self>) -> u64 {
+        if message.0 == 0 {
+            panic!("nothing to add");
+        }
+
+        self.total += message.0;
+        self.total
+    }
+}
+
+struct Panics;
+
+impl Message for Panics {
+    type Result = usize;
+}
+
+impl InlineHandler<Panics> for Tally {
+    fn handle(&mut self, _message: Panics, _context: &mut Context<Self>) -> usize {
+        self.panics
+    }
+}
+
+#[tokio::test]
+async fn handles_messages_inline() {
+    let address = Tally::default().run();
+
+    address.notify_inline(Add(2)).await.unwrap();
+    assert_eq!(address.send_inline(Add(3)).await, Ok(5));
+}
+
+#[tokio::test]
+async fn reports_inline_panics() {
+    let address = Tally::default().run();
+
+    assert!(address.send_inline(Add(0)).await.is_err());
+    assert_eq!(address.send_inline(Panics).await, Ok(1));
+    assert_eq!(address.send_inline(Add(1)).await, Ok(1));
+}
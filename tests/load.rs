@@ -0,0 +1,48 @@
+#![cfg(feature = "load")]
+
+use std::time::Duration;
+
+use akt::{Actor, Context, Handler, Load, Message};
+use async_trait::async_trait;
+
+struct Echo;
+
+impl Actor for Echo {}
+
+struct Ping;
+
+impl Message for Ping {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Ping> for Echo {
+    async fn handle(&mut self, _message: Ping, _context: &mut Context<Self>) {}
+}
+
+#[tokio::test]
+async fn reports_latencies() {
+    let report = Load::new(Echo.run())
+        .message(3, || Ping)
+        .message(1, || Ping)
+        .rate(200)
+        .duration(Duration::from_millis(50))
+        .run()
+        .await;
+
+    assert!(report.handled() > 0);
+    assert_eq!(report.failed(), 0);
+    assert!(report.percentile(50.0).is_some());
+}
+
+#[tokio::test]
+async fn clamps_rates_above_one_per_nanosecond() {
+    let report = Load::new(Echo.run())
+        .message(1, || Ping)
+        .rate(u32::MAX)
+        .duration(Duration::ZERO)
+        .run()
+        .await;
+
+    assert_eq!(report.failed(), 0);
+}
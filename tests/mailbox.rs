@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+
+use akt::{Actor, Context, Handler, Letter, Mailbox, Message};
+use async_trait::async_trait;
+
+/// Keeps only the most recent letters
+struct Ring<A: Actor> {
+    letters: VecDeque<Letter<A>>,
+    capacity: usize,
+}
+
+impl<A: Actor> Mailbox<A> for Ring<A> {
+    fn push(&mut self, letter: Letter<A>) {
+        if self.letters.len() == self.capacity {
+            self.letters.pop_front();
+        }
+
+        self.letters.push_back(letter);
+    }
+
+    fn pop(&mut self) -> Option<Letter<A>> {
+        self.letters.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.letters.len()
+    }
+}
+
+struct Telemetry {
+    samples: Vec<u32>,
+}
+
+impl Actor for Telemetry {}
+
+struct Sample(u32);
+
+impl Message for Sample {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Sample> for Telemetry {
+    async fn handle(&mut self, message: Sample, _context: &mut Context<Self>) {
+        self.samples.push(message.0);
+    }
+}
+
+struct Samples;
+
+impl Message for Samples {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Samples> for Telemetry {
+    async fn handle(&mut self, _message: Samples, _context: &mut Context<Self>) -> Vec<u32> {
+        self.samples.clone()
+    }
+}
+
+#[tokio::test]
+async fn drops_letters_overwritten_by_custom_mailbox() {
+    let address = Telemetry {
+        samples: Vec::new(),
+    }
+    .run_with_mailbox(Ring {
+        letters: VecDeque::new(),
+        capacity: 2,
+    });
+
+    // Nothing is handled before the first await
+    for sample in 1..=5 {
+        address.try_notify(Sample(sample)).unwrap();
+    }
+
+    assert_eq!(address.send(Samples).await, Ok(vec![5]));
+
+    address.notify(Sample(6)).await.unwrap();
+
+    assert_eq!(address.send(Samples).await, Ok(vec![5, 6]));
+}
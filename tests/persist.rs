@@ -0,0 +1,252 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use akt::{
+    Actor, ActorSendError, ActorState, Codec, Context, DecodeError, Handler, Journal, Message,
+};
+use async_trait::async_trait;
+use tokio::time::sleep;
+
+#[derive(Default)]
+struct Log {
+    entries: Mutex<Vec<(u64, Vec<u8>)>>,
+    next_id: Mutex<u64>,
+}
+
+#[async_trait]
+impl Journal for Log {
+    async fn persist(&self, _tag: &'static str, bytes: Vec<u8>) -> u64 {
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        self.entries.lock().unwrap().push((*next_id, bytes));
+
+        *next_id
+    }
+
+    async fn acknowledge(&self, id: u64) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(entry, _)| *entry != id);
+    }
+}
+
+struct Queue {
+    log: Arc<Log>,
+    done: Vec<String>,
+}
+
+impl Queue {
+    fn new(log: &Arc<Log>) -> Queue {
+        Queue {
+            log: log.clone(),
+            done: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Actor for Queue {
+    async fn recover(&mut self, context: &mut Context<Self>) {
+        let entries = std::mem::take(&mut *self.log.entries.lock().unwrap());
+
+        for (_, bytes) in entries {
+            context.notify(Job::decode(&bytes).unwrap()).unwrap();
+        }
+    }
+
+    fn journal(&self) -> Option<Arc<dyn Journal>> {
+        Some(self.log.clone())
+    }
+}
+
+struct Job(String);
+
+impl Message for Job {
+    type Result = usize;
+}
+
+impl Codec for Job {
+    fn encode(&self) -> Vec<u8> {
+        self.0.encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        String::decode(bytes).map(Job)
+    }
+}
+
+#[async_trait]
+impl Handler<Job> for Queue {
+    // Responds with the number of entries persisted while handling
+    async fn handle(&mut self, message: Job, _context: &mut Context<Self>) -> usize {
+        self.done.push(message.0);
+
+        self.log.entries.lock().unwrap().len()
+    }
+}
+
+struct Stall;
+
+impl Message for Stall {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Stall> for Queue {
+    async fn handle(&mut self, _message: Stall, _context: &mut Context<Self>) {
+        sleep(Duration::from_secs(3600)).await;
+    }
+}
+
+struct Done;
+
+impl Message for Done {
+    type Result = Vec<String>;
+}
+
+#[async_trait]
+impl Handler<Done> for Queue {
+    async fn handle(&mut self, _message: Done, _context: &mut Context<Self>) -> Vec<String> {
+        self.done.clone()
+    }
+}
+
+#[tokio::test]
+async fn persists_messages_until_handled() {
+    let log = Arc::new(Log::default());
+    let address = Queue::new(&log).run();
+    address.wait_for_state(ActorState::Started).await;
+
+    assert_eq!(address.send_persisted(Job("a".into())).await, Ok(1));
+    assert_eq!(address.send(Job("b".into())).await, Ok(0));
+    address.notify_persisted(Job("c".into())).await.unwrap();
+
+    assert_eq!(address.send(Done).await.unwrap(), vec!["a", "b", "c"]);
+    assert!(log.entries.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn recovers_unacknowledged_messages() {
+    let log = Arc::new(Log::default());
+    log.entries
+        .lock()
+        .unwrap()
+        .push((7, Job("lost".into()).encode()));
+
+    let address = Queue::new(&log).run();
+
+    assert_eq!(address.send(Done).await.unwrap(), vec!["lost"]);
+}
+
+#[tokio::test]
+async fn replays_messages_queued_behind_a_crash() {
+    let log = Arc::new(Log::default());
+    let (address, abort) = Queue::new(&log).run_abortable();
+    address.wait_for_state(ActorState::Started).await;
+
+    address.notify(Stall).await.unwrap();
+    address.notify_persisted(Job("a".into())).await.unwrap();
+    address.notify_persisted(Job("b".into())).await.unwrap();
+
+    abort.abort();
+
+    assert_eq!(log.entries.lock().unwrap().len(), 2);
+
+    let address = Queue::new(&log).run();
+
+    assert_eq!(address.send(Done).await.unwrap(), vec!["a", "b"]);
+}
+
+#[tokio::test]
+async fn acknowledges_undelivered_messages() {
+    let log = Arc::new(Log::default());
+    let address = Queue::new(&log).run();
+
+    address.stop().await.unwrap();
+    address.stopped().await;
+
+    assert_eq!(
+        address.send_persisted(Job("a".into())).await,
+        Err(ActorSendError::FailedToDeliver)
+    );
+    assert!(address.notify_persisted(Job("b".into())).await.is_err());
+    assert!(log.entries.lock().unwrap().is_empty());
+}
+
+struct Postpone;
+
+impl Message for Postpone {
+    type Result = usize;
+}
+
+impl Codec for Postpone {
+    fn encode(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn decode(_bytes: &[u8]) -> Result<Self, DecodeError> {
+        Ok(Postpone)
+    }
+}
+
+#[async_trait]
+impl Handler<Postpone> for Queue {
+    async fn handle(&mut self, _message: Postpone, context: &mut Context<Self>) -> usize {
+        let reply = context.take_reply::<Postpone>().unwrap();
+
+        tokio::spawn(async move {
+            let _ = reply.send(42);
+        });
+
+        0
+    }
+}
+
+#[tokio::test]
+async fn replies_later_to_persisted_messages() {
+    let log = Arc::new(Log::default());
+    let address = Queue::new(&log).run();
+    address.wait_for_state(ActorState::Started).await;
+
+    assert_eq!(address.send_persisted(Postpone).await, Ok(42));
+}
+
+struct Gate {
+    log: Arc<Log>,
+}
+
+#[async_trait]
+impl Actor for Gate {
+    const REJECT_UNTIL_STARTED: bool = true;
+
+    async fn on_start(&mut self, _context: &mut Context<Self>) {
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    fn journal(&self) -> Option<Arc<dyn Journal>> {
+        Some(self.log.clone())
+    }
+}
+
+#[async_trait]
+impl Handler<Job> for Gate {
+    async fn handle(&mut self, message: Job, _context: &mut Context<Self>) -> usize {
+        message.0.len()
+    }
+}
+
+#[tokio::test]
+async fn acknowledges_rejected_messages() {
+    let log = Arc::new(Log::default());
+    let address = Gate { log: log.clone() }.run();
+
+    assert_eq!(
+        address.send_persisted(Job("early".to_string())).await,
+        Err(ActorSendError::NotReady)
+    );
+    assert!(log.entries.lock().unwrap().is_empty());
+    assert_eq!(address.send_persisted(Job("late".to_string())).await, Ok(4));
+}
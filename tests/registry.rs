@@ -0,0 +1,52 @@
+use akt::{registry, Actor, Context, Handler, Message};
+use async_trait::async_trait;
+
+struct Service;
+
+impl Actor for Service {
+    fn name(&self) -> Option<&str> {
+        Some("service")
+    }
+}
+
+struct Ping;
+
+impl Message for Ping {
+    type Result = &'static str;
+}
+
+#[async_trait]
+impl Handler<Ping> for Service {
+    async fn handle(&mut self, _message: Ping, _context: &mut Context<Self>) -> &'static str {
+        "pong"
+    }
+}
+
+struct Other;
+
+impl Actor for Other {}
+
+#[tokio::test]
+async fn looks_up_named_actors() {
+    let address = Service.run();
+
+    let found = registry().get::<Service>("service").unwrap();
+    assert_eq!(found.send(Ping).await, Ok("pong"));
+
+    // Names are scoped by actor type
+    assert!(registry().get::<Other>("service").is_none());
+
+    let other = Other.run();
+    registry().register_named("service", &other);
+    assert!(registry().get::<Other>("service").is_some());
+
+    assert!(registry().unregister::<Other>("service"));
+    assert!(registry().get::<Other>("service").is_none());
+
+    // Registry doesn't keep actors alive
+    drop(found);
+    drop(address);
+    tokio::task::yield_now().await;
+
+    assert!(registry().get::<Service>("service").is_none());
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "remote")]
+
+use akt::{
+    Actor, Codec, Context, DecodeError, Handler, Message, RemoteAddress, RemoteError, RemoteMessage,
+};
+use async_trait::async_trait;
+use std::convert::TryInto;
+
+struct Inventory {
+    stock: u32,
+}
+
+impl Actor for Inventory {}
+
+struct Reserve(u32);
+
+impl Message for Reserve {
+    type Result = Remaining;
+}
+
+impl Codec for Reserve {
+    fn encode(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| DecodeError::new("expected 4 bytes"))?;
+
+        Ok(Reserve(u32::from_le_bytes(bytes)))
+    }
+}
+
+impl RemoteMessage for Reserve {
+    const TAG: &'static str = "reserve";
+}
+
+#[derive(Debug, PartialEq)]
+struct Remaining(u32);
+
+impl Codec for Remaining {
+    fn encode(&self) -> Vec<u8> {
+        Reserve(self.0).encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Reserve::decode(bytes).map(|reserve| Remaining(reserve.0))
+    }
+}
+
+#[async_trait]
+impl Handler<Reserve> for Inventory {
+    async fn handle(&mut self, message: Reserve, _context: &mut Context<Self>) -> Remaining {
+        self.stock -= message.0;
+
+        Remaining(self.stock)
+    }
+}
+
+#[tokio::test]
+async fn round_trips_messages_through_bytes() {
+    let remote = RemoteAddress::new(Inventory { stock: 10 }.run()).route::<Reserve>();
+
+    assert_eq!(remote.send(Reserve(3)).await, Ok(Remaining(7)));
+
+    let response = remote.send_bytes("reserve", Reserve(2).encode()).await;
+    assert_eq!(response, Ok(Remaining(5).encode()));
+}
+
+#[tokio::test]
+async fn reports_unroutable_bytes() {
+    let remote = RemoteAddress::new(Inventory { stock: 10 }.run()).route::<Reserve>();
+
+    assert_eq!(
+        remote.send_bytes("restock", Vec::new()).await,
+        Err(RemoteError::UnknownMessage("restock".to_string()))
+    );
+    assert_eq!(
+        remote.send_bytes("reserve", vec![1]).await,
+        Err(RemoteError::Decode(DecodeError::new("expected 4 bytes")))
+    );
+}
+
+struct Discreet {
+    stock: u32,
+}
+
+impl Actor for Discreet {
+    // Never reveals more than a few items in stock
+    fn map_response(&mut self, response: &mut dyn std::any::Any, _context: &mut Context<Self>) {
+        if let Some(remaining) = response.downcast_mut::<Remaining>() {
+            remaining.0 = remaining.0.min(5);
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Reserve> for Discreet {
+    async fn handle(&mut self, message: Reserve, _context: &mut Context<Self>) -> Remaining {
+        self.stock -= message.0;
+
+        Remaining(self.stock)
+    }
+}
+
+#[tokio::test]
+async fn maps_responses_before_encoding_them() {
+    let remote = RemoteAddress::new(Discreet { stock: 10 }.run()).route::<Reserve>();
+
+    assert_eq!(remote.send(Reserve(1)).await, Ok(Remaining(5)));
+}
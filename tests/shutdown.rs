@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use akt::{Actor, Context, ShutdownCoordinator, ShutdownOrder};
+use async_trait::async_trait;
+
+struct Service {
+    name: &'static str,
+    coordinator: ShutdownCoordinator,
+    stopped: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Service {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        context.register_for_shutdown(&self.coordinator);
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.stopped.lock().unwrap().push(self.name);
+    }
+}
+
+#[tokio::test]
+async fn shuts_down_in_reverse_order() {
+    let coordinator = ShutdownCoordinator::new();
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let mut addresses = Vec::new();
+
+    for name in ["database", "cache", "api"] {
+        let (address, ready) = Service {
+            name,
+            coordinator: coordinator.clone(),
+            stopped: stopped.clone(),
+        }
+        .run_ready();
+
+        ready.await.unwrap();
+        addresses.push(address);
+    }
+
+    assert_eq!(coordinator.len(), 3);
+
+    coordinator.shutdown_ordered(ShutdownOrder::Reverse).await;
+
+    assert_eq!(*stopped.lock().unwrap(), ["api", "cache", "database"]);
+    assert!(addresses.iter().all(|address| address.is_closed()));
+    assert!(coordinator.is_empty());
+}
+
+#[tokio::test]
+async fn forgets_actors_stopped_on_their_own() {
+    let coordinator = ShutdownCoordinator::new();
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+
+    let (address, handle) = Service {
+        name: "worker",
+        coordinator: coordinator.clone(),
+        stopped: stopped.clone(),
+    }
+    .run_with_handle();
+
+    tokio::task::yield_now().await;
+    assert_eq!(coordinator.len(), 1);
+
+    drop(address);
+    handle.await.unwrap();
+
+    assert!(coordinator.is_empty());
+    coordinator.shutdown().await;
+    assert_eq!(*stopped.lock().unwrap(), ["worker"]);
+}
+
+struct Component {
+    name: &'static str,
+    depends_on: &'static [&'static str],
+    coordinator: ShutdownCoordinator,
+    stopped: Arc<Mutex<Vec<&'static str>>>,
+}
+
+#[async_trait]
+impl Actor for Component {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        context.register_for_shutdown_named(&self.coordinator, self.name, self.depends_on);
+    }
+
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.stopped.lock().unwrap().push(self.name);
+    }
+}
+
+#[tokio::test]
+async fn shuts_down_dependents_before_dependencies() {
+    let coordinator = ShutdownCoordinator::new();
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let mut addresses = Vec::new();
+
+    let components: [(&str, &[&str]); 4] = [
+        ("writer", &[]),
+        ("buffer", &["writer"]),
+        ("ingest", &["buffer"]),
+        ("metrics", &[]),
+    ];
+
+    for (name, depends_on) in components {
+        let (address, ready) = Component {
+            name,
+            depends_on,
+            coordinator: coordinator.clone(),
+            stopped: stopped.clone(),
+        }
+        .run_ready();
+
+        ready.await.unwrap();
+        addresses.push(address);
+    }
+
+    coordinator
+        .shutdown_ordered(ShutdownOrder::Dependencies)
+        .await;
+
+    assert_eq!(
+        *stopped.lock().unwrap(),
+        ["ingest", "buffer", "writer", "metrics"]
+    );
+    assert!(addresses.iter().all(|address| address.is_closed()));
+}
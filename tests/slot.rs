@@ -0,0 +1,76 @@
+use akt::{Actor, AddressSlot, Context, Handler, Message};
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+struct Item(u32);
+
+impl Message for Item {
+    type Result = ();
+}
+
+struct Source {
+    next: AddressSlot<Double>,
+}
+
+impl Actor for Source {}
+
+#[async_trait]
+impl Handler<Item> for Source {
+    async fn handle(&mut self, message: Item, _context: &mut Context<Self>) {
+        let _ = self.next.get().await.notify(message).await;
+    }
+}
+
+struct Double {
+    next: AddressSlot<Sink>,
+}
+
+impl Actor for Double {}
+
+#[async_trait]
+impl Handler<Item> for Double {
+    async fn handle(&mut self, message: Item, _context: &mut Context<Self>) {
+        let _ = self.next.get().await.notify(Item(message.0 * 2)).await;
+    }
+}
+
+struct Sink {
+    out: Option<oneshot::Sender<u32>>,
+}
+
+impl Actor for Sink {}
+
+#[async_trait]
+impl Handler<Item> for Sink {
+    async fn handle(&mut self, message: Item, _context: &mut Context<Self>) {
+        if let Some(out) = self.out.take() {
+            let _ = out.send(message.0);
+        }
+    }
+}
+
+#[tokio::test]
+async fn wires_pipeline_through_slots() {
+    let double_slot = AddressSlot::new();
+    let sink_slot = AddressSlot::new();
+    let (out_tx, out_rx) = oneshot::channel();
+
+    let source = Source {
+        next: double_slot.clone(),
+    }
+    .run();
+
+    source.notify(Item(21)).await.unwrap();
+
+    assert!(double_slot
+        .fill(
+            Double {
+                next: sink_slot.clone()
+            }
+            .run()
+        )
+        .is_ok());
+    assert!(sink_slot.fill(Sink { out: Some(out_tx) }.run()).is_ok());
+
+    assert_eq!(out_rx.await, Ok(42));
+}
@@ -0,0 +1,306 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use akt::{Actor, Context, Handler, Message, StreamExt, StreamHandler};
+use async_trait::async_trait;
+use tokio::{
+    sync::{broadcast, mpsc, oneshot, Semaphore},
+    time::{sleep, timeout},
+};
+
+struct Collector {
+    items: Vec<u32>,
+    done: Option<oneshot::Sender<Vec<u32>>>,
+    stream: Option<mpsc::Receiver<u32>>,
+}
+
+#[async_trait]
+impl Actor for Collector {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        if let Some(stream) = self.stream.take() {
+            context.add_stream(stream);
+        }
+    }
+}
+
+#[async_trait]
+impl StreamHandler<u32> for Collector {
+    async fn handle(&mut self, item: u32, _context: &mut Context<Self>) {
+        self.items.push(item);
+    }
+
+    async fn finished(&mut self, _context: &mut Context<Self>) {
+        if let Some(done) = self.done.take() {
+            let _ = done.send(self.items.clone());
+        }
+    }
+}
+
+#[tokio::test]
+async fn handles_stream_items_in_order() {
+    let (tx, rx) = mpsc::channel(4);
+    let (done_tx, done_rx) = oneshot::channel();
+
+    let _address = Collector {
+        items: Vec::new(),
+        done: Some(done_tx),
+        stream: Some(rx),
+    }
+    .run();
+
+    for i in 0..3 {
+        tx.send(i).await.unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(done_rx.await.unwrap(), vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn stops_polling_stream_once_actor_stops() {
+    let (tx, rx) = mpsc::channel::<u32>(4);
+
+    let address = Collector {
+        items: Vec::new(),
+        done: None,
+        stream: Some(rx),
+    }
+    .run();
+
+    address.stop().await.unwrap();
+    address.stopped().await;
+
+    // The forwarding task drops the stream as it exits
+    assert!(timeout(Duration::from_secs(1), tx.closed()).await.is_ok());
+}
+
+struct Throttled {
+    items: Vec<u32>,
+    permits: Arc<Semaphore>,
+    stream: Option<mpsc::Receiver<u32>>,
+}
+
+#[async_trait]
+impl Actor for Throttled {
+    const PRIVATE_MAILBOX_CAPACITY: Option<usize> = Some(1);
+
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        if let Some(stream) = self.stream.take() {
+            context.add_stream(stream);
+        }
+    }
+}
+
+#[async_trait]
+impl StreamHandler<u32> for Throttled {
+    async fn handle(&mut self, item: u32, _context: &mut Context<Self>) {
+        self.permits.acquire().await.unwrap().forget();
+        self.items.push(item);
+    }
+}
+
+#[tokio::test]
+async fn pulls_stream_items_once_mailbox_has_room() {
+    let (tx, rx) = mpsc::channel(1);
+    let permits = Arc::new(Semaphore::new(0));
+    let sent = Arc::new(AtomicUsize::new(0));
+
+    let address = Throttled {
+        items: Vec::new(),
+        permits: permits.clone(),
+        stream: Some(rx),
+    }
+    .run();
+
+    let producer = tokio::spawn({
+        let sent = sent.clone();
+
+        async move {
+            for i in 0..10 {
+                tx.send(i).await.unwrap();
+                sent.fetch_add(1, Ordering::AcqRel);
+            }
+        }
+    });
+
+    sleep(Duration::from_millis(20)).await;
+
+    // One item being handled, one queued, one pulled and one buffered
+    assert_eq!(sent.load(Ordering::Acquire), 4);
+
+    permits.add_permits(10);
+    producer.await.unwrap();
+    sleep(Duration::from_millis(20)).await;
+
+    let items = address.with_state(|actor| actor.items.clone()).await;
+
+    assert_eq!(items, Ok((0..10).collect::<Vec<_>>()));
+}
+
+struct Item(u32);
+
+impl Message for Item {
+    type Result = ();
+}
+
+#[async_trait]
+impl Handler<Item> for Collector {
+    async fn handle(&mut self, item: Item, _context: &mut Context<Self>) {
+        self.items.push(item.0);
+    }
+}
+
+struct Items;
+
+impl Message for Items {
+    type Result = Vec<u32>;
+}
+
+#[async_trait]
+impl Handler<Items> for Collector {
+    async fn handle(&mut self, _message: Items, _context: &mut Context<Self>) -> Vec<u32> {
+        self.items.clone()
+    }
+}
+
+#[tokio::test]
+async fn forwards_stream_into_address() {
+    let (tx, rx) = mpsc::channel(4);
+
+    let address = Collector {
+        items: Vec::new(),
+        done: None,
+        stream: None,
+    }
+    .run_with_capacity(1);
+
+    tokio::spawn(async move {
+        for i in 0..5 {
+            tx.send(Item(i)).await.unwrap();
+        }
+    });
+
+    rx.forward(address.clone().into_sink()).await.unwrap();
+
+    // Closing the sink leaves the actor running
+    assert_eq!(address.send(Items).await, Ok(vec![0, 1, 2, 3, 4]));
+}
+
+struct Subscriber {
+    items: Vec<u32>,
+    skipped: u64,
+    done: Option<oneshot::Sender<(Vec<u32>, u64)>>,
+    rx: Option<broadcast::Receiver<u32>>,
+}
+
+#[async_trait]
+impl Actor for Subscriber {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        if let Some(rx) = self.rx.take() {
+            context.add_broadcast_stream(rx);
+        }
+    }
+}
+
+#[async_trait]
+impl StreamHandler<u32> for Subscriber {
+    async fn handle(&mut self, item: u32, _context: &mut Context<Self>) {
+        self.items.push(item);
+    }
+
+    async fn finished(&mut self, _context: &mut Context<Self>) {
+        if let Some(done) = self.done.take() {
+            let _ = done.send((self.items.clone(), self.skipped));
+        }
+    }
+
+    async fn lagged(&mut self, skipped: u64, _context: &mut Context<Self>) {
+        self.skipped += skipped;
+    }
+}
+
+#[tokio::test]
+async fn reports_lagging_behind_broadcast() {
+    let (tx, rx) = broadcast::channel(2);
+    let (done_tx, done_rx) = oneshot::channel();
+
+    let _address = Subscriber {
+        items: Vec::new(),
+        skipped: 0,
+        done: Some(done_tx),
+        rx: Some(rx),
+    }
+    .run();
+
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+
+    drop(tx);
+
+    assert_eq!(done_rx.await.unwrap(), (vec![2, 3], 2));
+}
+
+struct Aggregator {
+    numbers: Option<mpsc::Receiver<u32>>,
+    words: Option<mpsc::Receiver<&'static str>>,
+    items: Vec<u32>,
+}
+
+#[async_trait]
+impl Actor for Aggregator {
+    async fn on_start(&mut self, context: &mut Context<Self>) {
+        if let Some(numbers) = self.numbers.take() {
+            context.add_stream_mapped(numbers, Item);
+        }
+
+        if let Some(words) = self.words.take() {
+            context.add_stream_mapped(words, |word| Item(word.len() as u32));
+        }
+    }
+}
+
+#[async_trait]
+impl Handler<Item> for Aggregator {
+    async fn handle(&mut self, item: Item, _context: &mut Context<Self>) {
+        self.items.push(item.0);
+    }
+}
+
+#[async_trait]
+impl Handler<Items> for Aggregator {
+    async fn handle(&mut self, _message: Items, _context: &mut Context<Self>) -> Vec<u32> {
+        self.items.clone()
+    }
+}
+
+#[tokio::test]
+async fn merges_mapped_streams() {
+    let (numbers_tx, numbers) = mpsc::channel(4);
+    let (words_tx, words) = mpsc::channel(4);
+
+    numbers_tx.send(1).await.unwrap();
+    numbers_tx.send(2).await.unwrap();
+    words_tx.send("three").await.unwrap();
+    drop((numbers_tx, words_tx));
+
+    let address = Aggregator {
+        numbers: Some(numbers),
+        words: Some(words),
+        items: Vec::new(),
+    }
+    .run();
+
+    sleep(Duration::from_millis(10)).await;
+
+    let mut items = address.send(Items).await.unwrap();
+    items.sort_unstable();
+
+    assert_eq!(items, vec![1, 2, 5]);
+}
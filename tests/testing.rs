@@ -0,0 +1,82 @@
+#![cfg(feature = "testing")]
+
+use akt::{Actor, Context, Handler, Message, MockContext, TestActor};
+use async_trait::async_trait;
+
+struct Account {
+    balance: u32,
+    stopped: bool,
+}
+
+#[async_trait]
+impl Actor for Account {
+    async fn on_stopped(&mut self, _context: &mut Context<Self>) {
+        self.stopped = true;
+    }
+}
+
+struct Deposit(u32);
+
+impl Message for Deposit {
+    type Result = u32;
+}
+
+#[async_trait]
+impl Handler<Deposit> for Account {
+    async fn handle(&mut self, message: Deposit, context: &mut Context<Self>) -> u32 {
+        self.balance += message.0;
+
+        // Bonus for large deposits goes through the private mailbox
+        if message.0 >= 100 {
+            context.notify(Deposit(1)).unwrap();
+        }
+
+        self.balance
+    }
+}
+
+#[tokio::test]
+async fn steps_through_messages() {
+    let mut account = TestActor::start(Account {
+        balance: 0,
+        stopped: false,
+    })
+    .await
+    .unwrap();
+
+    let address = account.address();
+
+    let first = address.ask(Deposit(10)).await.unwrap();
+    address.notify(Deposit(100)).await.unwrap();
+
+    assert!(account.step().await);
+    assert_eq!(account.actor().balance, 10);
+    assert_eq!(first.await, Ok(10));
+
+    assert!(account.step().await);
+    assert_eq!(account.actor().balance, 110);
+
+    // The bonus is handled last
+    assert_eq!(account.step_all().await, 1);
+    assert_eq!(account.actor().balance, 111);
+    assert!(!account.step().await);
+
+    let account = account.stop().await;
+    assert!(account.stopped);
+}
+
+#[tokio::test]
+async fn handles_messages_with_mock_context() {
+    let mut account = Account {
+        balance: 0,
+        stopped: false,
+    };
+    let mut context = MockContext::new();
+
+    assert_eq!(account.handle(Deposit(10), &mut context).await, 10);
+    assert!(context.drain_queued().is_empty());
+
+    assert_eq!(account.handle(Deposit(100), &mut context).await, 110);
+    assert_eq!(context.drain_queued(), [std::any::type_name::<Deposit>()]);
+    assert!(!context.is_stopping());
+}
@@ -0,0 +1,51 @@
+#![cfg(all(feature = "unstable", feature = "testing"))]
+
+use akt::{Actor, Address, Context, Envelope, Handler, Message, MockContext};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+struct Shim {
+    pings: usize,
+}
+
+impl Actor for Shim {}
+
+struct Ping;
+
+impl Message for Ping {
+    type Result = usize;
+}
+
+#[async_trait]
+impl Handler<Ping> for Shim {
+    async fn handle(&mut self, _message: Ping, _context: &mut Context<Self>) -> usize {
+        self.pings += 1;
+        self.pings
+    }
+}
+
+#[tokio::test]
+async fn builds_address_from_own_channel() {
+    let (tx, mut rx) = mpsc::channel::<Box<dyn Envelope<Shim> + Send>>(4);
+    let address = Address::<Shim>::from_sender(tx);
+
+    // Hand-rolled loop handling the envelopes with a mock context
+    let shim = tokio::spawn(async move {
+        let mut shim = Shim { pings: 0 };
+        let mut context = MockContext::new();
+
+        while let Some(envelope) = rx.recv().await {
+            envelope.handle(&mut shim, &mut context).await;
+        }
+
+        shim
+    });
+
+    address.notify(Ping).await.unwrap();
+    assert_eq!(address.send(Ping).await, Ok(2));
+    assert_eq!(address.sender().max_capacity(), 4);
+
+    drop(address);
+
+    assert_eq!(shim.await.unwrap().pings, 2);
+}